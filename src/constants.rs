@@ -22,6 +22,12 @@ pub const TIME_ID: usize = 0;
 /// The threshold of Salability which turns a good into a Money.
 pub const MONEY_SALABILITY_THRESHOLD: f64 = 0.9;
 
+// NOTE: Desire Constants
+
+/// How many steps of a desire without an end count towards it's normalized
+/// satisfaction, if it's demand curve never reaches 0.0.
+pub const NORMALIZED_SATISFACTION_HORIZON: f64 = 100.0;
+
 // NOTE: Pop Constants
 
 /// The minimum size a want can take in storage. Anything less than this should decay to 0.0.
@@ -39,4 +45,80 @@ pub const ADULT_LABOR_EFFICIENCY: f64 = 1.0;
 /// Per market day, how many days of labor children are good for.
 pub const CHILDREN_LABOR_EFFICIENCY: f64 = 0.3;
 /// Per market day, how many days of labor Elders are good for.
-pub const ELDER_LABOR_EFFICIENCY: f64 = 0.5;
\ No newline at end of file
+pub const ELDER_LABOR_EFFICIENCY: f64 = 0.5;
+// NOTE: Habituation Constants
+
+/// How many days of satisfaction a pop remembers for a habituating desire.
+/// 
+/// Habituating desires cannot ask for more days than this.
+pub const HABIT_MEMORY: usize = 32;
+
+/// How satisfied (0.0 to 1.0) a habituating desire needs to be each day to 
+/// count towards raising it.
+pub const HABIT_SATISFIED_THRESHOLD: f64 = 0.9;
+
+/// How satisfied (0.0 to 1.0) a habituating desire can be each day to count 
+/// towards lowering it.
+pub const HABIT_DEPRIVED_THRESHOLD: f64 = 0.5;
+
+/// The lowest a habit can reduce a desire's amount, relative to it's baseline.
+pub const HABIT_MINIMUM_MULTIPLIER: f64 = 0.5;
+
+/// The highest a habit can raise a desire's amount, relative to it's baseline.
+pub const HABIT_MAXIMUM_MULTIPLIER: f64 = 4.0;
+
+// NOTE: Positional Constants
+
+/// The lowest per person reference a positional desire can be scaled to, 
+/// so that no one holding anything doesn't zero out the desire.
+pub const POSITIONAL_MINIMUM_REFERENCE: f64 = 0.01;
+
+// NOTE: Free Time Constants
+
+/// How much time (in units of TIME_ID) a pop spends on each thing it plans
+/// or does for the community in it's free time.
+pub const POP_PLANNING_TIME: f64 = 1.0;
+
+// NOTE: Savings Constants
+
+/// How many tiers of savings efficiency (salability * durability) non-money
+/// goods are sorted into when a pop builds it's savings.
+pub const SAVINGS_TIERS: usize = 10;
+
+// NOTE: Financial Mood Constants
+
+/// How far (0.0 to 1.0) a pop's financial mood moves towards it's target each
+/// day.
+pub const MOOD_ADJUSTMENT_RATE: f64 = 0.1;
+
+/// The portion (0.0 to 1.0) of it's AMV a pop seeks to save by default, 
+/// before it's mood moves it.
+pub const POP_BASE_SAVING_RATE: f64 = 0.1;
+
+/// The most (0.0 to 1.0) a pop's saving rate can rise to by default.
+pub const POP_SAVING_RATE_CAP: f64 = 0.5;
+
+// NOTE: Price Expectation Constants
+
+/// How far (as a fraction of the market price) a pop's starting price 
+/// expectations are typically allowed to be from the market price, when it's
+/// given noise. See PriceExpectations::with_noise().
+pub const PRICE_EXPECTATION_NOISE: f64 = 0.05;
+
+/// How far (0.0 to 1.0) a pop's price expectations move towards each price 
+/// it sees by default.
+pub const PRICE_EXPECTATION_RATE: f64 = 0.25;
+
+// NOTE: Population Growth Constants
+
+/// How many days household birth and mortality rates are measured over, so 
+/// each turn applies DAYS_PER_TURN / POP_GROWTH_PERIOD of them.
+pub const POP_GROWTH_PERIOD: f64 = 400.0;
+
+/// How much (as a fraction) a pop's population has to change from what it's
+/// desires were built for before they are rebuilt.
+pub const POP_DESIRE_REBUILD_THRESHOLD: f64 = 0.01;
+
+/// How much desire satisfaction shifts births for species with cohort aging 
+/// by default, see CohortAging::birth_multiplier().
+pub const COHORT_BIRTH_SENSITIVITY: f64 = 0.5;
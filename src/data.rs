@@ -1,6 +1,8 @@
-use std::collections::{HashMap, HashSet};
+use std::{collections::{HashMap, HashSet}, fmt};
 
-use crate::{constants::TIME_ID, culture::Culture, good::Good, process::Process, species::Species, want::Want};
+use itertools::Itertools;
+
+use crate::{constants::TIME_ID, culture::Culture, desire::Desire, good::{full_name, Good}, item::Item, layer::Layer, markethistory::MarketHistory, process::Process, species::Species, want::{Want, WantSource}};
 
 /// # Data
 pub struct Data {
@@ -15,6 +17,8 @@ pub struct Data {
     pub processes: HashMap<usize, Process>,
     pub species: HashMap<usize, Species>,
     pub culture: HashMap<usize, Culture>,
+    /// Generic demographic layers (religion, class, profession, etc).
+    pub layers: HashMap<usize, Layer>,
 }
 
 impl Data {
@@ -26,6 +30,7 @@ impl Data {
             processes: HashMap::new(),
             species: HashMap::new(),
             culture: HashMap::new(),
+            layers: HashMap::new(),
         }
     }
 
@@ -47,6 +52,10 @@ impl Data {
                 return Err(format!("Class base Good '{}' in Good '{}' does not currently exist. Be sure it exists before adding a new member of said class.", 
                 class_id, good.id))
             }
+            if self.goods.contains_key(&class_id) && !self.classes.contains_key(&class_id) {
+                return Err(format!("Good '{}' in Good '{}' is not the base of a class.", 
+                class_id, good.id))
+            }
         }
         // check wants it references exist
         for (want, _) in good.consumption_wants.iter() {
@@ -67,28 +76,605 @@ impl Data {
         // all checks done and no invalid data, add to data and make connections.
         if let Some(class_id) = good.class {
             if class_id == good.id {
-                // if we are the class base good, add whole cloth.
-                let mut newset = HashSet::new();
-                newset.insert(class_id);
-                self.classes.insert(class_id, newset);
+                // if we are the class base good, add whole cloth (keeping any
+                // members, in case we are being replaced).
+                self.classes.entry(class_id).or_default().insert(class_id);
             } else {
                 self.classes.get_mut(&class_id).unwrap().insert(good.id);
             }
         }
         // add want connections
-        for (want, _) in good.consumption_wants.iter() {
-            self.wants.get_mut(want).unwrap().consumption_sources.push(good.id);
+        self.link_want_sources(&good);
+        self.goods.insert(good.id, good);
+        Ok(())
+    }
+
+    /// # Try Add Want
+    /// 
+    /// Adds a new want to data.
+    /// 
+    /// Any sources recorded in the want are cleared, as Data fills those in 
+    /// itself as goods are added.
+    pub fn try_add_want(&mut self, mut want: Want) -> Result<(), String> {
+        if self.wants.contains_key(&want.id) {
+            return Err(format!("Want '{}' already exists in data.", want.id));
         }
-        for (want, _) in good.use_wants.iter() {
-            self.wants.get_mut(want).unwrap().use_sources.push(good.id);
+        for source in WantSource::ALL {
+            want.sources_mut(source).clear();
         }
-        for (want, _) in good.own_wants.iter() {
-            self.wants.get_mut(want).unwrap().ownership_sources.push(good.id);
+        // pick up any goods already in data that produce it.
+        for good in self.goods.values().sorted_by_key(|x| x.id) {
+            for source in WantSource::ALL {
+                if good.wants_from(source).contains_key(&want.id) {
+                    want.sources_mut(source).push(good.id);
+                }
+            }
+        }
+        self.wants.insert(want.id, want);
+        Ok(())
+    }
+
+    /// # Try Add Process
+    /// 
+    /// Adds a new process to data, so long as everything it uses and makes
+    /// already exists.
+    pub fn try_add_process(&mut self, process: Process) -> Result<(), String> {
+        if self.processes.contains_key(&process.id) {
+            return Err(format!("Process '{}' already exists in data.", process.id));
+        }
+        self.check_process_items(&process)?;
+        self.processes.insert(process.id, process);
+        Ok(())
+    }
+
+    /// # Try Add Species
+    /// 
+    /// Adds a new species to data, so long as it's desires are valid, see 
+    /// Desire::check().
+    pub fn try_add_species(&mut self, species: Species) -> Result<(), String> {
+        if self.species.contains_key(&species.id) {
+            return Err(format!("Species '{}' already exists in data.", species.id));
+        }
+        Data::check_desires(&species.desires)?;
+        self.species.insert(species.id, species);
+        Ok(())
+    }
+
+    /// # Try Add Culture
+    /// 
+    /// Adds a new culture to data, so long as it's desires are valid, see 
+    /// Desire::check().
+    pub fn try_add_culture(&mut self, culture: Culture) -> Result<(), String> {
+        if self.culture.contains_key(&culture.id) {
+            return Err(format!("Culture '{}' already exists in data.", culture.id));
+        }
+        Data::check_desires(&culture.desires)?;
+        self.culture.insert(culture.id, culture);
+        Ok(())
+    }
+
+    /// # Try Add Layer
+    /// 
+    /// Adds a new demographic layer to data, so long as it's desires are 
+    /// valid, see Desire::check().
+    pub fn try_add_layer(&mut self, layer: Layer) -> Result<(), String> {
+        if self.layers.contains_key(&layer.id) {
+            return Err(format!("Layer '{}' already exists in data.", layer.id));
+        }
+        Data::check_desires(&layer.desires)?;
+        self.layers.insert(layer.id, layer);
+        Ok(())
+    }
+
+    /// # Check Desires
+    /// 
+    /// Checks each desire with Desire::check(), returning the first failure.
+    fn check_desires(desires: &[Desire]) -> Result<(), String> {
+        for desire in desires.iter() {
+            desire.check()
+                .map_err(|err| format!("Desire for {} is invalid. {}", desire.item, err))?;
         }
-        self.goods.insert(good.id, good);
         Ok(())
     }
 
+    /// # Check Reloaded Desires
+    /// 
+    /// Checks desires being reloaded, each must desire an item which exists
+    /// and pass Desire::check().
+    fn check_reloaded_desires(&self, desires: &[Desire]) -> Result<(), String> {
+        if let Some(missing) = desires.iter().find(|x| !self.item_exists(x.item)) {
+            return Err(format!("{} does not currently exist in Data.", missing.item));
+        }
+        Data::check_desires(desires)
+    }
+
+    /// # Good References
+    /// 
+    /// Finds everything in data which depends on the good.
+    /// 
+    /// This includes members of it's class (if it's a class base), goods that 
+    /// decay into it, processes which use or make it (or it's class), and 
+    /// desires for it (or it's class) in species and cultures.
+    /// 
+    /// It does not include the want sources it's a part of, those are Data's own 
+    /// index and are always updated.
+    pub fn good_references(&self, id: usize) -> Vec<DataReference> {
+        let mut result = vec![];
+        // class members, if we're the base of a class.
+        if let Some(class) = self.classes.get(&id) {
+            for member in class.iter().filter(|x| **x != id).sorted() {
+                result.push(DataReference::ClassMember { good: *member });
+            }
+        }
+        // goods which decay into it.
+        for good in self.goods.values()
+        .filter(|x| x.id != id && x.decays_to.is_some_and(|(to, _)| to == id))
+        .map(|x| x.id).sorted() {
+            result.push(DataReference::DecaysTo { good });
+        }
+        // processes which use or make it.
+        for process in self.processes.values().sorted_by_key(|x| x.id) {
+            if process.inputs.iter().any(|x| x.good == id) {
+                result.push(DataReference::ProcessInput { process: process.id });
+            }
+            if process.outputs.iter().any(|x| x.item == Item::Good(id) || x.item == Item::Class(id)) {
+                result.push(DataReference::ProcessOutput { process: process.id });
+            }
+        }
+        // desires in species and cultures.
+        result.extend(self.desire_references(Item::Good(id)));
+        if self.classes.contains_key(&id) {
+            result.extend(self.desire_references(Item::Class(id)));
+        }
+        result
+    }
+
+    /// # Want References
+    /// 
+    /// Finds everything in data which depends on the want.
+    /// 
+    /// This includes the goods which produce it, processes which output it, and
+    /// desires for it in species and cultures.
+    pub fn want_references(&self, id: usize) -> Vec<DataReference> {
+        let mut result = vec![];
+        if let Some(want) = self.wants.get(&id) {
+            for source in WantSource::ALL {
+                for good in want.sources(source).iter().sorted() {
+                    result.push(DataReference::WantSource { good: *good, source });
+                }
+            }
+        }
+        for process in self.processes.values().sorted_by_key(|x| x.id) {
+            if process.outputs.iter().any(|x| x.item == Item::Want(id)) {
+                result.push(DataReference::ProcessOutput { process: process.id });
+            }
+        }
+        result.extend(self.desire_references(Item::Want(id)));
+        result
+    }
+
+    /// # Process References
+    /// 
+    /// Finds all processes which are derived from this process.
+    pub fn process_references(&self, id: usize) -> Vec<DataReference> {
+        self.processes.values()
+            .filter(|x| x.id != id && x.parent == Some(id))
+            .map(|x| x.id)
+            .sorted()
+            .map(|process| DataReference::ProcessParent { process })
+            .collect()
+    }
+
+    /// # Desire References
+    /// 
+    /// Finds all species, cultures, and layers with a desire for the item.
+    fn desire_references(&self, item: Item) -> Vec<DataReference> {
+        let mut result = vec![];
+        for species in self.species.values().sorted_by_key(|x| x.id) {
+            if species.desires.iter().any(|x| x.item == item) {
+                result.push(DataReference::SpeciesDesire { species: species.id, item });
+            }
+        }
+        for culture in self.culture.values().sorted_by_key(|x| x.id) {
+            if culture.desires.iter().any(|x| x.item == item) {
+                result.push(DataReference::CultureDesire { culture: culture.id, item });
+            }
+        }
+        for layer in self.layers.values().sorted_by_key(|x| x.id) {
+            if layer.desires.iter().any(|x| x.item == item) {
+                result.push(DataReference::LayerDesire { layer: layer.id, item });
+            }
+        }
+        result
+    }
+
+    /// # Cascade
+    /// 
+    /// Strips each reference out of the thing that depends on the removed good or want.
+    /// 
+    /// Dependents are never removed themselves, only the offending part of them.
+    fn cascade(&mut self, removed: Item, references: &[DataReference]) {
+        for reference in references.iter() {
+            match *reference {
+                DataReference::ClassMember { good } => {
+                    if let Some(good) = self.goods.get_mut(&good) {
+                        good.class = None;
+                    }
+                },
+                DataReference::DecaysTo { good } => {
+                    if let Some(good) = self.goods.get_mut(&good) {
+                        good.decays_to = None;
+                    }
+                },
+                DataReference::ProcessInput { process } => {
+                    if let Some(process) = self.processes.get_mut(&process) {
+                        process.inputs.retain(|x| Item::Good(x.good) != removed);
+                    }
+                },
+                DataReference::ProcessOutput { process } => {
+                    if let Some(process) = self.processes.get_mut(&process) {
+                        // a removed good takes the class it's the base of with it.
+                        let class = match removed {
+                            Item::Good(id) => Some(Item::Class(id)),
+                            _ => None,
+                        };
+                        process.outputs.retain(|x| x.item != removed && Some(x.item) != class);
+                    }
+                },
+                DataReference::ProcessParent { .. } => {}, // only for processes, handled there.
+                DataReference::SpeciesDesire { species, item } => {
+                    if let Some(species) = self.species.get_mut(&species) {
+                        species.desires.retain(|x| x.item != item);
+                    }
+                },
+                DataReference::CultureDesire { culture, item } => {
+                    if let Some(culture) = self.culture.get_mut(&culture) {
+                        culture.desires.retain(|x| x.item != item);
+                    }
+                },
+                DataReference::LayerDesire { layer, item } => {
+                    if let Some(layer) = self.layers.get_mut(&layer) {
+                        layer.desires.retain(|x| x.item != item);
+                    }
+                },
+                DataReference::WantSource { good, source } => {
+                    if let Some(good) = self.goods.get_mut(&good) {
+                        let want = removed.unwrap();
+                        match source {
+                            WantSource::Ownership => good.own_wants.remove(&want),
+                            WantSource::Use => good.use_wants.remove(&want),
+                            WantSource::Consumption => good.consumption_wants.remove(&want),
+                        };
+                    }
+                },
+            }
+        }
+    }
+
+    /// # Remove Good
+    /// 
+    /// Removes a good from data, removing it from it's class and the want 
+    /// sources it's a part of.
+    /// 
+    /// If anything else depends on the good (see good_references), then
+    /// RemoveMode::Refuse returns Err listing them and changes nothing, while
+    /// RemoveMode::Cascade strips those references out and returns them alongside
+    /// the removed good.
+    /// 
+    /// Time can never be removed.
+    pub fn remove_good(&mut self, id: usize, mode: RemoveMode) -> Result<Removal<Good>, String> {
+        if id == TIME_ID {
+            return Err(String::from("Time cannot be removed from data."));
+        }
+        if !self.goods.contains_key(&id) {
+            return Err(format!("Good '{}' does not exist in data.", id));
+        }
+        let references = self.good_references(id);
+        if mode == RemoveMode::Refuse && !references.is_empty() {
+            return Err(format!("Good '{}' is still referenced by: {}", id, 
+                references.iter().join(", ")));
+        }
+        self.cascade(Item::Good(id), &references);
+        // class desires and members are cascaded above, so just drop the class.
+        self.classes.remove(&id);
+        let good = self.goods.remove(&id).unwrap();
+        if let Some(class) = good.class.and_then(|x| self.classes.get_mut(&x)) {
+            class.remove(&id);
+        }
+        self.unlink_want_sources(&good);
+        Ok(Removal { removed: good, cascaded: references })
+    }
+
+    /// # Replace Good
+    /// 
+    /// Replaces an existing good with a new definition of the same id, updating 
+    /// it's class and want sources to match.
+    /// 
+    /// Fails under the same conditions as try_add_good, or if the new definition
+    /// would leave members of it's class without a base. If it fails, the old
+    /// definition is kept.
+    /// 
+    /// Returns the old definition.
+    pub fn replace_good(&mut self, good: Good) -> Result<Good, String> {
+        let old = match self.goods.get(&good.id) {
+            Some(old) => old,
+            None => return Err(format!("Good '{}' does not exist in data.", good.id)),
+        };
+        if self.classes.get(&good.id).is_some_and(|x| x.len() > 1) && 
+        good.class != Some(good.id) {
+            return Err(format!("Good '{}' is the base of a class with other members and must stay so.", good.id));
+        }
+        if let Some(class_id) = good.class && !self.goods.contains_key(&class_id) {
+            return Err(format!("Class base Good '{}' in Good '{}' does not currently exist.", 
+            class_id, good.id));
+        }
+        if let Some(class_id) = good.class && class_id != good.id && 
+        !self.classes.contains_key(&class_id) {
+            return Err(format!("Good '{}' in Good '{}' is not the base of a class.", 
+            class_id, good.id));
+        }
+        for source in WantSource::ALL {
+            for want in good.wants_from(source).keys() {
+                if !self.wants.contains_key(want) {
+                    return Err(format!("Want '{}' does not currently exist in Data.", want));
+                }
+            }
+        }
+        // all good, swap it in.
+        if let Some(class) = old.class.and_then(|x| self.classes.get_mut(&x)) {
+            class.remove(&good.id);
+        }
+        if old.class == Some(good.id) && good.class != Some(good.id) {
+            self.classes.remove(&good.id);
+        }
+        let old = self.goods.remove(&good.id).unwrap();
+        self.unlink_want_sources(&old);
+        if let Err(msg) = self.try_add_good(good) {
+            // put the old definition back so nothing is lost.
+            self.try_add_good(old.clone())
+                .expect("Old good could not be restored after a failed replace.");
+            return Err(msg);
+        }
+        Ok(old)
+    }
+
+    /// # Remove Want
+    /// 
+    /// Removes a want from data.
+    /// 
+    /// If anything depends on the want (see want_references), then 
+    /// RemoveMode::Refuse returns Err listing them and changes nothing, while
+    /// RemoveMode::Cascade strips those references out and returns them alongside
+    /// the removed want.
+    pub fn remove_want(&mut self, id: usize, mode: RemoveMode) -> Result<Removal<Want>, String> {
+        if !self.wants.contains_key(&id) {
+            return Err(format!("Want '{}' does not exist in data.", id));
+        }
+        let references = self.want_references(id);
+        if mode == RemoveMode::Refuse && !references.is_empty() {
+            return Err(format!("Want '{}' is still referenced by: {}", id, 
+                references.iter().join(", ")));
+        }
+        self.cascade(Item::Want(id), &references);
+        let want = self.wants.remove(&id).unwrap();
+        Ok(Removal { removed: want, cascaded: references })
+    }
+
+    /// # Replace Want
+    /// 
+    /// Replaces an existing want with a new definition of the same id.
+    /// 
+    /// The want sources are maintained by data, so those are carried over from
+    /// the old definition.
+    /// 
+    /// Returns the old definition.
+    pub fn replace_want(&mut self, mut want: Want) -> Result<Want, String> {
+        let old = match self.wants.remove(&want.id) {
+            Some(old) => old,
+            None => return Err(format!("Want '{}' does not exist in data.", want.id)),
+        };
+        for source in WantSource::ALL {
+            *want.sources_mut(source) = old.sources(source).to_vec();
+        }
+        self.wants.insert(want.id, want);
+        Ok(old)
+    }
+
+    /// # Remove Process
+    /// 
+    /// Removes a process from data.
+    /// 
+    /// The only things which depend on a process are those derived from it. 
+    /// RemoveMode::Refuse returns Err if any exist, RemoveMode::Cascade clears
+    /// their parent.
+    pub fn remove_process(&mut self, id: usize, mode: RemoveMode) -> Result<Removal<Process>, String> {
+        if !self.processes.contains_key(&id) {
+            return Err(format!("Process '{}' does not exist in data.", id));
+        }
+        let references = self.process_references(id);
+        if mode == RemoveMode::Refuse && !references.is_empty() {
+            return Err(format!("Process '{}' is still referenced by: {}", id, 
+                references.iter().join(", ")));
+        }
+        for reference in references.iter() {
+            if let DataReference::ProcessParent { process } = reference {
+                self.processes.get_mut(process).unwrap().parent = None;
+            }
+        }
+        let process = self.processes.remove(&id).unwrap();
+        Ok(Removal { removed: process, cascaded: references })
+    }
+
+    /// # Replace Process
+    /// 
+    /// Replaces an existing process with a new definition of the same id.
+    /// 
+    /// Fails if the process doesn't exist, or if it uses or makes goods or 
+    /// wants which don't exist.
+    /// 
+    /// Returns the old definition.
+    pub fn replace_process(&mut self, process: Process) -> Result<Process, String> {
+        if !self.processes.contains_key(&process.id) {
+            return Err(format!("Process '{}' does not exist in data.", process.id));
+        }
+        self.check_process_items(&process)?;
+        Ok(self.processes.insert(process.id, process).unwrap())
+    }
+
+    /// # Check Process Items
+    /// 
+    /// Checks that everything a process takes in or puts out exists in data.
+    fn check_process_items(&self, process: &Process) -> Result<(), String> {
+        for input in process.inputs.iter() {
+            if !self.goods.contains_key(&input.good) {
+                return Err(format!("Good '{}' does not currently exist in Data.", input.good));
+            }
+        }
+        for output in process.outputs.iter() {
+            let exists = match output.item {
+                Item::Want(id) => self.wants.contains_key(&id),
+                Item::Class(id) => self.classes.contains_key(&id),
+                Item::Good(id) => self.goods.contains_key(&id),
+            };
+            if !exists {
+                return Err(format!("{} does not currently exist in Data.", output.item));
+            }
+        }
+        Ok(())
+    }
+
+    /// # Remove Species
+    /// 
+    /// Removes a species from data. Nothing in data depends on species, 
+    /// but pops with the species in their demographics will need to be updated
+    /// separately.
+    pub fn remove_species(&mut self, id: usize) -> Result<Species, String> {
+        self.species.remove(&id)
+            .ok_or(format!("Species '{}' does not exist in data.", id))
+    }
+
+    /// # Replace Species
+    /// 
+    /// Replaces an existing species with a new definition of the same id.
+    /// 
+    /// Returns the old definition.
+    pub fn replace_species(&mut self, species: Species) -> Result<Species, String> {
+        if !self.species.contains_key(&species.id) {
+            return Err(format!("Species '{}' does not exist in data.", species.id));
+        }
+        Ok(self.species.insert(species.id, species).unwrap())
+    }
+
+    /// # Remove Culture
+    /// 
+    /// Removes a culture from data. Nothing in data depends on cultures, 
+    /// but pops with the culture in their demographics will need to be updated
+    /// separately.
+    pub fn remove_culture(&mut self, id: usize) -> Result<Culture, String> {
+        self.culture.remove(&id)
+            .ok_or(format!("Culture '{}' does not exist in data.", id))
+    }
+
+    /// # Replace Culture
+    /// 
+    /// Replaces an existing culture with a new definition of the same id.
+    /// 
+    /// Returns the old definition.
+    pub fn replace_culture(&mut self, culture: Culture) -> Result<Culture, String> {
+        if !self.culture.contains_key(&culture.id) {
+            return Err(format!("Culture '{}' does not exist in data.", culture.id));
+        }
+        Ok(self.culture.insert(culture.id, culture).unwrap())
+    }
+
+    /// # Link Want Sources
+    /// 
+    /// Adds the good to the sources of every want it produces.
+    /// 
+    /// Skips any wants that don't exist and any connections that already exist.
+    fn link_want_sources(&mut self, good: &Good) {
+        for source in WantSource::ALL {
+            for want in good.wants_from(source).keys() {
+                if let Some(want) = self.wants.get_mut(want) {
+                    let sources = want.sources_mut(source);
+                    if !sources.contains(&good.id) {
+                        sources.push(good.id);
+                    }
+                }
+            }
+        }
+    }
+
+    /// # Unlink Want Sources
+    /// 
+    /// Removes the good from the sources of every want it produces.
+    fn unlink_want_sources(&mut self, good: &Good) {
+        for source in WantSource::ALL {
+            for want in good.wants_from(source).keys() {
+                if let Some(want) = self.wants.get_mut(want) {
+                    want.sources_mut(source).retain(|x| *x != good.id);
+                }
+            }
+        }
+    }
+
+    /// # Get Want Sources
+    /// 
+    /// Gets the goods which produce the want via the source given.
+    /// 
+    /// # Panics
+    /// 
+    /// If the want does not exist.
+    pub fn get_want_sources(&self, want: usize, source: WantSource) -> &[usize] {
+        self.get_want(want).sources(source)
+    }
+
+    /// # Want Sources
+    /// 
+    /// Gets all goods which produce the want, how they produce it, and how 
+    /// much of the want one unit of the good produces.
+    /// 
+    /// Ordered by source (Ownership -> Use -> Consumption), then the index's order.
+    /// 
+    /// # Panics
+    /// 
+    /// If the want does not exist.
+    pub fn want_sources(&self, want: usize) -> Vec<(usize, WantSource, f64)> {
+        let mut result = vec![];
+        for source in WantSource::ALL {
+            for good in self.get_want_sources(want, source).iter() {
+                let eff = *self.get_good(*good).wants_from(source).get(&want)
+                    .expect("Want source index out of sync with good.");
+                result.push((*good, source, eff));
+            }
+        }
+        result
+    }
+
+    /// # Cheapest Want Sources
+    /// 
+    /// Ranks the goods which produce a want by the AMV it costs to get one unit 
+    /// of the want from them at current prices (price / efficiency), cheapest first.
+    /// 
+    /// Goods without a price in the market are left out. Time costs are not included.
+    /// 
+    /// # Panics
+    /// 
+    /// If the want does not exist.
+    pub fn cheapest_want_sources(&self, want: usize, market: &MarketHistory) 
+    -> Vec<(usize, WantSource, f64)> {
+        let mut result = vec![];
+        for (good, source, eff) in self.want_sources(want) {
+            if let Some(record) = market.good_records.get(&good) {
+                result.push((good, source, record.price / eff));
+            }
+        }
+        result.sort_by(|a, b| a.2.total_cmp(&b.2)
+            .then(a.1.cmp(&b.1))
+            .then(a.0.cmp(&b.0)));
+        result
+    }
+
     /// # Add Good
     /// 
     /// Hard Add good, panics if addition fails, sending the failure message out.
@@ -111,6 +697,13 @@ impl Data {
         .expect(format!("Species '{} not found!'", id).as_str())
     }
 
+    /// # Try Get Species
+    /// 
+    /// Non-panicking version of get_species.
+    pub fn try_get_species(&self, id: usize) -> Option<&Species> {
+        self.species.get(&id)
+    }
+
     /// # Get Culture
     /// 
     /// wrapper for get(id).expect()
@@ -123,6 +716,28 @@ impl Data {
         .expect(format!("Culture '{} not found!'", id).as_str())
     }
 
+    /// # Try Get Culture
+    /// 
+    /// Non-panicking version of get_culture.
+    pub fn try_get_culture(&self, id: usize) -> Option<&Culture> {
+        self.culture.get(&id)
+    }
+
+    /// # Get Layer
+    /// 
+    /// Wrapper for get(id).expect("Layer 'id' not found!").
+    pub fn get_layer(&self, id: usize) -> &Layer {
+        self.layers.get(&id)
+        .unwrap_or_else(|| panic!("Layer '{} not found!'", id))
+    }
+
+    /// # Try Get Layer
+    /// 
+    /// Non-panicking version of get_layer.
+    pub fn try_get_layer(&self, id: usize) -> Option<&Layer> {
+        self.layers.get(&id)
+    }
+
     /// # Get Class
     /// 
     /// Wrapper for get(id).expect("Class 'id' not fonud!").
@@ -134,6 +749,13 @@ impl Data {
         self.classes.get(&id)
         .expect(format!("Class '{}' not found!", id).as_str())
     }
+
+    /// # Try Get Class
+    /// 
+    /// Non-panicking version of get_class.
+    pub fn try_get_class(&self, id: usize) -> Option<&HashSet<usize>> {
+        self.classes.get(&id)
+    }
     
     /// # Get Good
     /// 
@@ -146,6 +768,13 @@ impl Data {
         self.goods.get(&id)
         .expect(format!("Good '{}' not found!", id).as_str())
     }
+
+    /// # Try Get Good
+    /// 
+    /// Non-panicking version of get_good.
+    pub fn try_get_good(&self, id: usize) -> Option<&Good> {
+        self.goods.get(&id)
+    }
     
     /// # Get Want
     /// 
@@ -158,14 +787,600 @@ impl Data {
         self.wants.get(&id)
         .expect(format!("Want '{}' not found!", id).as_str())
     }
+
+    /// # Try Get Want
+    /// 
+    /// Non-panicking version of get_want.
+    pub fn try_get_want(&self, id: usize) -> Option<&Want> {
+        self.wants.get(&id)
+    }
+
+    /// # Try Get Process
+    /// 
+    /// Gets the process, if it exists.
+    pub fn try_get_process(&self, id: usize) -> Option<&Process> {
+        self.processes.get(&id)
+    }
+
+    /// # Find Want
+    /// 
+    /// Finds the id of a want by it's name.
+    pub fn find_want(&self, name: &str) -> Option<usize> {
+        self.wants.values()
+            .find(|x| x.name == name)
+            .map(|x| x.id)
+    }
+
+    /// # Find Good
+    /// 
+    /// Finds the id of a good by it's full name, "Name" or "Name (Variant)".
+    pub fn find_good(&self, name: &str) -> Option<usize> {
+        self.goods.values()
+            .find(|x| full_name(&x.name, &x.variant_name) == name)
+            .map(|x| x.id)
+    }
+
+    /// # Item Exists
+    /// 
+    /// Checks that the want, class, or good pointed to exists.
+    pub fn item_exists(&self, item: Item) -> bool {
+        match item {
+            Item::Want(id) => self.wants.contains_key(&id),
+            Item::Class(id) => self.classes.contains_key(&id),
+            Item::Good(id) => self.goods.contains_key(&id),
+        }
+    }
+
+    /// # Validate
+    /// 
+    /// Checks the whole of data for problems, returning every problem found 
+    /// rather than stopping at the first.
+    /// 
+    /// Checks
+    /// - Ids match the key they are stored under.
+    /// - Goods point to wants, classes, and decay goods which exist.
+    /// - Want source indices match what goods produce.
+    /// - Classes have a base good and their members point back to them.
+    /// - Processes use and make things which exist, and have parents which exist.
+    /// - Processes don't use any goods which can never be produced.
+    /// - Species, Culture, and Layer desires point to items which exist, and 
+    ///   are valid, see Desire::check().
+    /// 
+    /// Problems are ordered by what they're found in (goods, wants, classes,
+    /// processes, species, cultures, then layers) and then by id.
+    pub fn validate(&self) -> Result<(), Vec<DataError>> {
+        let mut errors = vec![];
+        // goods
+        for (&key, good) in self.goods.iter().sorted_by_key(|x| x.0) {
+            if key != good.id {
+                errors.push(DataError::IdMismatch { kind: DataKind::Good, key, id: good.id });
+            }
+            if let Some(class) = good.class && !self.classes.contains_key(&class) {
+                errors.push(DataError::MissingClass { good: key, class });
+            }
+            if let Some((target, _)) = good.decays_to && !self.goods.contains_key(&target) {
+                errors.push(DataError::MissingDecayTarget { good: key, target });
+            }
+            for source in WantSource::ALL {
+                for &want in good.wants_from(source).keys().sorted() {
+                    match self.wants.get(&want) {
+                        None => errors.push(DataError::MissingWant { good: key, want }),
+                        Some(rec) => if !rec.sources(source).contains(&key) {
+                            errors.push(DataError::WantSourceMismatch { want, good: key, source });
+                        },
+                    }
+                }
+            }
+        }
+        // wants
+        for (&key, want) in self.wants.iter().sorted_by_key(|x| x.0) {
+            if key != want.id {
+                errors.push(DataError::IdMismatch { kind: DataKind::Want, key, id: want.id });
+            }
+            for source in WantSource::ALL {
+                for &good in want.sources(source).iter() {
+                    if !self.goods.get(&good).is_some_and(|x| x.wants_from(source).contains_key(&key)) {
+                        errors.push(DataError::WantSourceMismatch { want: key, good, source });
+                    }
+                }
+            }
+        }
+        // classes
+        for (&class, members) in self.classes.iter().sorted_by_key(|x| x.0) {
+            if self.goods.get(&class).is_none_or(|x| x.class != Some(class)) {
+                errors.push(DataError::OrphanClass { class });
+            }
+            for &good in members.iter().sorted() {
+                if self.goods.get(&good).is_none_or(|x| x.class != Some(class)) {
+                    errors.push(DataError::ClassMemberMismatch { class, good });
+                }
+            }
+        }
+        // processes
+        let producible = self.producible_goods();
+        for (&key, process) in self.processes.iter().sorted_by_key(|x| x.0) {
+            if key != process.id {
+                errors.push(DataError::IdMismatch { kind: DataKind::Process, key, id: process.id });
+            }
+            if let Some(parent) = process.parent && !self.processes.contains_key(&parent) {
+                errors.push(DataError::MissingProcessParent { process: key, parent });
+            }
+            for input in process.inputs.iter() {
+                if !self.goods.contains_key(&input.good) {
+                    errors.push(DataError::MissingProcessInput { process: key, good: input.good });
+                } else if !producible.contains(&input.good) {
+                    errors.push(DataError::UnproducibleInput { process: key, good: input.good });
+                }
+            }
+            for output in process.outputs.iter() {
+                if !self.item_exists(output.item) {
+                    errors.push(DataError::MissingProcessOutput { process: key, item: output.item });
+                }
+            }
+        }
+        // species and cultures
+        for (&key, species) in self.species.iter().sorted_by_key(|x| x.0) {
+            if key != species.id {
+                errors.push(DataError::IdMismatch { kind: DataKind::Species, key, id: species.id });
+            }
+            for desire in species.desires.iter() {
+                if !self.item_exists(desire.item) {
+                    errors.push(DataError::MissingDesireItem { kind: DataKind::Species, owner: key, item: desire.item });
+                }
+                if desire.check().is_err() {
+                    errors.push(DataError::InvalidDesire { kind: DataKind::Species, owner: key, item: desire.item });
+                }
+            }
+        }
+        for (&key, culture) in self.culture.iter().sorted_by_key(|x| x.0) {
+            if key != culture.id {
+                errors.push(DataError::IdMismatch { kind: DataKind::Culture, key, id: culture.id });
+            }
+            for desire in culture.desires.iter() {
+                if !self.item_exists(desire.item) {
+                    errors.push(DataError::MissingDesireItem { kind: DataKind::Culture, owner: key, item: desire.item });
+                }
+                if desire.check().is_err() {
+                    errors.push(DataError::InvalidDesire { kind: DataKind::Culture, owner: key, item: desire.item });
+                }
+            }
+        }
+        for (&key, layer) in self.layers.iter().sorted_by_key(|x| x.0) {
+            if key != layer.id {
+                errors.push(DataError::IdMismatch { kind: DataKind::Layer, key, id: layer.id });
+            }
+            for desire in layer.desires.iter() {
+                if !self.item_exists(desire.item) {
+                    errors.push(DataError::MissingDesireItem { kind: DataKind::Layer, owner: key, item: desire.item });
+                }
+                if desire.check().is_err() {
+                    errors.push(DataError::InvalidDesire { kind: DataKind::Layer, owner: key, item: desire.item });
+                }
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// # Producible Goods
+    /// 
+    /// Finds every good which can be made from nothing.
+    /// 
+    /// Time can always be made, as can the outputs of any process whose inputs
+    /// can all be made (including processes with no inputs), and what those
+    /// goods decay into.
+    pub fn producible_goods(&self) -> HashSet<usize> {
+        let mut producible = HashSet::new();
+        if self.goods.contains_key(&TIME_ID) {
+            producible.insert(TIME_ID);
+        }
+        // keep going until nothing new is found.
+        loop {
+            let start = producible.len();
+            for process in self.processes.values() {
+                if process.inputs.iter().all(|x| producible.contains(&x.good)) {
+                    for output in process.outputs.iter() {
+                        if let Item::Good(id) = output.item && self.goods.contains_key(&id) {
+                            producible.insert(id);
+                        }
+                    }
+                }
+            }
+            let decayed: Vec<usize> = producible.iter()
+                .filter_map(|x| self.goods.get(x).and_then(|g| g.decays_to))
+                .map(|(x, _)| x)
+                .filter(|x| self.goods.contains_key(x))
+                .collect();
+            producible.extend(decayed);
+            if producible.len() == start {
+                break;
+            }
+        }
+        producible
+    }
     
+    /// # Reload
+    /// 
+    /// Reloads changed definitions into data while it's in use.
+    /// 
+    /// Goods and processes are added if new or replace the existing definition
+    /// if different. Species, culture, and layer desires replace the existing 
+    /// desires if different, so long as they pass the same checks as 
+    /// try_add_species() and the rest. Anything identical to what's already 
+    /// in data is skipped.
+    /// 
+    /// Time is reserved and cannot be reloaded.
+    /// 
+    /// Returns what was changed and anything which couldn't be reloaded. Pops
+    /// and markets are not touched, see World::reload_data() for that.
+    pub fn reload(&mut self, reload: DataReload) -> ReloadReport {
+        let mut report = ReloadReport::new();
+        // goods, class bases first so new members can join them.
+        for good in reload.goods.into_iter()
+        .sorted_by_key(|x| (x.class != Some(x.id), x.id)) {
+            let id = good.id;
+            if id == TIME_ID {
+                report.rejected.push((DataKind::Good, id, String::from("Time is reserved and cannot be reloaded.")));
+                continue;
+            }
+            match self.goods.get(&id) {
+                Some(old) if old.identical_to(&good) => {},
+                Some(_) => match self.replace_good(good) {
+                    Ok(_) => report.goods_changed.push(id),
+                    Err(e) => report.rejected.push((DataKind::Good, id, e)),
+                },
+                None => match self.try_add_good(good) {
+                    Ok(_) => report.goods_added.push(id),
+                    Err(e) => report.rejected.push((DataKind::Good, id, e)),
+                }
+            }
+        }
+        // processes
+        for process in reload.processes.into_iter().sorted_by_key(|x| x.id) {
+            let id = process.id;
+            match self.processes.get(&id) {
+                Some(old) if *old == process => {},
+                Some(_) => match self.replace_process(process) {
+                    Ok(_) => report.processes_changed.push(id),
+                    Err(e) => report.rejected.push((DataKind::Process, id, e)),
+                },
+                None => match self.try_add_process(process) {
+                    Ok(_) => report.processes_added.push(id),
+                    Err(e) => report.rejected.push((DataKind::Process, id, e)),
+                }
+            }
+        }
+        // species desires
+        for (id, desires) in reload.species_desires.into_iter().sorted_by_key(|x| x.0) {
+            if let Err(err) = self.check_reloaded_desires(&desires) {
+                report.rejected.push((DataKind::Species, id, err));
+                continue;
+            }
+            let Some(mut species) = self.species.remove(&id) else {
+                report.rejected.push((DataKind::Species, id, format!("Species '{}' does not exist in data.", id)));
+                continue;
+            };
+            let old = std::mem::take(&mut species.desires);
+            for desire in desires {
+                species = species.with_desire(desire);
+            }
+            if species.desires != old {
+                report.species_changed.push(id);
+            }
+            self.species.insert(id, species);
+        }
+        // culture desires
+        for (id, desires) in reload.culture_desires.into_iter().sorted_by_key(|x| x.0) {
+            if let Err(err) = self.check_reloaded_desires(&desires) {
+                report.rejected.push((DataKind::Culture, id, err));
+                continue;
+            }
+            let Some(mut culture) = self.culture.remove(&id) else {
+                report.rejected.push((DataKind::Culture, id, format!("Culture '{}' does not exist in data.", id)));
+                continue;
+            };
+            let old = std::mem::take(&mut culture.desires);
+            for desire in desires {
+                culture = culture.with_desire(desire);
+            }
+            if culture.desires != old {
+                report.cultures_changed.push(id);
+            }
+            self.culture.insert(id, culture);
+        }
+        // layer desires
+        for (id, desires) in reload.layer_desires.into_iter().sorted_by_key(|x| x.0) {
+            if let Err(err) = self.check_reloaded_desires(&desires) {
+                report.rejected.push((DataKind::Layer, id, err));
+                continue;
+            }
+            let Some(mut layer) = self.layers.remove(&id) else {
+                report.rejected.push((DataKind::Layer, id, format!("Layer '{}' does not exist in data.", id)));
+                continue;
+            };
+            let old = std::mem::take(&mut layer.desires);
+            for desire in desires {
+                layer = layer.with_desire(desire);
+            }
+            if layer.desires != old {
+                report.layers_changed.push(id);
+            }
+            self.layers.insert(id, layer);
+        }
+        report
+    }
+
     /// # Add Time
     /// 
     /// Adds Time, the default product, to the data. Overrides whatever
     /// is in Good ID 0.
     pub fn add_time(&mut self) {
+        if let Some(old) = self.goods.remove(&TIME_ID) {
+            self.unlink_want_sources(&old);
+        }
         self.goods.insert(TIME_ID, 
         Good::new(TIME_ID, String::from("Time"), String::new())
         .with_decay_rate(1.0));
     }
-}
\ No newline at end of file
+}
+
+/// # Remove Mode
+/// 
+/// How removing something from Data should treat anything which depends on it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RemoveMode {
+    /// Don't remove if anything depends on it.
+    Refuse,
+    /// Remove it and strip the reference out of everything that depends on it.
+    Cascade,
+}
+
+/// # Removal
+/// 
+/// The result of removing something from Data, the thing removed and the 
+/// references which were cascaded.
+#[derive(Debug)]
+pub struct Removal<T> {
+    /// What was removed.
+    pub removed: T,
+    /// The references that were stripped out of other data.
+    pub cascaded: Vec<DataReference>,
+}
+
+/// # Data Reference
+/// 
+/// A reference from one part of Data to another, used to report what depends
+/// on something being removed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DataReference {
+    /// The good is a member of the class.
+    ClassMember { good: usize },
+    /// The good decays into the target.
+    DecaysTo { good: usize },
+    /// The process uses the target as an input.
+    ProcessInput { process: usize },
+    /// The process makes the target as an output.
+    ProcessOutput { process: usize },
+    /// The process is derived from the target.
+    ProcessParent { process: usize },
+    /// The species has a desire for the item.
+    SpeciesDesire { species: usize, item: Item },
+    /// The culture has a desire for the item.
+    CultureDesire { culture: usize, item: Item },
+    /// The layer has a desire for the item.
+    LayerDesire { layer: usize, item: Item },
+    /// The good produces the target want via the source.
+    WantSource { good: usize, source: WantSource },
+}
+
+impl fmt::Display for DataReference {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DataReference::ClassMember { good } => write!(f, "Class Member Good '{}'", good),
+            DataReference::DecaysTo { good } => write!(f, "Decay of Good '{}'", good),
+            DataReference::ProcessInput { process } => write!(f, "Input of Process '{}'", process),
+            DataReference::ProcessOutput { process } => write!(f, "Output of Process '{}'", process),
+            DataReference::ProcessParent { process } => write!(f, "Parent of Process '{}'", process),
+            DataReference::SpeciesDesire { species, item } => write!(f, "Desire ({}) of Species '{}'", item, species),
+            DataReference::CultureDesire { culture, item } => write!(f, "Desire ({}) of Culture '{}'", item, culture),
+            DataReference::LayerDesire { layer, item } => write!(f, "Desire ({}) of Layer '{}'", item, layer),
+            DataReference::WantSource { good, source } => write!(f, "{:?} Source Good '{}'", source, good),
+        }
+    }
+}
+
+/// # Data Reload
+/// 
+/// A set of definitions to reload into data while it's in use, see 
+/// Data::reload().
+#[derive(Debug, Clone, Default)]
+pub struct DataReload {
+    /// Goods to add or replace.
+    pub goods: Vec<Good>,
+    /// Processes to add or replace.
+    pub processes: Vec<Process>,
+    /// The new desires of a species, by species id.
+    pub species_desires: HashMap<usize, Vec<Desire>>,
+    /// The new desires of a culture, by culture id.
+    pub culture_desires: HashMap<usize, Vec<Desire>>,
+    /// The new desires of a layer, by layer id.
+    pub layer_desires: HashMap<usize, Vec<Desire>>,
+}
+
+impl DataReload {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// # With Good
+    /// 
+    /// Adds a good to reload.
+    pub fn with_good(mut self, good: Good) -> Self {
+        self.goods.push(good);
+        self
+    }
+
+    /// # With Process
+    /// 
+    /// Adds a process to reload.
+    pub fn with_process(mut self, process: Process) -> Self {
+        self.processes.push(process);
+        self
+    }
+
+    /// # With Species Desires
+    /// 
+    /// Sets the new desires of a species.
+    pub fn with_species_desires(mut self, species: usize, desires: Vec<Desire>) -> Self {
+        self.species_desires.insert(species, desires);
+        self
+    }
+
+    /// # With Culture Desires
+    /// 
+    /// Sets the new desires of a culture.
+    pub fn with_culture_desires(mut self, culture: usize, desires: Vec<Desire>) -> Self {
+        self.culture_desires.insert(culture, desires);
+        self
+    }
+
+    /// # With Layer Desires
+    /// 
+    /// Sets the new desires of a layer.
+    pub fn with_layer_desires(mut self, layer: usize, desires: Vec<Desire>) -> Self {
+        self.layer_desires.insert(layer, desires);
+        self
+    }
+}
+
+/// # Reload Report
+/// 
+/// What a reload changed, and what it couldn't.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ReloadReport {
+    /// Goods which were new.
+    pub goods_added: Vec<usize>,
+    /// Goods which replaced a different definition.
+    pub goods_changed: Vec<usize>,
+    /// Processes which were new.
+    pub processes_added: Vec<usize>,
+    /// Processes which replaced a different definition.
+    pub processes_changed: Vec<usize>,
+    /// Species whose desires changed.
+    pub species_changed: Vec<usize>,
+    /// Cultures whose desires changed.
+    pub cultures_changed: Vec<usize>,
+    /// Layers whose desires changed.
+    pub layers_changed: Vec<usize>,
+    /// Anything which couldn't be reloaded, and why.
+    pub rejected: Vec<(DataKind, usize, String)>,
+    /// Pops whose desires were rebuilt.
+    pub pops_rebuilt: Vec<usize>,
+    /// Markets whose cached data was refreshed.
+    pub markets_refreshed: Vec<usize>,
+}
+
+impl ReloadReport {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// # Any Goods Changed
+    /// 
+    /// Whether any good was added or changed.
+    pub fn any_goods_changed(&self) -> bool {
+        !self.goods_added.is_empty() || !self.goods_changed.is_empty()
+    }
+
+    /// # Any Desires Changed
+    /// 
+    /// Whether any species, culture, or layer desires changed.
+    pub fn any_desires_changed(&self) -> bool {
+        !self.species_changed.is_empty() || !self.cultures_changed.is_empty() ||
+        !self.layers_changed.is_empty()
+    }
+}
+
+/// # Data Kind
+/// 
+/// The kinds of things stored in Data.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DataKind {
+    Want,
+    Good,
+    Process,
+    Species,
+    Culture,
+    Layer,
+}
+
+/// # Data Error
+/// 
+/// A problem found in Data by Data::validate().
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DataError {
+    /// The thing stored under key has a different id.
+    IdMismatch { kind: DataKind, key: usize, id: usize },
+    /// The good produces a want which doesn't exist.
+    MissingWant { good: usize, want: usize },
+    /// The good is in a class which doesn't exist.
+    MissingClass { good: usize, class: usize },
+    /// The good decays into a good which doesn't exist.
+    MissingDecayTarget { good: usize, target: usize },
+    /// The want's source index and the good disagree on whether the good 
+    /// produces the want via the source.
+    WantSourceMismatch { want: usize, good: usize, source: WantSource },
+    /// The class has no base good, or the base good doesn't point to itself.
+    OrphanClass { class: usize },
+    /// The class lists a good which doesn't exist or isn't in the class.
+    ClassMemberMismatch { class: usize, good: usize },
+    /// The process uses a good which doesn't exist.
+    MissingProcessInput { process: usize, good: usize },
+    /// The process makes an item which doesn't exist.
+    MissingProcessOutput { process: usize, item: Item },
+    /// The process is derived from a process which doesn't exist.
+    MissingProcessParent { process: usize, parent: usize },
+    /// The process uses a good which nothing can ever produce, so it can never run.
+    UnproducibleInput { process: usize, good: usize },
+    /// The species or culture desires an item which doesn't exist.
+    MissingDesireItem { kind: DataKind, owner: usize, item: Item },
+    /// The species, culture, or layer has a desire which fails Desire::check().
+    InvalidDesire { kind: DataKind, owner: usize, item: Item },
+}
+
+impl fmt::Display for DataError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DataError::IdMismatch { kind, key, id } => 
+                write!(f, "{:?} stored under '{}' has id '{}'.", kind, key, id),
+            DataError::MissingWant { good, want } => 
+                write!(f, "Good '{}' produces Want '{}' which does not exist.", good, want),
+            DataError::MissingClass { good, class } => 
+                write!(f, "Good '{}' is in Class '{}' which does not exist.", good, class),
+            DataError::MissingDecayTarget { good, target } => 
+                write!(f, "Good '{}' decays into Good '{}' which does not exist.", good, target),
+            DataError::WantSourceMismatch { want, good, source } => 
+                write!(f, "Want '{}' {:?} sources out of sync with Good '{}'.", want, source, good),
+            DataError::OrphanClass { class } => 
+                write!(f, "Class '{}' has no base good.", class),
+            DataError::ClassMemberMismatch { class, good } => 
+                write!(f, "Class '{}' lists Good '{}' which is not in the class.", class, good),
+            DataError::MissingProcessInput { process, good } => 
+                write!(f, "Process '{}' uses Good '{}' which does not exist.", process, good),
+            DataError::MissingProcessOutput { process, item } => 
+                write!(f, "Process '{}' makes {} which does not exist.", process, item),
+            DataError::MissingProcessParent { process, parent } => 
+                write!(f, "Process '{}' is derived from Process '{}' which does not exist.", process, parent),
+            DataError::UnproducibleInput { process, good } => 
+                write!(f, "Process '{}' uses Good '{}' which can never be produced.", process, good),
+            DataError::MissingDesireItem { kind, owner, item } => 
+                write!(f, "{:?} '{}' desires {} which does not exist.", kind, owner, item),
+            DataError::InvalidDesire { kind, owner, item } => 
+                write!(f, "{:?} '{}' has an invalid desire for {}.", kind, owner, item),
+        }
+    }
+}
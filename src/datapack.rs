@@ -0,0 +1,606 @@
+use std::{collections::{HashMap, HashSet}, fmt};
+
+use itertools::Itertools;
+
+use crate::{constants::TIME_ID, culture::Culture, data::{Data, DataKind, DataReference, RemoveMode}, desire::Desire, good::{full_name, Good}, item::Item, process::Process, species::Species, want::Want};
+
+/// # Data Pack
+///
+/// A layer of data to be loaded on top of whatever has been loaded before it.
+///
+/// Packs can add new wants, goods, processes, species, and cultures, override
+/// existing ones, or remove them outright.
+///
+/// Everything in a pack is written with pack local ids, which only need to be
+/// unique within the pack. When loaded, these are mapped to their id in Data by
+/// name, so the same name always gets the same id, regardless of which pack it
+/// came from or what order the packs are loaded in.
+///
+/// To refer to something from an earlier pack, it needs to be imported by name
+/// with a local id.
+///
+/// Good local id TIME_ID is always Time, and cannot be defined by a pack.
+pub struct DataPack {
+    /// The name of the pack.
+    pub name: String,
+    /// Wants to add, override, or remove.
+    pub wants: Vec<PackEntry<Want>>,
+    /// Goods to add, override, or remove.
+    pub goods: Vec<PackEntry<Good>>,
+    /// Processes to add, override, or remove.
+    pub processes: Vec<PackEntry<Process>>,
+    /// Species to add, override, or remove.
+    pub species: Vec<PackEntry<Species>>,
+    /// Cultures to add, override, or remove.
+    pub cultures: Vec<PackEntry<Culture>>,
+    /// Things from earlier packs this pack refers to, by kind and local id.
+    pub imports: HashMap<(DataKind, usize), String>,
+}
+
+impl DataPack {
+    pub fn new(name: String) -> Self {
+        Self {
+            name,
+            wants: vec![],
+            goods: vec![],
+            processes: vec![],
+            species: vec![],
+            cultures: vec![],
+            imports: HashMap::new(),
+        }
+    }
+
+    /// # With Want
+    ///
+    /// Adds a want entry to the pack.
+    pub fn with_want(mut self, entry: PackEntry<Want>) -> Self {
+        self.wants.push(entry);
+        self
+    }
+
+    /// # With Good
+    ///
+    /// Adds a good entry to the pack.
+    pub fn with_good(mut self, entry: PackEntry<Good>) -> Self {
+        self.goods.push(entry);
+        self
+    }
+
+    /// # With Process
+    ///
+    /// Adds a process entry to the pack.
+    pub fn with_process(mut self, entry: PackEntry<Process>) -> Self {
+        self.processes.push(entry);
+        self
+    }
+
+    /// # With Species
+    ///
+    /// Adds a species entry to the pack.
+    pub fn with_species(mut self, entry: PackEntry<Species>) -> Self {
+        self.species.push(entry);
+        self
+    }
+
+    /// # With Culture
+    ///
+    /// Adds a culture entry to the pack.
+    pub fn with_culture(mut self, entry: PackEntry<Culture>) -> Self {
+        self.cultures.push(entry);
+        self
+    }
+
+    /// # Imports
+    ///
+    /// Maps a local id of the pack to something loaded by an earlier pack.
+    pub fn imports(mut self, kind: DataKind, local: usize, name: String) -> Self {
+        self.imports.insert((kind, local), name);
+        self
+    }
+}
+
+/// # Pack Entry
+///
+/// A single change a pack makes to data.
+#[derive(Debug, Clone)]
+pub enum PackEntry<T> {
+    /// Add something new. It's name must not already exist.
+    Add(T),
+    /// Replace something that already exists with the same name.
+    Override(T),
+    /// Remove the thing with the given name.
+    Remove(String),
+}
+
+/// # Pack Registry
+///
+/// Keeps track of the packs loaded into data and the mapping from names to ids.
+///
+/// Once a name is given an id it keeps it, even if it's removed by a later
+/// pack, so re-adding it gets it back in the same place.
+pub struct PackRegistry {
+    /// The names recorded for each kind and the id they map to.
+    names: HashMap<DataKind, HashMap<String, usize>>,
+    /// The next free id for each kind.
+    next_id: HashMap<DataKind, usize>,
+    /// The packs loaded so far, in order.
+    pub loaded: Vec<String>,
+}
+
+impl PackRegistry {
+    /// # New
+    ///
+    /// Creates an empty registry, with Time already reserved.
+    pub fn new() -> Self {
+        let mut names: HashMap<DataKind, HashMap<String, usize>> = HashMap::new();
+        names.entry(DataKind::Good).or_default()
+            .insert(String::from("Time"), TIME_ID);
+        let mut next_id = HashMap::new();
+        next_id.insert(DataKind::Good, TIME_ID + 1);
+        Self {
+            names,
+            next_id,
+            loaded: vec![],
+        }
+    }
+
+    /// # Id Of
+    ///
+    /// Gets the id a name is mapped to, if it's been recorded.
+    pub fn id_of(&self, kind: DataKind, name: &str) -> Option<usize> {
+        self.names.get(&kind)
+            .and_then(|x| x.get(name))
+            .copied()
+    }
+
+    /// # Name Of
+    ///
+    /// Gets the name an id is mapped to, if it's been recorded.
+    pub fn name_of(&self, kind: DataKind, id: usize) -> Option<&str> {
+        self.names.get(&kind)?
+            .iter()
+            .find(|(_, x)| **x == id)
+            .map(|(name, _)| name.as_str())
+    }
+
+    /// # Reserve
+    ///
+    /// Gets the id for the name, giving it the next free id if it doesn't have
+    /// one yet.
+    fn reserve(&mut self, kind: DataKind, name: &str) -> usize {
+        if let Some(id) = self.id_of(kind, name) {
+            return id;
+        }
+        let next = self.next_id.entry(kind).or_insert(0);
+        let id = *next;
+        *next += 1;
+        self.names.entry(kind).or_default().insert(name.to_string(), id);
+        id
+    }
+
+    /// # Load Pack
+    ///
+    /// Loads a pack into data on top of everything already there.
+    ///
+    /// All names in the pack are mapped to ids first, so entries can refer to
+    /// anything else in the pack. Then adds and overrides are applied wants
+    /// first, then goods, processes, species, and cultures. Removals are done
+    /// last, in the reverse order, and strip out anything that depended on
+    /// what was removed.
+    ///
+    /// Anything which can't be applied is skipped and recorded as a conflict
+    /// in the report, the rest of the pack is still loaded.
+    pub fn load_pack(&mut self, data: &mut Data, pack: DataPack) -> PackReport {
+        let mut report = PackReport::new(pack.name.clone());
+        if !data.goods.contains_key(&TIME_ID) {
+            data.add_time();
+        }
+        let mut maps = IdMaps::new();
+        maps.insert(DataKind::Good, TIME_ID, TIME_ID);
+        for ((kind, local), name) in pack.imports.iter()
+        .sorted_by_key(|((kind, local), _)| (*kind as usize, *local)) {
+            match self.id_of(*kind, name) {
+                Some(id) => maps.insert(*kind, *local, id),
+                None => report.conflicts.push(PackConflict::UnknownImport {
+                    kind: *kind, name: name.clone() }),
+            }
+        }
+
+        // map everything to their ids first.
+        let wants = self.stage(pack.wants, |id| data.wants.contains_key(&id),
+            &mut maps, &mut report);
+        let goods = self.stage(pack.goods, |id| data.goods.contains_key(&id),
+            &mut maps, &mut report);
+        let processes = self.stage(pack.processes, |id| data.processes.contains_key(&id),
+            &mut maps, &mut report);
+        let species = self.stage(pack.species, |id| data.species.contains_key(&id),
+            &mut maps, &mut report);
+        let cultures = self.stage(pack.cultures, |id| data.culture.contains_key(&id),
+            &mut maps, &mut report);
+
+        // wants
+        for (add, want) in wants.changes {
+            let name = want.key();
+            let want = remap_want(&maps, want);
+            let result = if add {
+                data.try_add_want(want)
+            } else {
+                data.replace_want(want).map(|_| ())
+            };
+            report.record(DataKind::Want, name, add, result);
+        }
+        // goods, class bases go first so their members can be added.
+        for (add, good) in goods.changes.into_iter()
+        .sorted_by_key(|(_, good)| good.class != Some(good.id)) {
+            let name = good.key();
+            let result = remap_good(&maps, good).and_then(|good| if add {
+                data.try_add_good(good)
+            } else {
+                data.replace_good(good).map(|_| ())
+            });
+            report.record(DataKind::Good, name, add, result);
+        }
+        // processes
+        for (add, process) in processes.changes {
+            let name = process.key();
+            let result = remap_process(&maps, process).and_then(|process| if add {
+                data.try_add_process(process)
+            } else {
+                data.replace_process(process).map(|_| ())
+            });
+            report.record(DataKind::Process, name, add, result);
+        }
+        // species
+        for (add, mut species) in species.changes {
+            let name = species.key();
+            let result = remap_desires(&maps, &mut species.desires)
+            .and_then(|_| {
+                species.id = maps.get(DataKind::Species, species.id)?;
+                if add {
+                    data.try_add_species(species)
+                } else {
+                    data.replace_species(species).map(|_| ())
+                }
+            });
+            report.record(DataKind::Species, name, add, result);
+        }
+        // cultures
+        for (add, mut culture) in cultures.changes {
+            let name = culture.key();
+            let result = remap_desires(&maps, &mut culture.desires)
+            .and_then(|_| {
+                culture.id = maps.get(DataKind::Culture, culture.id)?;
+                if add {
+                    data.try_add_culture(culture)
+                } else {
+                    data.replace_culture(culture).map(|_| ())
+                }
+            });
+            report.record(DataKind::Culture, name, add, result);
+        }
+
+        // removals, dependents first.
+        for (name, id) in cultures.removals {
+            report.record_removal(DataKind::Culture, name,
+                data.remove_culture(id).map(|_| vec![]));
+        }
+        for (name, id) in species.removals {
+            report.record_removal(DataKind::Species, name,
+                data.remove_species(id).map(|_| vec![]));
+        }
+        for (name, id) in processes.removals {
+            report.record_removal(DataKind::Process, name,
+                data.remove_process(id, RemoveMode::Cascade).map(|x| x.cascaded));
+        }
+        for (name, id) in goods.removals {
+            report.record_removal(DataKind::Good, name,
+                data.remove_good(id, RemoveMode::Cascade).map(|x| x.cascaded));
+        }
+        for (name, id) in wants.removals {
+            report.record_removal(DataKind::Want, name,
+                data.remove_want(id, RemoveMode::Cascade).map(|x| x.cascaded));
+        }
+
+        self.loaded.push(pack.name);
+        report
+    }
+
+    /// # Stage
+    ///
+    /// Maps the entries of one kind in a pack to their ids, splitting them into
+    /// the changes to make and removals to do.
+    ///
+    /// Entries which can't be applied are recorded as conflicts and dropped.
+    fn stage<T: PackItem>(&mut self, entries: Vec<PackEntry<T>>, exists: impl Fn(usize) -> bool,
+    maps: &mut IdMaps, report: &mut PackReport) -> Staged<T> {
+        let mut staged = Staged { changes: vec![], removals: vec![] };
+        let mut seen = HashSet::new();
+        for entry in entries {
+            let (name, local) = match &entry {
+                PackEntry::Add(item) | PackEntry::Override(item) => (item.key(), Some(item.local_id())),
+                PackEntry::Remove(name) => (name.clone(), None),
+            };
+            if T::KIND == DataKind::Good &&
+            (self.id_of(T::KIND, &name) == Some(TIME_ID) || local == Some(TIME_ID)) {
+                report.conflicts.push(PackConflict::ReservedTime { name });
+                continue;
+            }
+            if !seen.insert(name.clone()) ||
+            local.is_some_and(|local| maps.contains(T::KIND, local)) {
+                report.conflicts.push(PackConflict::Duplicate { kind: T::KIND, name });
+                continue;
+            }
+            let existing = self.id_of(T::KIND, &name).filter(|id| exists(*id));
+            match (entry, existing) {
+                (PackEntry::Add(item), None) => {
+                    let id = self.reserve(T::KIND, &name);
+                    maps.insert(T::KIND, item.local_id(), id);
+                    staged.changes.push((true, item));
+                },
+                (PackEntry::Override(item), Some(id)) => {
+                    maps.insert(T::KIND, item.local_id(), id);
+                    staged.changes.push((false, item));
+                },
+                (PackEntry::Remove(_), Some(id)) => staged.removals.push((name, id)),
+                (PackEntry::Add(_), Some(_)) => report.conflicts
+                    .push(PackConflict::AlreadyExists { kind: T::KIND, name }),
+                (_, None) => report.conflicts
+                    .push(PackConflict::NotFound { kind: T::KIND, name }),
+            }
+        }
+        staged
+    }
+}
+
+impl Default for PackRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// # Pack Report
+///
+/// What loading a pack did to data and anything that conflicted with it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PackReport {
+    /// The name of the pack loaded.
+    pub pack: String,
+    /// Everything newly added.
+    pub added: Vec<(DataKind, String)>,
+    /// Everything which replaced an existing definition.
+    pub overridden: Vec<(DataKind, String)>,
+    /// Everything removed.
+    pub removed: Vec<(DataKind, String)>,
+    /// Everything which couldn't be applied cleanly.
+    pub conflicts: Vec<PackConflict>,
+}
+
+impl PackReport {
+    pub fn new(pack: String) -> Self {
+        Self {
+            pack,
+            added: vec![],
+            overridden: vec![],
+            removed: vec![],
+            conflicts: vec![],
+        }
+    }
+
+    /// # Is Clean
+    ///
+    /// Whether the pack loaded without any conflicts.
+    pub fn is_clean(&self) -> bool {
+        self.conflicts.is_empty()
+    }
+
+    /// # Record
+    ///
+    /// Records the result of an add or override.
+    fn record(&mut self, kind: DataKind, name: String, add: bool, result: Result<(), String>) {
+        match result {
+            Ok(_) if add => self.added.push((kind, name)),
+            Ok(_) => self.overridden.push((kind, name)),
+            Err(reason) => self.conflicts.push(PackConflict::Rejected { kind, name, reason }),
+        }
+    }
+
+    /// # Record Removal
+    ///
+    /// Records the result of a removal, noting anything that was cascaded.
+    fn record_removal(&mut self, kind: DataKind, name: String,
+    result: Result<Vec<DataReference>, String>) {
+        match result {
+            Ok(cascaded) => {
+                if !cascaded.is_empty() {
+                    self.conflicts.push(PackConflict::Cascaded { kind, name: name.clone(),
+                        references: cascaded });
+                }
+                self.removed.push((kind, name));
+            },
+            Err(reason) => self.conflicts.push(PackConflict::Rejected { kind, name, reason }),
+        }
+    }
+}
+
+/// # Pack Conflict
+///
+/// Something in a pack which didn't apply cleanly to data.
+#[derive(Debug, Clone, PartialEq)]
+pub enum PackConflict {
+    /// The pack tried to add something which already exists.
+    AlreadyExists { kind: DataKind, name: String },
+    /// The pack tried to override or remove something which doesn't exist.
+    NotFound { kind: DataKind, name: String },
+    /// The pack has the same name or local id more than once.
+    Duplicate { kind: DataKind, name: String },
+    /// The pack tried to define, change, or remove Time.
+    ReservedTime { name: String },
+    /// The pack imports something no earlier pack has defined.
+    UnknownImport { kind: DataKind, name: String },
+    /// Data refused the entry.
+    Rejected { kind: DataKind, name: String, reason: String },
+    /// Removing the entry stripped references out of other data.
+    Cascaded { kind: DataKind, name: String, references: Vec<DataReference> },
+}
+
+impl fmt::Display for PackConflict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PackConflict::AlreadyExists { kind, name } =>
+                write!(f, "{:?} '{}' already exists.", kind, name),
+            PackConflict::NotFound { kind, name } =>
+                write!(f, "{:?} '{}' does not exist.", kind, name),
+            PackConflict::Duplicate { kind, name } =>
+                write!(f, "{:?} '{}' is defined more than once in the pack.", kind, name),
+            PackConflict::ReservedTime { name } =>
+                write!(f, "Good '{}' uses Time, which is reserved.", name),
+            PackConflict::UnknownImport { kind, name } =>
+                write!(f, "Imported {:?} '{}' has not been loaded.", kind, name),
+            PackConflict::Rejected { kind, name, reason } =>
+                write!(f, "{:?} '{}' was rejected: {}", kind, name, reason),
+            PackConflict::Cascaded { kind, name, references } =>
+                write!(f, "Removing {:?} '{}' removed: {}", kind, name, references.iter().join(", ")),
+        }
+    }
+}
+
+/// # Pack Item
+///
+/// Something which can be placed in a pack.
+trait PackItem {
+    const KIND: DataKind;
+    /// The id it has within the pack.
+    fn local_id(&self) -> usize;
+    /// The name used to map it to it's id.
+    fn key(&self) -> String;
+}
+
+impl PackItem for Want {
+    const KIND: DataKind = DataKind::Want;
+    fn local_id(&self) -> usize { self.id }
+    fn key(&self) -> String { self.name.clone() }
+}
+
+impl PackItem for Good {
+    const KIND: DataKind = DataKind::Good;
+    fn local_id(&self) -> usize { self.id }
+    fn key(&self) -> String { full_name(&self.name, &self.variant_name) }
+}
+
+impl PackItem for Process {
+    const KIND: DataKind = DataKind::Process;
+    fn local_id(&self) -> usize { self.id }
+    fn key(&self) -> String { full_name(&self.name, &self.variant_name) }
+}
+
+impl PackItem for Species {
+    const KIND: DataKind = DataKind::Species;
+    fn local_id(&self) -> usize { self.id }
+    fn key(&self) -> String { self.name.clone() }
+}
+
+impl PackItem for Culture {
+    const KIND: DataKind = DataKind::Culture;
+    fn local_id(&self) -> usize { self.id }
+    fn key(&self) -> String { self.name.clone() }
+}
+
+/// The entries of one kind, mapped and ready to apply.
+struct Staged<T> {
+    /// Adds (true) and overrides (false).
+    changes: Vec<(bool, T)>,
+    /// The name and id of everything to remove.
+    removals: Vec<(String, usize)>,
+}
+
+/// # Id Maps
+///
+/// The mapping from a pack's local ids to the ids in data.
+struct IdMaps {
+    maps: HashMap<DataKind, HashMap<usize, usize>>,
+}
+
+impl IdMaps {
+    fn new() -> Self {
+        Self { maps: HashMap::new() }
+    }
+
+    fn insert(&mut self, kind: DataKind, local: usize, id: usize) {
+        self.maps.entry(kind).or_default().insert(local, id);
+    }
+
+    fn contains(&self, kind: DataKind, local: usize) -> bool {
+        self.maps.get(&kind).is_some_and(|x| x.contains_key(&local))
+    }
+
+    /// # Get
+    ///
+    /// Gets the id in data for the local id.
+    fn get(&self, kind: DataKind, local: usize) -> Result<usize, String> {
+        self.maps.get(&kind)
+            .and_then(|x| x.get(&local))
+            .copied()
+            .ok_or(format!("Local {:?} '{}' is not defined or imported by the pack.", kind, local))
+    }
+
+    /// # Get Item
+    ///
+    /// Gets the item in data for the local item. Classes are goods.
+    fn get_item(&self, item: Item) -> Result<Item, String> {
+        Ok(match item {
+            Item::Want(id) => Item::Want(self.get(DataKind::Want, id)?),
+            Item::Class(id) => Item::Class(self.get(DataKind::Good, id)?),
+            Item::Good(id) => Item::Good(self.get(DataKind::Good, id)?),
+        })
+    }
+
+    fn get_wants(&self, wants: &HashMap<usize, f64>) -> Result<HashMap<usize, f64>, String> {
+        wants.iter()
+            .map(|(want, eff)| self.get(DataKind::Want, *want).map(|want| (want, *eff)))
+            .collect()
+    }
+}
+
+fn remap_want(maps: &IdMaps, mut want: Want) -> Want {
+    // wants are always staged, so they are always mapped.
+    want.id = maps.get(DataKind::Want, want.id).unwrap();
+    want
+}
+
+fn remap_good(maps: &IdMaps, mut good: Good) -> Result<Good, String> {
+    good.id = maps.get(DataKind::Good, good.id)?;
+    if let Some(class) = good.class {
+        good.class = Some(maps.get(DataKind::Good, class)?);
+    }
+    if let Some((target, eff)) = good.decays_to {
+        good.decays_to = Some((maps.get(DataKind::Good, target)?, eff));
+    }
+    good.consumption_wants = maps.get_wants(&good.consumption_wants)?;
+    good.use_wants = maps.get_wants(&good.use_wants)?;
+    good.own_wants = maps.get_wants(&good.own_wants)?;
+    Ok(good)
+}
+
+fn remap_process(maps: &IdMaps, mut process: Process) -> Result<Process, String> {
+    process.id = maps.get(DataKind::Process, process.id)?;
+    if let Some(parent) = process.parent {
+        process.parent = Some(maps.get(DataKind::Process, parent)?);
+    }
+    let mut inputs = std::mem::take(&mut process.inputs);
+    for input in inputs.iter_mut() {
+        input.good = maps.get(DataKind::Good, input.good)?;
+    }
+    // re-add so they are sorted by their new ids.
+    process = process.uses_inputs(inputs);
+    for output in process.outputs.iter_mut() {
+        output.item = maps.get_item(output.item)?;
+    }
+    Ok(process)
+}
+
+fn remap_desires(maps: &IdMaps, desires: &mut [Desire]) -> Result<(), String> {
+    for desire in desires.iter_mut() {
+        desire.item = maps.get_item(desire.item)?;
+    }
+    Ok(())
+}
@@ -1,3 +1,7 @@
+use std::fmt;
+
+use itertools::Itertools;
+
 /// # Demand Curves
 /// 
 /// Defines how valuation of a unit of desire changes over time.
@@ -31,7 +35,21 @@
 /// Root: Factor is the multiplier outside of our squore root. It should be a positive
 /// value.
 /// Start - (factor * sqrt(n))
-#[derive(Clone, Copy, Debug, PartialEq)]
+/// 
+/// Piecewise Linear: A series of breakpoints (step, change from start), with 
+/// straight lines between them. Starts at (0, 0) and stays flat after the last 
+/// breakpoint.
+/// 
+/// Tabular: An explicit change from start for each step. Partial steps have the
+/// value of the whole step they are in, and steps past the end keep the last value.
+/// 
+/// Exponential Decay: Decays from start towards a floor it never passes.
+/// floor + (start - floor) * e^(-rate * n)
+/// 
+/// Logistic: Holds near start, then drops off quickly around the midpoint, 
+/// approaching 0.
+/// start * (1 + e^(-steepness * midpoint)) / (1 + e^(steepness * (n - midpoint)))
+#[derive(Clone, Debug, PartialEq)]
 pub enum DemandCurve {
     /// Linear Value Function. 
     /// start + slope * n
@@ -64,6 +82,36 @@ pub enum DemandCurve {
     /// 
     /// 0 = (start / factor)^2
     Root{factor: f64},
+    /// Piecewise Linear Value Function
+    /// start + the change at n, found by drawing straight lines between the 
+    /// breakpoints.
+    /// 
+    /// Points are (step, change from start), sorted by step, with steps above 0 and
+    /// changes which never increase. (0, 0) is always the first point and the
+    /// value stays flat after the last point.
+    PiecewiseLinear{points: Vec<(f64, f64)>},
+    /// Tabular Value Function
+    /// start + values[floor(n)]
+    /// 
+    /// Values are the change from start at each step. The first must be 0 and 
+    /// they should never increase. Steps past the end keep the last value.
+    /// An empty table is flat and always returns start.
+    Tabular{values: Vec<f64>},
+    /// Exponential Decay Value Function
+    /// floor + (start - floor) * e^(-rate * n)
+    /// 
+    /// Rate must be a Positive Value.
+    /// 
+    /// Never reaches the floor. If start is below the floor it will rise towards 
+    /// it instead.
+    ExponentialDecay{rate: f64, floor: f64},
+    /// Logistic Value Function
+    /// start * (1 + e^(-steepness * midpoint)) / (1 + e^(steepness * (n - midpoint)))
+    /// 
+    /// Steepness must be a Positive Value, midpoint must be non-negative.
+    /// 
+    /// Reaches half of start at about the midpoint, never reaches 0.
+    Logistic{steepness: f64, midpoint: f64},
 }
 
 impl DemandCurve {
@@ -99,6 +147,141 @@ impl DemandCurve {
         Self::Logarithmic { factor }
     }
 
+    /// # Piecewise Linear
+    /// 
+    /// Safely creates a Piecewise Linear Value Function.
+    pub fn piecewise_linear(points: Vec<(f64, f64)>) -> Self {
+        assert!(!points.is_empty(), "Must have at least one point!");
+        let mut prev = (0.0, 0.0);
+        for &(step, change) in points.iter() {
+            assert!(step > prev.0, "Points must be sorted by step, with steps greater than 0!");
+            assert!(change <= prev.1, "Points must never increase in value!");
+            prev = (step, change);
+        }
+        Self::PiecewiseLinear { points }
+    }
+
+    /// # Tabular
+    /// 
+    /// Safely creates a Tabular Value Function.
+    pub fn tabular(values: Vec<f64>) -> Self {
+        assert!(values.first() == Some(&0.0), "First value must be 0.0!");
+        assert!(values.iter().tuple_windows().all(|(a, b)| b <= a), "Values must never increase!");
+        Self::Tabular { values }
+    }
+
+    /// # Exponential Decay
+    /// 
+    /// Safely creates an Exponential Decay Value Function.
+    pub fn exponential_decay(rate: f64, floor: f64) -> Self {
+        assert!(rate > 0.0, "Rate must be a positive value!");
+        assert!(!floor.is_nan(), "Floor must be a number!");
+        Self::ExponentialDecay { rate, floor }
+    }
+
+    /// # Logistic
+    /// 
+    /// Safely creates a Logistic Value Function.
+    pub fn logistic(steepness: f64, midpoint: f64) -> Self {
+        assert!(steepness > 0.0, "Steepness must be a positive value!");
+        assert!(midpoint >= 0.0, "Midpoint must be non-negative!");
+        Self::Logistic { steepness, midpoint }
+    }
+
+    /// # Parse
+    /// 
+    /// Parses a curve from it's text form, as written by Display.
+    /// 
+    /// - linear(slope)
+    /// - geometric(factor)
+    /// - logarithmic(factor)
+    /// - root(factor)
+    /// - piecewise(step:change, ...)
+    /// - tabular(change, ...)
+    /// - exponential(rate, floor)
+    /// - logistic(steepness, midpoint)
+    /// 
+    /// Applies the same checks as the safe constructors, returning Err instead
+    /// of panicking.
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let text = text.trim();
+        let (name, args) = text.strip_suffix(')')
+            .and_then(|x| x.split_once('('))
+            .ok_or(format!("Demand curve '{}' must be in the form name(args).", text))?;
+        let args: Vec<&str> = if args.trim().is_empty() { vec![] } 
+            else { args.split(',').map(|x| x.trim()).collect() };
+        let number = |arg: &str| arg.parse::<f64>()
+            .map_err(|_| format!("'{}' in demand curve '{}' is not a number.", arg, text))
+            .and_then(|x| if x.is_finite() { Ok(x) } else { Err(format!("Non-finite number in demand curve '{}'.", text)) });
+        let single = || -> Result<f64, String> {
+            match args.as_slice() {
+                [arg] => number(arg),
+                _ => Err(format!("Demand curve '{}' takes 1 value.", text)),
+            }
+        };
+        let pair = || -> Result<(f64, f64), String> {
+            match args.as_slice() {
+                [a, b] => Ok((number(a)?, number(b)?)),
+                _ => Err(format!("Demand curve '{}' takes 2 values.", text)),
+            }
+        };
+        match name.trim() {
+            "linear" => {
+                let slope = single()?;
+                if slope >= 0.0 { return Err(String::from("Slope must be a Negative value!")); }
+                Ok(Self::Linear { slope })
+            },
+            "geometric" => {
+                let factor = single()?;
+                if !(1.0 > factor && factor > 0.0) { return Err(String::from("Factor must be between 0.0 and 1.0 exclusive!")); }
+                Ok(Self::Geometric { factor })
+            },
+            "logarithmic" => {
+                let factor = single()?;
+                if factor <= 1.0 { return Err(String::from("Factor must be greater than 1.0")); }
+                Ok(Self::Logarithmic { factor })
+            },
+            "root" => {
+                let factor = single()?;
+                if factor <= 0.0 { return Err(String::from("Factor must be a positive value!")); }
+                Ok(Self::Root { factor })
+            },
+            "piecewise" => {
+                if args.is_empty() { return Err(String::from("Must have at least one point!")); }
+                let mut points = vec![];
+                let mut prev = (0.0, 0.0);
+                for arg in args.iter() {
+                    let (step, change) = arg.split_once(':')
+                        .ok_or(format!("Point '{}' must be in the form step:change.", arg))?;
+                    let point = (number(step.trim())?, number(change.trim())?);
+                    if point.0 <= prev.0 { return Err(String::from("Points must be sorted by step, with steps greater than 0!")); }
+                    if point.1 > prev.1 { return Err(String::from("Points must never increase in value!")); }
+                    points.push(point);
+                    prev = point;
+                }
+                Ok(Self::PiecewiseLinear { points })
+            },
+            "tabular" => {
+                let values = args.iter().map(|x| number(x)).collect::<Result<Vec<f64>, String>>()?;
+                if values.first() != Some(&0.0) { return Err(String::from("First value must be 0.0!")); }
+                if !values.iter().tuple_windows().all(|(a, b)| b <= a) { return Err(String::from("Values must never increase!")); }
+                Ok(Self::Tabular { values })
+            },
+            "exponential" => {
+                let (rate, floor) = pair()?;
+                if rate <= 0.0 { return Err(String::from("Rate must be a positive value!")); }
+                Ok(Self::ExponentialDecay { rate, floor })
+            },
+            "logistic" => {
+                let (steepness, midpoint) = pair()?;
+                if steepness <= 0.0 { return Err(String::from("Steepness must be a positive value!")); }
+                if midpoint < 0.0 { return Err(String::from("Midpoint must be non-negative!")); }
+                Ok(Self::Logistic { steepness, midpoint })
+            },
+            _ => Err(format!("Unknown demand curve '{}'.", name)),
+        }
+    }
+
     /// # Value
     /// 
     /// Calculates and returns the current priority value of the desire
@@ -119,7 +302,29 @@ impl DemandCurve {
             },
             DemandCurve::Logarithmic { factor } => {
                 start - (n + 1.0).log(*factor)
-            }
+            },
+            DemandCurve::PiecewiseLinear { points } => {
+                let mut prev = (0.0, 0.0);
+                for &(step, change) in points.iter() {
+                    if n <= step {
+                        return start + prev.1 + (change - prev.1) * (n - prev.0) / (step - prev.0);
+                    }
+                    prev = (step, change);
+                }
+                start + prev.1
+            },
+            DemandCurve::Tabular { values } => {
+                // an empty table is treated as flat, always at start.
+                let step = (n.max(0.0).floor() as usize).min(values.len().saturating_sub(1));
+                start + values.get(step).copied().unwrap_or(0.0)
+            },
+            DemandCurve::ExponentialDecay { rate, floor } => {
+                floor + (start - floor) * (-rate * n).exp()
+            },
+            DemandCurve::Logistic { steepness, midpoint } => {
+                start * (1.0 + (-steepness * midpoint).exp()) / 
+                    (1.0 + (steepness * (n - midpoint)).exp())
+            },
         }
     }
 
@@ -164,6 +369,9 @@ impl DemandCurve {
     /// # Inverse
     /// 
     /// Given a priority, it returns the step it's on.
+    /// 
+    /// For curves which flatten out, if the value is never reached, it returns
+    /// infinity. Flat sections return the first step the value is reached.
     pub fn inverse(&self, start: f64, value: f64) -> f64 {
         match self {
             DemandCurve::Linear { slope } => {
@@ -178,6 +386,42 @@ impl DemandCurve {
             DemandCurve::Logarithmic { factor } => {
                 factor.powf(start - value) - 1.0
             },
+            DemandCurve::PiecewiseLinear { points } => {
+                let target = value - start;
+                let mut prev = (0.0, 0.0);
+                for &(step, change) in points.iter() {
+                    if target >= change {
+                        if change == prev.1 { // flat, reached at the start of it.
+                            return prev.0;
+                        }
+                        return prev.0 + (target - prev.1) * (step - prev.0) / (change - prev.1);
+                    }
+                    prev = (step, change);
+                }
+                f64::INFINITY
+            },
+            DemandCurve::Tabular { values } => {
+                if values.is_empty() {
+                    return if start <= value { 0.0 } else { f64::INFINITY };
+                }
+                values.iter()
+                    .position(|x| start + x <= value)
+                    .map_or(f64::INFINITY, |x| x as f64)
+            },
+            DemandCurve::ExponentialDecay { rate, floor } => {
+                let ratio = (value - floor) / (start - floor);
+                if ratio <= 0.0 {
+                    return f64::INFINITY;
+                }
+                -ratio.ln() / rate
+            },
+            DemandCurve::Logistic { steepness, midpoint } => {
+                let ratio = start * (1.0 + (-steepness * midpoint).exp()) / value - 1.0;
+                if ratio <= 0.0 || ratio.is_nan() {
+                    return f64::INFINITY;
+                }
+                midpoint + ratio.ln() / steepness
+            },
         }
     }
 
@@ -185,8 +429,12 @@ impl DemandCurve {
     /// 
     /// Calculates the derivative/slope of the function at a particular point.
     /// 
+    /// Start is needed for those curves which scale with it.
+    /// 
+    /// Tabular is flat within each step, so it is always 0.
+    /// 
     /// NOTE: Not used currently. No real use for it.
-    pub fn derivative(&self, step: f64) -> f64 {
+    pub fn derivative(&self, start: f64, step: f64) -> f64 {
         match self {
             DemandCurve::Linear { slope } => {
                 *slope
@@ -197,7 +445,28 @@ impl DemandCurve {
             DemandCurve::Geometric { factor } => {
                 factor * (1.0 + step).ln() * (1.0 * step).powf(factor * step)
             },
-            DemandCurve::Logarithmic { factor } => todo!(),
+            DemandCurve::Logarithmic { factor } => {
+                -1.0 / ((step + 1.0) * factor.ln())
+            },
+            DemandCurve::PiecewiseLinear { points } => {
+                let mut prev = (0.0, 0.0);
+                for &(point, change) in points.iter() {
+                    if step < point {
+                        return (change - prev.1) / (point - prev.0);
+                    }
+                    prev = (point, change);
+                }
+                0.0
+            },
+            DemandCurve::Tabular { .. } => 0.0,
+            DemandCurve::ExponentialDecay { rate, floor } => {
+                -rate * (start - floor) * (-rate * step).exp()
+            },
+            DemandCurve::Logistic { steepness, midpoint } => {
+                let growth = (steepness * (step - midpoint)).exp();
+                -start * (1.0 + (-steepness * midpoint).exp()) * steepness * growth / 
+                    (1.0 + growth).powf(2.0)
+            },
         }
     }
 
@@ -222,39 +491,53 @@ impl DemandCurve {
                 let diffy = slope * diffx;
                 ((diffx).powf(2.0) + (diffy).powf(2.0)).sqrt()
             },
-            DemandCurve::Root { .. } => {
-                let diff = end - start; // get distance between start and endof the interval
-                let step_size = diff / 8.0; // divide it up
-                let mut acc = 0.0; // distance accumulator
-                for cl in 0..8 { // step 7 times (8 points)
-                    // get our end point steps
-                    let lower_step = cl as f64 * step_size;
-                    let upper_step = (cl + 1) as f64 * step_size;
-                    // get our end point ys.
-                    let lowery = self.value(start, lower_step);
-                    let uppery = self.value(start, upper_step);
-                    // add distance to our accumulator
-                    acc += (step_size.powf(2.0) + (uppery - lowery).powf(2.0)).sqrt();
-                }
-                acc
-            },
-            DemandCurve::Geometric { .. } => {
-                let diff = end - start; // get distance between start and endof the interval
-                let step_size = diff / 8.0; // divide it up
-                let mut acc = 0.0; // distance accumulator
-                for cl in 0..8 { // step 7 times (8 points)
-                    // get our end point steps
-                    let lower_step = cl as f64 * step_size;
-                    let upper_step = (cl + 1) as f64 * step_size;
-                    // get our end point ys.
-                    let lowery = self.value(start, lower_step);
-                    let uppery = self.value(start, upper_step);
-                    // add distance to our accumulator
-                    acc += (step_size.powf(2.0) + (uppery - lowery).powf(2.0)).sqrt();
-                }
-                acc
+            DemandCurve::Root { .. } |
+            DemandCurve::Geometric { .. } |
+            DemandCurve::Logarithmic { .. } |
+            DemandCurve::PiecewiseLinear { .. } |
+            DemandCurve::Tabular { .. } |
+            DemandCurve::ExponentialDecay { .. } |
+            DemandCurve::Logistic { .. } => {
+                self.approximate_arc_length(start, end)
             },
-            DemandCurve::Logarithmic { factor } => todo!(),
         }
     }
-}
\ No newline at end of file
+
+    /// # Approximate Arc Length
+    /// 
+    /// The 8 step approximation of arc length used by those curves without a 
+    /// simple arc length integral.
+    /// 
+    /// For tabular curves this undercounts the jumps between steps.
+    fn approximate_arc_length(&self, start: f64, end: f64) -> f64 {
+        let diff = end - start; // get distance between start and endof the interval
+        let step_size = diff / 8.0; // divide it up
+        let mut acc = 0.0; // distance accumulator
+        for cl in 0..8 { // step 7 times (8 points)
+            // get our end point steps
+            let lower_step = cl as f64 * step_size;
+            let upper_step = (cl + 1) as f64 * step_size;
+            // get our end point ys.
+            let lowery = self.value(start, lower_step);
+            let uppery = self.value(start, upper_step);
+            // add distance to our accumulator
+            acc += (step_size.powf(2.0) + (uppery - lowery).powf(2.0)).sqrt();
+        }
+        acc
+    }
+}
+impl fmt::Display for DemandCurve {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DemandCurve::Linear { slope } => write!(f, "linear({})", slope),
+            DemandCurve::Geometric { factor } => write!(f, "geometric({})", factor),
+            DemandCurve::Logarithmic { factor } => write!(f, "logarithmic({})", factor),
+            DemandCurve::Root { factor } => write!(f, "root({})", factor),
+            DemandCurve::PiecewiseLinear { points } => write!(f, "piecewise({})", 
+                points.iter().map(|(step, change)| format!("{}:{}", step, change)).join(", ")),
+            DemandCurve::Tabular { values } => write!(f, "tabular({})", values.iter().join(", ")),
+            DemandCurve::ExponentialDecay { rate, floor } => write!(f, "exponential({}, {})", rate, floor),
+            DemandCurve::Logistic { steepness, midpoint } => write!(f, "logistic({}, {})", steepness, midpoint),
+        }
+    }
+}
@@ -1,6 +1,8 @@
-use std::{mem::discriminant, num::{NonZero, NonZeroUsize}};
+use std::{fmt, mem::discriminant, num::{NonZero, NonZeroUsize}};
 
-use crate::{demandcurve::DemandCurve, household::HouseholdMember, item::Item};
+use itertools::Itertools;
+
+use crate::{constants::{HABIT_MEMORY, NORMALIZED_SATISFACTION_HORIZON}, data::Data, demandcurve::DemandCurve, good::full_name, household::HouseholdMember, item::Item};
 
 /// # Desire
 /// 
@@ -16,7 +18,7 @@ use crate::{demandcurve::DemandCurve, household::HouseholdMember, item::Item};
 /// The Priority Function defines how each new step (satisfaction / amount) increases
 /// the current priority value. This is a smooth function, so partial satisfaction still
 /// creates a useful value.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct Desire {
     /// The desired Item.
     pub item: Item,
@@ -142,6 +144,31 @@ impl Desire {
         self
     }
 
+    /// # Check
+    /// 
+    /// Checks what with_tag() and with_steps() assert, for desires which 
+    /// were built or changed without them.
+    /// 
+    /// Every pair of tags must be safe together, see DesireTag::safe_with(),
+    /// and a LifeNeed must have a finite number of steps and a mortality 
+    /// greater than 0.0 and no greater than 1.0.
+    pub fn check(&self) -> Result<(), String> {
+        for (idx, tag) in self.tags.iter().enumerate() {
+            if let DesireTag::LifeNeed(mortality) = tag {
+                if self.steps.is_none() {
+                    return Err(String::from("A Desire with the tag LifeNeed must have a finite number of steps."));
+                }
+                if !(*mortality > 0.0 && *mortality <= 1.0) {
+                    return Err(String::from("Mortality must be greater than 0.0, and no greater than 1.0."));
+                }
+            }
+            for other in self.tags.iter().skip(idx + 1) {
+                tag.safe_with(other)?;
+            }
+        }
+        Ok(())
+    }
+
     /// # Max Satisfaction
     /// 
     /// Calculates the maximum satisfaction possible for this desire.
@@ -191,6 +218,48 @@ impl Desire {
         self.satisfaction / self.amount
     }
 
+    /// # Normalized Satisfaction
+    /// 
+    /// The satisfaction of the desire in units of satisfaction, rather than 
+    /// steps.
+    /// 
+    /// Each satisfied step is weighted by value(step) / total_value(start, steps),
+    /// so a fully satisfied desire is always worth 1.0, no matter how small 
+    /// it's amount is. Like total_value, partial steps give a partial weight.
+    /// 
+    /// Steps only count until the demand curve reaches 0.0. Desires without
+    /// an end which never reach 0.0 are counted up to 
+    /// NORMALIZED_SATISFACTION_HORIZON steps.
+    pub fn normalized_satisfaction(&self) -> f64 {
+        let mut span = self.demand_fn.inverse(self.starting_value, 0.0);
+        if !span.is_finite() || span <= 0.0 {
+            span = NORMALIZED_SATISFACTION_HORIZON;
+        }
+        if let Some(steps) = self.steps {
+            span = span.min(steps.get() as f64);
+        } else {
+            span = span.min(NORMALIZED_SATISFACTION_HORIZON);
+        }
+        let total = self.demand_fn.total_value(self.starting_value, span);
+        if total <= 0.0 {
+            return 0.0;
+        }
+        let satisfied = self.satisfied_steps().min(span);
+        self.demand_fn.total_value(self.starting_value, satisfied) / total
+    }
+
+    /// # Same Desire
+    /// 
+    /// Checks whether the two desires are the same, ignoring satisfaction.
+    pub fn same_desire(&self, other: &Desire) -> bool {
+        self.item == other.item &&
+        self.amount == other.amount &&
+        self.starting_value == other.starting_value &&
+        self.demand_fn == other.demand_fn &&
+        self.steps == other.steps &&
+        self.tags == other.tags
+    }
+
     /// # End
     /// 
     /// Gets the upper priority bound of our prioirity curve.
@@ -358,6 +427,108 @@ impl Desire {
             false
         }
     }
+
+    /// # Parse
+    /// 
+    /// Parses a desire from it's text form, as written by to_text().
+    /// 
+    /// `Want(Food) x2 @10 geometric(0.9) steps=5 [LifeNeed(0.1), HouseholdNeed]`
+    /// 
+    /// - The item comes first, as Want(name), Class(name), or Good(name). Goods
+    ///   and classes use the full name of the good, "Name (Variant)" if it has 
+    ///   a variant. A numeric id may be used if nothing has that name.
+    /// - x is the amount, defaulting to 1.
+    /// - @ is the starting value, required.
+    /// - The demand curve, required, see DemandCurve::parse().
+    /// - steps= is the number of steps, or inf for no end. Defaults to 1.
+    /// - Tags go in square brackets, separated by commas.
+    /// 
+    /// Everything after the item can come in any order. Tags are checked with 
+    /// DesireTag::safe_with(), and LifeNeeds must have finite steps.
+    pub fn parse(text: &str, data: &Data) -> Result<Desire, String> {
+        let tokens = split_outside_brackets(text, |c| c.is_whitespace());
+        let (item, rest) = tokens.split_first()
+            .ok_or(String::from("Desire text is empty."))?;
+        let item = parse_item(item, data)?;
+        let mut amount = None;
+        let mut start = None;
+        let mut curve = None;
+        let mut steps = None;
+        let mut tags = None;
+        let duplicate = |name: &str| format!("Desire '{}' has more than one {}.", text, name);
+        for token in rest.iter() {
+            if let Some(value) = token.strip_prefix('x') {
+                if amount.is_some() { return Err(duplicate("amount")); }
+                amount = Some(parse_number(value)?);
+            } else if let Some(value) = token.strip_prefix('@') {
+                if start.is_some() { return Err(duplicate("starting value")); }
+                start = Some(parse_number(value)?);
+            } else if let Some(value) = token.strip_prefix("steps=") {
+                if steps.is_some() { return Err(duplicate("steps")); }
+                steps = Some(if value == "inf" { 0 } else {
+                    value.parse::<usize>().ok()
+                        .filter(|x| *x > 0)
+                        .ok_or(format!("Steps '{}' must be a positive integer or inf.", value))?
+                });
+            } else if let Some(value) = token.strip_prefix('[').and_then(|x| x.strip_suffix(']')) {
+                if tags.is_some() { return Err(duplicate("tag list")); }
+                tags = Some(split_outside_brackets(value, |c| c == ',').iter()
+                    .map(|x| DesireTag::parse(x))
+                    .collect::<Result<Vec<DesireTag>, String>>()?);
+            } else {
+                if curve.is_some() { return Err(duplicate("demand curve")); }
+                curve = Some(DemandCurve::parse(token)?);
+            }
+        }
+        let amount = amount.unwrap_or(1.0);
+        if amount <= 0.0 {
+            return Err(String::from("Amount must be a positive value."));
+        }
+        let start = start.ok_or(format!("Desire '{}' has no starting value.", text))?;
+        let curve = curve.ok_or(format!("Desire '{}' has no demand curve.", text))?;
+        let mut desire = Desire::new(item, amount, start, curve)
+            .with_steps(steps.unwrap_or(1));
+        for tag in tags.unwrap_or_default() {
+            if let DesireTag::LifeNeed(mortality) = tag {
+                if desire.steps.is_none() {
+                    return Err(String::from("A Desire with the tag LifeNeed must have a finite number of steps."));
+                }
+                if !(mortality > 0.0 && mortality <= 1.0) {
+                    return Err(String::from("Mortality must be greater than 0.0, and no greater than 1.0."));
+                }
+            }
+            for existing in desire.tags.iter() {
+                existing.safe_with(&tag)?;
+            }
+            desire = desire.with_tag(tag);
+        }
+        Ok(desire)
+    }
+
+    /// # To Text
+    /// 
+    /// Writes the desire in it's text form, which Desire::parse() reads back 
+    /// into the same desire.
+    /// 
+    /// Items are written by name where they exist in data, otherwise by id.
+    /// Satisfaction is not included.
+    pub fn to_text(&self, data: &Data) -> String {
+        let item = match self.item {
+            Item::Want(id) => format!("Want({})", data.try_get_want(id)
+                .map_or(id.to_string(), |x| x.name.clone())),
+            Item::Class(id) => format!("Class({})", data.try_get_good(id)
+                .map_or(id.to_string(), |x| full_name(&x.name, &x.variant_name))),
+            Item::Good(id) => format!("Good({})", data.try_get_good(id)
+                .map_or(id.to_string(), |x| full_name(&x.name, &x.variant_name))),
+        };
+        let steps = self.steps.map_or(String::from("inf"), |x| x.to_string());
+        let mut result = format!("{} x{} @{} {} steps={}", item, self.amount, 
+            self.starting_value, self.demand_fn, steps);
+        if !self.tags.is_empty() {
+            result += &format!(" [{}]", self.tags.iter().join(", "));
+        }
+        result
+    }
 }
 
 /// # Desire Tag
@@ -370,8 +541,8 @@ impl Desire {
 pub enum DesireTag {
     /// Desire is necissary for life. Not getting it massively 
     /// increases mortality per step not met. Value attached is 
-    /// the increase to mortality (Current Mortality + value * missing_steps). 
-    /// (1.0 is 100% mortality)
+    /// the fraction of the pop which dies each day per missing step 
+    /// (value * missing_steps). (1.0 is 100% mortality)
     /// 
     /// ### Additional Limitations
     /// 
@@ -393,6 +564,61 @@ pub enum DesireTag {
     /// 
     /// This is exclusive with Household Need and itself.
     HouseMemberNeed(HouseholdMember),
+    /// The desire habituates, changing with how well it has been satisfied. 
+    /// 
+    /// If it is satisfied for `days` days in a row, the pop gets used to it, 
+    /// raising it's amount by `rate`. If it's deprived for `days` days in a row,
+    /// it's amount is lowered by `rate`. See Habit for the details.
+    /// 
+    /// The pop remembers this in Pop::habits.
+    Habituation { rate: f64, days: usize },
+    /// The desire is positional, it's valued relative to what other pops in 
+    /// the same market hold rather than on it's own.
+    /// 
+    /// Each day, the desire's amount is scaled by the per person holdings of 
+    /// the item by the other pops in the market, measured as given. A pop 
+    /// which holds as much per person as that reference satisfies one step.
+    /// 
+    /// The pop remembers the current reference in Pop::positions.
+    Positional(PositionalMeasure),
+}
+
+/// # Positional Measure
+/// 
+/// How a positional desire measures the holdings of the other pops in it's
+/// market to get the reference it compares itself to.
+#[derive(Clone, Copy, Debug, PartialEq, PartialOrd)]
+pub enum PositionalMeasure {
+    /// Compare to the mean of per person holdings.
+    Mean,
+    /// Compare to the per person holdings at this percentile (0.0 to 1.0) of 
+    /// the other pops, interpolating between pops.
+    Percentile(f64),
+}
+
+impl PositionalMeasure {
+    /// # Reference
+    /// 
+    /// Gets the reference value from the per person holdings of other pops.
+    /// 
+    /// Returns None if there are no other pops to compare to.
+    pub fn reference(&self, holdings: &[f64]) -> Option<f64> {
+        if holdings.is_empty() {
+            return None;
+        }
+        match self {
+            PositionalMeasure::Mean => Some(holdings.iter().sum::<f64>() / holdings.len() as f64),
+            PositionalMeasure::Percentile(percentile) => {
+                let sorted = holdings.iter().copied()
+                    .sorted_by(|a, b| a.total_cmp(b)).collect_vec();
+                let position = percentile * (sorted.len() - 1) as f64;
+                let lower = position.floor() as usize;
+                let upper = position.ceil() as usize;
+                let fraction = position - lower as f64;
+                Some(sorted[lower] + (sorted[upper] - sorted[lower]) * fraction)
+            },
+        }
+    }
 }
 
 impl DesireTag {
@@ -404,6 +630,74 @@ impl DesireTag {
         DesireTag::LifeNeed(mortality)
     }
 
+    /// # Habituation
+    /// 
+    /// Creates a Habituation desire tag safely, ensuring the rate is between 0.0
+    /// and 1.0 and the days can be remembered by the pop.
+    pub fn habituation(rate: f64, days: usize) -> DesireTag {
+        assert!(rate > 0.0 && rate < 1.0, "Rate must be between 0.0 and 1.0.");
+        assert!(days > 0 && days <= HABIT_MEMORY, "Days must be positive and no more than HABIT_MEMORY.");
+        DesireTag::Habituation { rate, days }
+    }
+
+    /// # Positional
+    /// 
+    /// Creates a Positional desire tag safely, ensuring any percentile is between
+    /// 0.0 and 1.0.
+    pub fn positional(measure: PositionalMeasure) -> DesireTag {
+        if let PositionalMeasure::Percentile(percentile) = measure {
+            assert!((0.0..=1.0).contains(&percentile), "Percentile must be between 0.0 and 1.0.");
+        }
+        DesireTag::Positional(measure)
+    }
+
+    /// # Parse
+    /// 
+    /// Parses a tag from it's text form, as written by Display.
+    /// 
+    /// LifeNeed(mortality), HouseholdNeed, HouseMemberNeed(Adult|Child|Elder),
+    /// Habituation(rate, days), or Positional(Mean|percentile).
+    pub fn parse(text: &str) -> Result<DesireTag, String> {
+        let text = text.trim();
+        if text == "HouseholdNeed" {
+            return Ok(DesireTag::HouseholdNeed);
+        }
+        if let Some(value) = text.strip_prefix("Habituation(").and_then(|x| x.strip_suffix(')')) {
+            let (rate, days) = value.split_once(',')
+                .ok_or(format!("Habituation '{}' needs a rate and days.", value))?;
+            let rate = parse_number(rate.trim())?;
+            let days = days.trim().parse::<usize>()
+                .map_err(|_| format!("'{}' is not a number of days.", days.trim()))?;
+            if rate <= 0.0 || rate >= 1.0 || days == 0 || days > HABIT_MEMORY {
+                return Err(format!("Habituation({}, {}) is out of range.", rate, days));
+            }
+            return Ok(DesireTag::Habituation { rate, days });
+        }
+        if let Some(value) = text.strip_prefix("Positional(").and_then(|x| x.strip_suffix(')')) {
+            let value = value.trim();
+            if value == "Mean" {
+                return Ok(DesireTag::Positional(PositionalMeasure::Mean));
+            }
+            let percentile = parse_number(value)?;
+            if !(0.0..=1.0).contains(&percentile) {
+                return Err(format!("Positional({}) is out of range.", percentile));
+            }
+            return Ok(DesireTag::Positional(PositionalMeasure::Percentile(percentile)));
+        }
+        if let Some(value) = text.strip_prefix("LifeNeed(").and_then(|x| x.strip_suffix(')')) {
+            return Ok(DesireTag::LifeNeed(parse_number(value.trim())?));
+        }
+        if let Some(value) = text.strip_prefix("HouseMemberNeed(").and_then(|x| x.strip_suffix(')')) {
+            return match value.trim() {
+                "Adult" => Ok(DesireTag::HouseMemberNeed(HouseholdMember::Adult)),
+                "Child" => Ok(DesireTag::HouseMemberNeed(HouseholdMember::Child)),
+                "Elder" => Ok(DesireTag::HouseMemberNeed(HouseholdMember::Elder)),
+                _ => Err(format!("Unknown household member '{}'.", value)),
+            };
+        }
+        Err(format!("Unknown desire tag '{}'.", text))
+    }
+
     /// # Safe With
     /// 
     /// Our enforcement checker to ensure two tags are safe next to each other.
@@ -415,7 +709,9 @@ impl DesireTag {
             return Err("Same Tags, never safe.");
         }
         match self {
-            DesireTag::LifeNeed(_) => {}, // safe next to all others.
+            DesireTag::LifeNeed(_) |
+            DesireTag::Habituation { .. } |
+            DesireTag::Positional(_) => {}, // safe next to all others.
             DesireTag::HouseholdNeed => {
                 // Cannot be next to any HouseMemberNeed
                 if let DesireTag::HouseMemberNeed(_) = other {
@@ -431,4 +727,80 @@ impl DesireTag {
         }
         Ok(())
     }
-}
\ No newline at end of file
+}
+impl fmt::Display for DesireTag {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DesireTag::LifeNeed(mortality) => write!(f, "LifeNeed({})", mortality),
+            DesireTag::HouseholdNeed => write!(f, "HouseholdNeed"),
+            DesireTag::HouseMemberNeed(member) => write!(f, "HouseMemberNeed({:?})", member),
+            DesireTag::Habituation { rate, days } => write!(f, "Habituation({}, {})", rate, days),
+            DesireTag::Positional(PositionalMeasure::Mean) => write!(f, "Positional(Mean)"),
+            DesireTag::Positional(PositionalMeasure::Percentile(percentile)) => write!(f, "Positional({})", percentile),
+        }
+    }
+}
+
+/// # Parse Number
+/// 
+/// Parses a number in desire text, rejecting NaN and infinities.
+fn parse_number(text: &str) -> Result<f64, String> {
+    text.parse::<f64>().ok()
+        .filter(|x| x.is_finite())
+        .ok_or(format!("'{}' is not a finite number.", text))
+}
+
+/// # Parse Item
+/// 
+/// Parses Want(name), Class(name), or Good(name), looking the name up in data,
+/// falling back to an id.
+fn parse_item(text: &str, data: &Data) -> Result<Item, String> {
+    let (kind, name) = text.strip_suffix(')')
+        .and_then(|x| x.split_once('('))
+        .ok_or(format!("Item '{}' must be in the form Kind(name).", text))?;
+    let id = name.parse::<usize>().ok();
+    let id = match kind {
+        "Want" => data.find_want(name)
+            .or(id.filter(|x| data.try_get_want(*x).is_some())),
+        "Good" => data.find_good(name)
+            .or(id.filter(|x| data.try_get_good(*x).is_some())),
+        "Class" => data.find_good(name)
+            .or(id)
+            .filter(|x| data.try_get_class(*x).is_some()),
+        _ => return Err(format!("Unknown item kind '{}'.", kind)),
+    }.ok_or(format!("{} '{}' does not exist in Data.", kind, name))?;
+    Ok(match kind {
+        "Want" => Item::Want(id),
+        "Class" => Item::Class(id),
+        _ => Item::Good(id),
+    })
+}
+
+/// # Split Outside Brackets
+/// 
+/// Splits text wherever split is true, except inside () or [], dropping any
+/// empty pieces.
+fn split_outside_brackets(text: &str, split: impl Fn(char) -> bool) -> Vec<String> {
+    let mut result = vec![];
+    let mut current = String::new();
+    let mut depth = 0;
+    for c in text.chars() {
+        match c {
+            '(' | '[' => depth += 1,
+            ')' | ']' => depth -= 1,
+            _ => {}
+        }
+        if depth == 0 && split(c) {
+            if !current.trim().is_empty() {
+                result.push(current.trim().to_string());
+            }
+            current.clear();
+        } else {
+            current.push(c);
+        }
+    }
+    if !current.trim().is_empty() {
+        result.push(current.trim().to_string());
+    }
+    result
+}
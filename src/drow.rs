@@ -7,16 +7,18 @@ use crate::{data::Data, household::Household};
 /// 
 /// Has a column for each 'layer' of information a pop can have.
 /// 
-/// Currently, we only use one.
-#[derive(Debug, Clone, Copy)]
+/// Species and culture have their own columns, all other layers (religion, 
+/// class, profession, etc) are generic Layers from Data.
+#[derive(Debug, Clone)]
 pub struct DRow {
     /// The Household(s) of this Demographic row.
     pub household: Household,
     /// All pops need a base species at all times.
     pub species: usize,
-    /// Culture is currently the only additional layer of info for a pop.
+    /// The culture of the row, if any.
     pub culture: Option<usize>,
-    // Placeholder for later columns.
+    /// The ids of any additional Layers the row has, at most one of each kind.
+    pub layers: Vec<usize>,
 }
 
 impl DRow {
@@ -26,6 +28,7 @@ impl DRow {
             household,
             species,
             culture: None,
+            layers: vec![],
         }
     }
 
@@ -34,9 +37,47 @@ impl DRow {
         self
     }
 
+    /// # With Layer
+    /// 
+    /// Adds a layer to the row. Does not check the layer's kind, use set_layer
+    /// for that.
+    pub fn with_layer(mut self, layer: usize) -> Self {
+        self.layers.push(layer);
+        self
+    }
+
+    /// # Set Layer
+    /// 
+    /// Sets a layer on the row, replacing any existing layer of the same kind.
+    /// 
+    /// Returns the id of the layer replaced, if any.
+    pub fn set_layer(&mut self, layer: usize, data: &Data) -> Option<usize> {
+        let kind = &data.get_layer(layer).kind;
+        let existing = self.layers.iter()
+            .position(|x| data.get_layer(*x).kind == *kind);
+        match existing {
+            Some(idx) => Some(std::mem::replace(&mut self.layers[idx], layer)),
+            None => {
+                self.layers.push(layer);
+                None
+            }
+        }
+    }
+
+    /// # Same Demographics
+    /// 
+    /// Whether the two rows are of the same species, culture, and layers, 
+    /// regardless of their households.
+    pub fn same_demographics(&self, other: &DRow) -> bool {
+        self.species == other.species && self.culture == other.culture &&
+        self.layers.len() == other.layers.len() && 
+        self.layers.iter().all(|x| other.layers.contains(x))
+    }
+
     /// # Update Household
     /// 
-    /// Used after setting the demographic parts, updates the household to match species.
+    /// Used after setting the demographic parts, updates the household to match species,
+    /// culture, and layers.
     /// 
     /// This does change population as household count is maintained.
     pub fn update_household(&mut self, data: &Data) {
@@ -45,6 +86,10 @@ impl DRow {
         if let Some(id) = self.culture {
             mods.push(data.get_culture(id).household_mod);
         }
-        self.household.add_mods(mods);
+        for &id in self.layers.iter() {
+            mods.push(data.get_layer(id).household_mod);
+        }
+        self.household = self.household.add_mods(mods)
+            .add_count(self.household.count);
     }
 }
\ No newline at end of file
@@ -50,6 +50,21 @@ pub struct Firm {
     pub shifts: f64,
 }
 impl Firm {
+    pub fn new(id: usize, name: String, market: usize) -> Self {
+        Self {
+            id,
+            name,
+            market,
+            parent: None,
+            children: HashSet::new(),
+            shares: 0,
+            property: HashMap::new(),
+            workers: HashMap::new(),
+            shift_length: 0.0,
+            shifts: 0.0,
+        }
+    }
+
     pub fn work_day_exchange(&self, data: &Data, pop: &mut Pop) -> 
     (HashMap<usize, f64>, HashMap<usize, f64>) {
         todo!()
@@ -87,10 +102,22 @@ pub struct WorkerInfo {
     pub worker_cap: usize,
 }
 
+impl WorkerInfo {
+    pub fn new(worker_type: WorkerType) -> Self {
+        Self {
+            worker_type,
+            wage: HashMap::new(),
+            labors: HashMap::new(),
+            shares: 0,
+            worker_cap: 0,
+        }
+    }
+}
+
 /// # Worker Type
 /// 
 /// The types of workers for a firm.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum WorkerType {
     /// The ultimate owner of the firm. Excess profits and losses come from this 
     /// pop. They proved work like workers, but are distinguished from normal workers.
@@ -1,5 +1,7 @@
 use std::collections::HashMap;
 
+use crate::want::WantSource;
+
 /// # Good
 /// 
 /// A good is something that is desireable or useful.
@@ -209,6 +211,30 @@ impl Good {
         self
     }
     
+    /// # Wants From
+    /// 
+    /// Gets the wants (and their efficiency) this good produces via the given source.
+    pub fn wants_from(&self, source: WantSource) -> &HashMap<usize, f64> {
+        match source {
+            WantSource::Ownership => &self.own_wants,
+            WantSource::Use => &self.use_wants,
+            WantSource::Consumption => &self.consumption_wants,
+        }
+    }
+
+    /// # Time For
+    /// 
+    /// How much time it takes to get wants via the given source.
+    /// 
+    /// Ownership takes no time.
+    pub fn time_for(&self, source: WantSource) -> f64 {
+        match source {
+            WantSource::Ownership => 0.0,
+            WantSource::Use => self.use_time,
+            WantSource::Consumption => self.consumption_time,
+        }
+    }
+
     /// # Is Exchangeable
     /// 
     /// A wrapper equal to checking that a good is not tagged as nonexchangeable.
@@ -243,6 +269,38 @@ impl Good {
         }
         None
     }
+
+    /// # Identical To
+    /// 
+    /// Checks that every part of the good's definition is the same, unlike
+    /// eq, which only checks ids.
+    pub fn identical_to(&self, other: &Good) -> bool {
+        self.id == other.id &&
+        self.name == other.name &&
+        self.variant_name == other.variant_name &&
+        self.consumption_wants == other.consumption_wants &&
+        self.consumption_time == other.consumption_time &&
+        self.use_wants == other.use_wants &&
+        self.use_time == other.use_time &&
+        self.own_wants == other.own_wants &&
+        self.class == other.class &&
+        self.decay_rate == other.decay_rate &&
+        self.decays_to == other.decays_to &&
+        self.bulk == other.bulk &&
+        self.mass == other.mass &&
+        self.tags == other.tags
+    }
+}
+
+/// # Full Name
+/// 
+/// The name used to identify something with a variant name.
+pub fn full_name(name: &str, variant_name: &str) -> String {
+    if variant_name.is_empty() {
+        name.to_string()
+    } else {
+        format!("{} ({})", name, variant_name)
+    }
 }
 
 impl PartialEq for Good {
@@ -0,0 +1,66 @@
+use circular_buffer::CircularBuffer;
+
+use crate::constants::{HABIT_DEPRIVED_THRESHOLD, HABIT_MAXIMUM_MULTIPLIER, HABIT_MEMORY, HABIT_MINIMUM_MULTIPLIER, HABIT_SATISFIED_THRESHOLD};
+
+/// # Habit
+/// 
+/// A pop's memory of how well one of it's habituating desires has been 
+/// satisfied, and how far that has moved the desire from it's baseline.
+/// 
+/// If the desire is satisfied day after day, the pop gets used to it and wants
+/// more (luxuries become necessities). If it goes without day after day, the
+/// pop learns to do with less.
+#[derive(Debug, Clone)]
+pub struct Habit {
+    /// How satisfied the desire was each day (0.0 to 1.0), oldest first.
+    /// 
+    /// Cleared whenever the multiplier changes, so each change needs to be 
+    /// sustained all over again.
+    pub history: CircularBuffer<HABIT_MEMORY, f64>,
+    /// What the desire's baseline amount is multiplied by.
+    pub multiplier: f64,
+}
+
+impl Habit {
+    pub fn new() -> Self {
+        Self {
+            history: CircularBuffer::new(),
+            multiplier: 1.0,
+        }
+    }
+
+    /// # Record
+    /// 
+    /// Records how satisfied the desire was today (0.0 to 1.0).
+    /// 
+    /// If the last `days` days were all satisfied, the multiplier goes up by 
+    /// `rate`. If they were all deprived, it goes down by `rate`. The 
+    /// multiplier is kept between HABIT_MINIMUM_MULTIPLIER and 
+    /// HABIT_MAXIMUM_MULTIPLIER.
+    /// 
+    /// Returns the multiplier.
+    pub fn record(&mut self, satisfaction: f64, rate: f64, days: usize) -> f64 {
+        self.history.push_back(satisfaction.clamp(0.0, 1.0));
+        if self.history.len() < days {
+            return self.multiplier;
+        }
+        let recent = self.history.iter().rev().take(days);
+        let change = if recent.clone().all(|x| *x >= HABIT_SATISFIED_THRESHOLD) {
+            1.0 + rate
+        } else if recent.clone().all(|x| *x <= HABIT_DEPRIVED_THRESHOLD) {
+            1.0 - rate
+        } else {
+            return self.multiplier;
+        };
+        self.multiplier = (self.multiplier * change)
+            .clamp(HABIT_MINIMUM_MULTIPLIER, HABIT_MAXIMUM_MULTIPLIER);
+        self.history.clear();
+        self.multiplier
+    }
+}
+
+impl Default for Habit {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -1,4 +1,4 @@
-use crate::constants::{DAYS_PER_TURN, TIME_UNITS_PER_DAY};
+use crate::constants::{COHORT_BIRTH_SENSITIVITY, DAYS_PER_TURN, POP_GROWTH_PERIOD, TIME_UNITS_PER_DAY};
 
 /// # Household
 /// 
@@ -30,6 +30,9 @@ use crate::constants::{DAYS_PER_TURN, TIME_UNITS_PER_DAY};
 /// Households are **Not** meant to change after being created, only adding households together
 /// should change them. All changes in household should come from HoueholdMod.
 /// 
+/// The exception is species with CohortAging, whose households have members 
+/// born, die, and age from one cohort into the next, see age_cohorts().
+/// 
 /// ## Default Household
 /// 
 /// The base household that is used as the 'default' for V1.0 is made of 
@@ -92,9 +95,28 @@ impl Household {
 
     /// # Mortality
     /// 
-    /// The mortality Rate of the household.
+    /// The mortality Rate of the household, the fraction of it which dies 
+    /// every POP_GROWTH_PERIOD days.
+    /// 
+    /// Each elder adds 0.1 deaths, spread over the members of the household.
     pub fn mortality(&self) -> f64 {
-        self.elders * 0.1
+        if self.household_size <= 0.0 {
+            return 0.0;
+        }
+        self.elders * 0.1 / self.household_size
+    }
+
+    /// # Effective Mortality
+    /// 
+    /// The mortality rate of the household with the mortality from unmet 
+    /// LifeNeeds added on top, in the same unit as mortality().
+    /// 
+    /// LifeNeed mortality is the fraction which dies each day, so it is 
+    /// scaled by POP_GROWTH_PERIOD to match. Capped at everyone dying every 
+    /// turn.
+    pub fn effective_mortality(&self, life_need: f64) -> f64 {
+        (self.mortality() + life_need * POP_GROWTH_PERIOD)
+            .min(POP_GROWTH_PERIOD / DAYS_PER_TURN)
     }
 
     /// # Birth Rate
@@ -120,6 +142,54 @@ impl Household {
             * TIME_UNITS_PER_DAY * DAYS_PER_TURN
     }
 
+    /// # Age Cohorts
+    /// 
+    /// Applies births and deaths (in people) to the household, then ages 
+    /// members between cohorts.
+    /// 
+    /// Births are added as children. Deaths come from the elders first, then
+    /// from adults and children evenly. After that, aging.maturation of the
+    /// children become adults and aging.aging of the adults become elders.
+    /// 
+    /// Households form around their adults, so the adults in each household
+    /// stay the same and the count changes with the number of adults, while 
+    /// the children and elders in each household are free to change. Labor 
+    /// follows along with the new makeup.
+    /// 
+    /// If no adults remain, the household count becomes 0.0, and any children
+    /// and elders left go with it. Callers should count them as deaths.
+    pub fn age_cohorts(&self, aging: &CohortAging, births: f64, deaths: f64) -> Self {
+        let mut children = self.total_children() + births;
+        let mut adults = self.total_adults();
+        let mut elders = self.total_elders();
+        // deaths come from elders first, then everyone else.
+        let elder_deaths = deaths.min(elders);
+        elders -= elder_deaths;
+        let young = children + adults;
+        if young > 0.0 {
+            let survival = (1.0 - (deaths - elder_deaths) / young).max(0.0);
+            children *= survival;
+            adults *= survival;
+        }
+        // then age everyone up.
+        let matured = children * aging.maturation;
+        let aged = adults * aging.aging;
+        children -= matured;
+        adults += matured - aged;
+        elders += aged;
+        let count = if self.adults > 0.0 { adults / self.adults } else { 0.0 };
+        if count <= 0.0 {
+            return self.mult(0.0);
+        }
+        Self {
+            count,
+            household_size: (adults + children + elders) / count,
+            adults: self.adults,
+            elders: elders / count,
+            children: children / count,
+        }
+    }
+
     /// # Combine Households
     /// 
     /// Takes multiple households and combines them into one. Counts are added
@@ -308,6 +378,55 @@ impl Household {
     }
 }
 
+/// # Cohort Aging
+/// 
+/// How the members of a household age from one cohort into the next each turn,
+/// see Household::age_cohorts().
+/// 
+/// Species without it keep the same household makeup forever.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CohortAging {
+    /// The fraction (0.0 to 1.0) of children who become adults each turn.
+    pub maturation: f64,
+    /// The fraction (0.0 to 1.0) of adults who become elders each turn.
+    pub aging: f64,
+    /// How much desire satisfaction shifts births, see birth_multiplier().
+    pub birth_sensitivity: f64,
+}
+
+impl CohortAging {
+    pub fn new(maturation: f64, aging: f64) -> Self {
+        assert!((0.0..=1.0).contains(&maturation), "Maturation must be between 0.0 and 1.0.");
+        assert!((0.0..=1.0).contains(&aging), "Aging must be between 0.0 and 1.0.");
+        Self {
+            maturation,
+            aging,
+            birth_sensitivity: COHORT_BIRTH_SENSITIVITY,
+        }
+    }
+
+    /// # With Birth Sensitivity
+    /// 
+    /// Sets how much desire satisfaction shifts births.
+    pub fn with_birth_sensitivity(mut self, birth_sensitivity: f64) -> Self {
+        self.birth_sensitivity = birth_sensitivity;
+        self
+    }
+
+    /// # Birth Multiplier
+    /// 
+    /// How births are multiplied given the shortfall (0.0 to 1.0) in a pop's
+    /// desires.
+    /// 
+    /// A fully satisfied pop has 1 + birth_sensitivity times the births, (a 
+    /// baby boom after good harvests) a half satisfied pop has the normal 
+    /// births, and a fully unsatisfied pop has 1 - birth_sensitivity times 
+    /// the births. Never goes below 0.0.
+    pub fn birth_multiplier(&self, shortfall: f64) -> f64 {
+        (1.0 + self.birth_sensitivity * (1.0 - 2.0 * shortfall)).max(0.0)
+    }
+}
+
 /// # Household Member
 /// 
 /// Used in various places to denote and reference the subcomponents
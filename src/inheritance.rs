@@ -0,0 +1,45 @@
+use std::collections::HashMap;
+
+/// # Heir
+/// 
+/// Who inherits the property of pop members who die.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Heir {
+    /// The surviving members of the pop. Their property never leaves the pop.
+    Survivors,
+    /// The market the pop was in, which holds it as unclaimed goods.
+    Market(usize),
+    /// A firm, which adds it to it's property.
+    Firm(usize),
+    // TODO: Add States as heirs once they exist.
+}
+
+/// # Inheritance
+/// 
+/// A record of property passed on from the dead of a pop to their heir.
+/// 
+/// While any of the pop survives, the survivors inherit the share of property
+/// held by the dead. Once it dies out entirely, everything goes to the pop's 
+/// designated heir, or it's market if it has none, see World::settle_inheritance().
+#[derive(Debug, Clone, PartialEq)]
+pub struct Inheritance {
+    /// The pop the dead belonged to.
+    pub pop: usize,
+    /// The market that pop is in.
+    pub market: usize,
+    /// Who inherits the goods.
+    pub heir: Heir,
+    /// The goods inherited and how many of each.
+    pub goods: HashMap<usize, f64>,
+}
+
+impl Inheritance {
+    pub fn new(pop: usize, market: usize, heir: Heir) -> Self {
+        Self {
+            pop,
+            market,
+            heir,
+            goods: HashMap::new(),
+        }
+    }
+}
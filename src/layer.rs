@@ -0,0 +1,60 @@
+use crate::{desire::Desire, household::HouseholdMod};
+
+/// # Layer
+/// 
+/// A generic demographic layer, such as a religion, class, or profession. 
+/// 
+/// Layers are registered in Data and attached to a pop's demographic rows, 
+/// adding their household modifications and desires on top of the row's 
+/// species and culture.
+/// 
+/// Each layer has a kind, a row can only have one layer of each kind.
+pub struct Layer {
+    /// The unique id of the layer.
+    pub id: usize,
+    /// The unique name of the layer.
+    pub name: String,
+    /// The kind of layer this is (Religion, Class, Profession, etc).
+    pub kind: String,
+
+    /// The effects on a household this layer has. This should be a count 0.0 
+    /// household, the same as Culture::household_mod.
+    pub household_mod: HouseholdMod,
+    /// The desires this layer adds.
+    pub desires: Vec<Desire>,
+}
+
+impl Layer {
+    pub fn new(id: usize, name: String, kind: String) -> Layer {
+        Layer {
+            id,
+            name,
+            kind,
+            household_mod: HouseholdMod::zero(),
+            desires: vec![],
+        }
+    }
+
+    /// # With Household Modification
+    /// 
+    /// Sets the changes to a household that this layer makes.
+    pub fn with_household_mod(mut self, household_mod: HouseholdMod) -> Self {
+        self.household_mod = household_mod;
+        self
+    }
+
+    /// # With Desire
+    /// 
+    /// Inserts desire into proper place.
+    pub fn with_desire(mut self, desire: Desire) -> Self {
+        // find where to insert it.
+        let mut index = 0; 
+        for (idx, des) in self.desires.iter().enumerate() {
+            if des.starting_value < desire.starting_value {
+                index = idx;
+            }
+        }
+        self.desires.insert(index, desire);
+        self
+    }
+}
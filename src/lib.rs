@@ -4,8 +4,10 @@ pub mod good;
 pub mod process;
 pub mod market;
 pub mod data;
+pub mod datapack;
 pub mod world;
 pub mod culture;
+pub mod layer;
 pub mod want;
 pub mod item;
 pub mod markethistory;
@@ -18,9 +20,63 @@ pub mod freetimeaction;
 pub mod popfinancials;
 pub mod firm;
 pub mod demandcurve;
+pub mod habit;
+pub mod priceexpectations;
+pub mod inheritance;
 
 #[cfg(test)]
 mod tests {
+    /// Shared setup for tests which need data, prices or a pop.
+    mod fixtures {
+        use crate::{data::Data, desire::Desire, drow::DRow, good::Good, household::Household, markethistory::{GoodRecord, MarketHistory}, pop::{Pop, PropertyRecord}, species::Species};
+
+        /// Data with time and a plain good for each id and name.
+        pub fn goods_data(goods: &[(usize, &str)]) -> Data {
+            let mut data = Data::new();
+            data.add_time();
+            for &(id, name) in goods {
+                data.add_good(Good::new(id, String::from(name), String::new()));
+            }
+            data
+        }
+
+        /// Adds Humans (species 1) who have desires to data.
+        pub fn add_humans(data: &mut Data, desires: Vec<Desire>) {
+            let human = desires.into_iter()
+                .fold(Species::new(1, String::from("Human")), |species, desire| species.with_desire(desire));
+            data.species.insert(human.id, human);
+        }
+
+        /// A history with every good priced at 1.0.
+        pub fn flat_history(goods: &[usize]) -> MarketHistory {
+            let mut history = MarketHistory::new();
+            for &good in goods {
+                history.good_records.insert(good, GoodRecord::new().with_price(1.0));
+            }
+            history
+        }
+
+        /// Pop 0 in market 0 which owns each good and amount in property.
+        pub fn owning_pop(property: &[(usize, f64)]) -> Pop {
+            let mut pop = Pop::new(0, 0, 0);
+            for &(good, amount) in property {
+                pop.property.insert(good, PropertyRecord::new(amount));
+            }
+            pop
+        }
+
+        /// Pop 0 in market, made of Humans in households, with desires built.
+        pub fn human_pop(data: &Data, market: usize, households: Vec<Household>) -> Pop {
+            let mut pop = Pop::new(0, market, 0);
+            for household in households {
+                pop.include_demo(DRow { household, species: 1, culture: None, layers: vec![] });
+            }
+            pop.combine_households(data);
+            pop.update_desires_full(data);
+            pop
+        }
+    }
+
     mod process_tests {
         mod uses_input_should {
             use crate::process::{InputTag, Process, ProcessInput};
@@ -953,6 +1009,209 @@ mod tests {
                 assert!(discriminant(test.tags.get(1).unwrap()) == discriminant(&DesireTag::HouseMemberNeed(HouseholdMember::Adult)))
             }
         }
+
+        mod normalized_satisfaction_should {
+            use crate::{demandcurve::DemandCurve, desire::Desire, item::Item};
+
+            #[test]
+            pub fn weigh_steps_by_value_over_total_value() {
+                // steps are worth 10, 9, 8, and 7, for a total of 34.
+                let mut test = Desire::new(Item::Good(0), 2.0, 10.0,
+                    DemandCurve::linear(-1.0))
+                    .with_steps(4);
+                assert_eq!(test.normalized_satisfaction(), 0.0);
+                test.change_satisfaction(1.0);
+                assert!((test.normalized_satisfaction() - 5.0 / 34.0).abs() < 0.000001);
+                test.change_satisfaction(3.0);
+                assert!((test.normalized_satisfaction() - 19.0 / 34.0).abs() < 0.000001);
+                test.change_satisfaction(4.0);
+                assert!((test.normalized_satisfaction() - 1.0).abs() < 0.000001);
+            }
+
+            #[test]
+            pub fn stop_counting_once_the_curve_reaches_zero() {
+                let mut test = Desire::new(Item::Good(0), 1.0, 4.0,
+                    DemandCurve::linear(-1.0))
+                    .with_steps(0);
+                let mut last = 0.0;
+                for _ in 0..4 {
+                    test.change_satisfaction(1.0);
+                    let current = test.normalized_satisfaction();
+                    assert!(current > last);
+                    last = current;
+                }
+                assert!((last - 1.0).abs() < 0.000001);
+                for _ in 0..3 {
+                    test.change_satisfaction(1.0);
+                    assert!((test.normalized_satisfaction() - 1.0).abs() < 0.000001);
+                }
+            }
+
+            #[test]
+            pub fn not_favor_small_amounts_under_linear_or_flat_curves() {
+                for (curve, half) in [(DemandCurve::linear(-1.0), 40.0 / 55.0), 
+                (DemandCurve::tabular(vec![0.0]), 0.5)] {
+                    let mut small = Desire::new(Item::Good(0), 1.0, 10.0,
+                        curve.clone())
+                        .with_steps(10);
+                    let mut large = Desire::new(Item::Good(1), 10.0, 10.0,
+                        curve.clone())
+                        .with_steps(10);
+                    // small gets 10 times the steps from the same satisfaction.
+                    small.change_satisfaction(5.0);
+                    large.change_satisfaction(5.0);
+                    assert_eq!(small.satisfied_steps() / large.satisfied_steps(), 10.0);
+                    assert!((small.normalized_satisfaction() - half).abs() < 0.000001);
+                    // but both halfway satisfied are worth the same.
+                    large.change_satisfaction(45.0);
+                    assert!((large.normalized_satisfaction() - half).abs() < 0.000001);
+                    // and neither can be worth more than 1.0.
+                    assert!(small.change_satisfaction(100.0).is_some());
+                    assert!(large.change_satisfaction(100.0).is_some());
+                    assert!((small.normalized_satisfaction() - 1.0).abs() < 0.000001);
+                    assert!((large.normalized_satisfaction() - 1.0).abs() < 0.000001);
+                }
+                // under linear, the large desire's first step is worth more 
+                // than a flat share, so the small desire's lead is reduced.
+                let mut small = Desire::new(Item::Good(0), 1.0, 10.0,
+                    DemandCurve::linear(-1.0))
+                    .with_steps(10);
+                let mut large = Desire::new(Item::Good(1), 10.0, 10.0,
+                    DemandCurve::linear(-1.0))
+                    .with_steps(10);
+                small.change_satisfaction(10.0);
+                large.change_satisfaction(10.0);
+                let normalized_ratio = small.normalized_satisfaction() / large.normalized_satisfaction();
+                assert!((normalized_ratio - 5.5).abs() < 0.000001);
+            }
+        }
+
+        mod parse_should {
+            use std::num::NonZero;
+
+            use crate::{data::Data, demandcurve::DemandCurve, desire::{Desire, DesireTag, PositionalMeasure}, good::Good, household::HouseholdMember, item::Item, want::Want};
+
+            fn test_data() -> Data {
+                let mut data = Data::new();
+                data.wants.insert(3, Want::new(3, String::from("Food")));
+                data.add_good(Good::new(4, String::from("Bread"), String::new()).in_class(4));
+                data.add_good(Good::new(5, String::from("Bread"), String::from("Rye")).in_class(4));
+                data
+            }
+
+            #[test]
+            pub fn parse_full_desire() {
+                let data = test_data();
+                let test = Desire::parse("Want(Food) x2 @10 geometric(0.9) steps=5 [LifeNeed(0.1), HouseholdNeed]", &data)
+                    .unwrap();
+                assert_eq!(test.item, Item::Want(3));
+                assert_eq!(test.amount, 2.0);
+                assert_eq!(test.starting_value, 10.0);
+                assert_eq!(test.demand_fn, DemandCurve::Geometric { factor: 0.9 });
+                assert_eq!(test.steps, NonZero::new(5));
+                assert_eq!(test.tags, vec![DesireTag::LifeNeed(0.1), DesireTag::HouseholdNeed]);
+
+                let test = Desire::parse("Good(Bread (Rye)) steps=inf piecewise(1:-8, 3:-9) @-2.5", &data)
+                    .unwrap();
+                assert_eq!(test.item, Item::Good(5));
+                assert_eq!(test.amount, 1.0);
+                assert_eq!(test.starting_value, -2.5);
+                assert_eq!(test.demand_fn, DemandCurve::PiecewiseLinear { points: vec![(1.0, -8.0), (3.0, -9.0)] });
+                assert_eq!(test.steps, None);
+
+                let test = Desire::parse("Class(Bread) @1 linear(-1) [HouseMemberNeed(Child)]", &data).unwrap();
+                assert_eq!(test.item, Item::Class(4));
+                assert_eq!(test.tags, vec![DesireTag::HouseMemberNeed(HouseholdMember::Child)]);
+            }
+
+            #[test]
+            pub fn reject_invalid_desires() {
+                let data = test_data();
+                // unsafe tags
+                assert!(Desire::parse("Want(Food) @10 linear(-1) [HouseholdNeed, HouseMemberNeed(Adult)]", &data).is_err());
+                assert!(Desire::parse("Want(Food) @10 linear(-1) [HouseholdNeed, HouseholdNeed]", &data).is_err());
+                assert!(Desire::parse("Want(Food) @10 linear(-1) steps=inf [LifeNeed(0.1)]", &data).is_err());
+                assert!(Desire::parse("Want(Food) @10 linear(-1) [LifeNeed(1.5)]", &data).is_err());
+                // bad curves and values
+                assert!(Desire::parse("Want(Food) @10 geometric(1.5)", &data).is_err());
+                assert!(Desire::parse("Want(Food) @10 bogus(1)", &data).is_err());
+                assert!(Desire::parse("Want(Food) x0 @10 linear(-1)", &data).is_err());
+                assert!(Desire::parse("Want(Food) linear(-1)", &data).is_err());
+                assert!(Desire::parse("Want(Food) @10", &data).is_err());
+                assert!(Desire::parse("Want(Food) @10 @11 linear(-1)", &data).is_err());
+                assert!(Desire::parse("Want(Food) xinf @10 linear(-1)", &data).is_err());
+                assert!(Desire::parse("Want(Food) @inf linear(-1)", &data).is_err());
+                assert!(Desire::parse("Want(Food) @-inf linear(-1)", &data).is_err());
+                assert!(Desire::parse("Want(Food) @10 linear(-inf)", &data).is_err());
+                // bad items
+                assert!(Desire::parse("Want(Drink) @10 linear(-1)", &data).is_err());
+                assert!(Desire::parse("Class(Bread (Rye)) @10 linear(-1)", &data).is_err());
+                assert!(Desire::parse("Thing(Food) @10 linear(-1)", &data).is_err());
+            }
+
+            #[test]
+            pub fn round_trip_through_text() {
+                let data = test_data();
+                let desires = [
+                    Desire::new(Item::Want(3), 2.0, 10.0, DemandCurve::geometric(0.9))
+                        .with_steps(5)
+                        .with_tag(DesireTag::life_need(0.1))
+                        .with_tag(DesireTag::HouseholdNeed),
+                    Desire::new(Item::Good(5), 0.1, -2.5, DemandCurve::piecewise_linear(vec![(1.5, -8.0), (3.0, -9.25)]))
+                        .with_steps(0),
+                    Desire::new(Item::Class(4), 1.0 / 3.0, 1.0, DemandCurve::tabular(vec![0.0, -4.0, -9.0]))
+                        .with_tag(DesireTag::HouseMemberNeed(HouseholdMember::Elder)),
+                    Desire::new(Item::Want(3), 1.0, 7.0, DemandCurve::exponential_decay(0.3, 2.0)),
+                    Desire::new(Item::Want(3), 1.0, 7.0, DemandCurve::logistic(0.5, 4.0)),
+                    Desire::new(Item::Want(3), 1.0, 7.0, DemandCurve::logarithmic(2.0)),
+                    Desire::new(Item::Want(3), 1.0, 7.0, DemandCurve::root(1.5)),
+                    // unknown ids are kept as ids.
+                    Desire::new(Item::Good(99), 1.0, 7.0, DemandCurve::linear(-0.5)),
+                ];
+                assert_eq!(desires[0].to_text(&data), 
+                    "Want(Food) x2 @10 geometric(0.9) steps=5 [LifeNeed(0.1), HouseholdNeed]");
+                assert_eq!(desires[7].to_text(&data), "Good(99) x1 @7 linear(-0.5) steps=1");
+                for desire in desires.iter().take(7) {
+                    let text = desire.to_text(&data);
+                    let parsed = Desire::parse(&text, &data).unwrap();
+                    assert_eq!(&parsed, desire, "{}", text);
+                    assert_eq!(parsed.to_text(&data), text);
+                }
+            }
+
+            #[test]
+            pub fn parse_habituation_tags() {
+                let data = test_data();
+                let desire = Desire::new(Item::Want(3), 1.0, 5.0, DemandCurve::linear(-1.0))
+                    .with_steps(2)
+                    .with_tag(DesireTag::habituation(0.25, 3))
+                    .with_tag(DesireTag::life_need(0.1));
+                let text = desire.to_text(&data);
+                assert_eq!(text, "Want(Food) x1 @5 linear(-1) steps=2 [LifeNeed(0.1), Habituation(0.25, 3)]");
+                assert_eq!(Desire::parse(&text, &data).unwrap(), desire);
+
+                assert!(DesireTag::parse("Habituation(0.25)").is_err());
+                assert!(DesireTag::parse("Habituation(1.5, 3)").is_err());
+                assert!(DesireTag::parse("Habituation(0.25, 0)").is_err());
+                assert!(DesireTag::parse("Habituation(0.25, 33)").is_err());
+            }
+
+            #[test]
+            pub fn parse_positional_tags() {
+                let data = test_data();
+                let desire = Desire::new(Item::Want(3), 1.0, 5.0, DemandCurve::linear(-1.0))
+                    .with_tag(DesireTag::positional(PositionalMeasure::Percentile(0.75)));
+                let text = desire.to_text(&data);
+                assert_eq!(text, "Want(Food) x1 @5 linear(-1) steps=1 [Positional(0.75)]");
+                assert_eq!(Desire::parse(&text, &data).unwrap(), desire);
+                assert_eq!(DesireTag::parse("Positional(Mean)").unwrap(), 
+                    DesireTag::Positional(PositionalMeasure::Mean));
+
+                assert!(DesireTag::parse("Positional()").is_err());
+                assert!(DesireTag::parse("Positional(1.5)").is_err());
+                assert!(DesireTag::parse("Positional(Median)").is_err());
+            }
+        }
     }
 
     mod demand_curve_tests {
@@ -996,6 +1255,35 @@ mod tests {
                 let asymptotic_test = DemandCurve::logarithmic(2.0);
                 assert_eq!(asymptotic_test.value(10.0, 0.0), 10.0, "Incorrect value!");
             }
+
+            #[test]
+            pub fn correctly_calculate_value_for_new_curves() {
+                let piecewise_test = DemandCurve::piecewise_linear(vec![(1.0, -8.0), (3.0, -9.0)]);
+                assert_eq!(piecewise_test.value(10.0, 0.0), 10.0, "Incorrect value!");
+                assert_eq!(piecewise_test.value(10.0, 0.5), 6.0, "Incorrect value!");
+                assert_eq!(piecewise_test.value(10.0, 1.0), 2.0, "Incorrect value!");
+                assert_eq!(piecewise_test.value(10.0, 2.0), 1.5, "Incorrect value!");
+                assert_eq!(piecewise_test.value(10.0, 10.0), 1.0, "Incorrect value!");
+
+                let tabular_test = DemandCurve::tabular(vec![0.0, -9.0]);
+                assert_eq!(tabular_test.value(10.0, 0.0), 10.0, "Incorrect value!");
+                assert_eq!(tabular_test.value(10.0, 0.5), 10.0, "Incorrect value!");
+                assert_eq!(tabular_test.value(10.0, 1.0), 1.0, "Incorrect value!");
+                assert_eq!(tabular_test.value(10.0, 5.0), 1.0, "Incorrect value!");
+
+                let decay_test = DemandCurve::exponential_decay(2.0_f64.ln(), 2.0);
+                assert_eq!(decay_test.value(10.0, 0.0), 10.0, "Incorrect value!");
+                assert!((decay_test.value(10.0, 1.0) - 6.0).abs() < 1e-10, "Incorrect value!");
+                assert!((decay_test.value(10.0, 2.0) - 4.0).abs() < 1e-10, "Incorrect value!");
+                assert!(decay_test.value(10.0, 20.0) > 2.0, "Incorrect value!");
+
+                let logistic_test = DemandCurve::logistic(1.0, 5.0);
+                assert_eq!(logistic_test.value(10.0, 0.0), 10.0, "Incorrect value!");
+                assert!(logistic_test.value(10.0, 1.0) > 9.5, "Incorrect value!");
+                let res = logistic_test.value(10.0, 5.0);
+                assert!(5.0 < res && res < 5.04, "Incorrect value!");
+                assert!(logistic_test.value(10.0, 15.0) < 0.01, "Incorrect value!");
+            }
         }
 
         mod total_value_should {
@@ -1042,6 +1330,32 @@ mod tests {
                 let res = logarithmic_test.total_value(10.0, 2.5);
                 assert!(23.2 < res && res < 23.21, "Incorrect value!");
             }
+
+            #[test]
+            pub fn correctly_calculate_new_curve_values() {
+                let piecewise_test = DemandCurve::piecewise_linear(vec![(1.0, -8.0), (3.0, -9.0)]);
+                // 10 + 2 + 1.5 * 0.5
+                assert_eq!(piecewise_test.total_value(10.0, 2.5), 12.75, "Incorrect value!");
+
+                let tabular_test = DemandCurve::tabular(vec![0.0, -9.0]);
+                // 10 + 1 + 1 + 1 * 0.5
+                assert_eq!(tabular_test.total_value(10.0, 3.5), 12.5, "Incorrect value!");
+
+                let decay_test = DemandCurve::exponential_decay(2.0_f64.ln(), 2.0);
+                let res = decay_test.total_value(10.0, 3.0);
+                assert!((res - 20.0).abs() < 1e-10, "Incorrect value!");
+
+                let logistic_test = DemandCurve::logistic(1.0, 5.0);
+                assert_eq!(logistic_test.total_value(10.0, 1.0), 10.0, "Incorrect value!");
+            }
+
+            #[test]
+            pub fn treat_an_empty_tabular_as_flat() {
+                let empty_tabular = DemandCurve::Tabular { values: vec![] };
+                assert_eq!(empty_tabular.value(10.0, 0.0), 10.0, "Incorrect value!");
+                assert_eq!(empty_tabular.value(10.0, 5.5), 10.0, "Incorrect value!");
+                assert_eq!(empty_tabular.total_value(10.0, 2.0), 20.0, "Incorrect value!");
+            }
         }
 
         mod inverse_should {
@@ -1074,157 +1388,1494 @@ mod tests {
                 assert!(10.0 < logarithm_test.inverse(1.0, val) && logarithm_test.inverse(1.0, val) < 10.000000001, 
                     "Incorrect value!");
             }
+
+            #[test]
+            pub fn correctly_calculate_new_curve_inverses() {
+                let piecewise_test = DemandCurve::piecewise_linear(vec![(1.0, -8.0), (3.0, -9.0)]);
+                assert_eq!(piecewise_test.inverse(10.0, 6.0), 0.5, "Incorrect value!");
+                assert_eq!(piecewise_test.inverse(10.0, 1.5), 2.0, "Incorrect value!");
+                assert_eq!(piecewise_test.inverse(10.0, 0.5), f64::INFINITY, "Incorrect value!");
+
+                let flat_test = DemandCurve::piecewise_linear(vec![(1.0, -5.0), (4.0, -5.0), (5.0, -6.0)]);
+                assert_eq!(flat_test.inverse(10.0, 5.0), 1.0, "Incorrect value!");
+                assert_eq!(flat_test.inverse(10.0, 4.5), 4.5, "Incorrect value!");
+
+                let tabular_test = DemandCurve::tabular(vec![0.0, -4.0, -9.0]);
+                assert_eq!(tabular_test.inverse(10.0, 10.0), 0.0, "Incorrect value!");
+                assert_eq!(tabular_test.inverse(10.0, 6.0), 1.0, "Incorrect value!");
+                assert_eq!(tabular_test.inverse(10.0, 3.0), 2.0, "Incorrect value!");
+                assert_eq!(tabular_test.inverse(10.0, 0.0), f64::INFINITY, "Incorrect value!");
+
+                let empty_tabular = DemandCurve::Tabular { values: vec![] };
+                assert_eq!(empty_tabular.inverse(10.0, 10.0), 0.0, "Incorrect value!");
+                assert_eq!(empty_tabular.inverse(10.0, 9.0), f64::INFINITY, "Incorrect value!");
+
+                let decay_test = DemandCurve::exponential_decay(0.3, 2.0);
+                let val = decay_test.value(10.0, 4.0);
+                assert!((decay_test.inverse(10.0, val) - 4.0).abs() < 1e-10, "Incorrect value!");
+                assert_eq!(decay_test.inverse(10.0, 2.0), f64::INFINITY, "Incorrect value!");
+
+                let logistic_test = DemandCurve::logistic(0.5, 4.0);
+                let val = logistic_test.value(10.0, 7.0);
+                assert!((logistic_test.inverse(10.0, val) - 7.0).abs() < 1e-10, "Incorrect value!");
+                assert_eq!(logistic_test.inverse(10.0, 0.0), f64::INFINITY, "Incorrect value!");
+            }
+        }
+
+        mod derivative_should {
+            use crate::demandcurve::DemandCurve;
+
+            #[test]
+            pub fn match_the_slope_of_each_new_curve() {
+                let curves = vec![
+                    DemandCurve::logarithmic(2.0),
+                    DemandCurve::piecewise_linear(vec![(1.0, -8.0), (3.0, -9.0)]),
+                    DemandCurve::exponential_decay(0.3, 2.0),
+                    DemandCurve::logistic(0.5, 4.0),
+                ];
+                let h = 1e-6;
+                for curve in curves {
+                    for step in [0.5, 2.5, 6.0] {
+                        let approx = (curve.value(10.0, step + h) - curve.value(10.0, step - h)) / (2.0 * h);
+                        let res = curve.derivative(10.0, step);
+                        assert!((approx - res).abs() < 1e-5, "{:?} at {}: {} vs {}", curve, step, res, approx);
+                    }
+                }
+                assert_eq!(DemandCurve::tabular(vec![0.0, -9.0]).derivative(10.0, 0.5), 0.0, "Incorrect value!");
+            }
+        }
+
+        mod arc_length_should {
+            use crate::demandcurve::DemandCurve;
+
+            #[test]
+            pub fn be_at_least_the_straight_line_distance() {
+                let curves = vec![
+                    DemandCurve::logarithmic(2.0),
+                    DemandCurve::piecewise_linear(vec![(1.0, -8.0), (3.0, -9.0)]),
+                    DemandCurve::tabular(vec![0.0, -4.0, -9.0]),
+                    DemandCurve::exponential_decay(0.3, 2.0),
+                    DemandCurve::logistic(0.5, 4.0),
+                ];
+                for curve in curves {
+                    assert_eq!(curve.arc_length(4.0, 4.0), 0.0, "Incorrect value!");
+                    let res = curve.arc_length(4.0, 12.0);
+                    let straight = (64.0 + (curve.value(4.0, 8.0) - 4.0).powf(2.0)).sqrt();
+                    assert!(res >= straight - 1e-10, "{:?}: {} < {}", curve, res, straight);
+                }
+            }
         }
     }
 
-    mod pop_tests {
-        mod try_satisfy_until_incomplete_should {
-            use crate::{data::Data, demandcurve::DemandCurve, desire::Desire, good::{Good, GoodTags}, item::Item, markethistory::{GoodRecord, MarketHistory}, pop::{Pop, PropertyRecord}};
+    mod data_tests {
+        mod try_add_good_should {
+            use std::collections::HashMap;
+
+            use crate::{data::Data, good::Good, want::{Want, WantSource}};
 
             #[test]
-            pub fn correctly_stop_when_incomplete_found() {
-                // set up data for goods.
+            pub fn add_good_to_want_sources() {
                 let mut data = Data::new();
-                data.goods.insert(2, Good::new(2, "2".to_string(), 
-                    String::from("")).with_tags(vec![GoodTags::Nonexchangeable]));
-                data.goods.insert(3, Good::new(3, "3".to_string(), String::from("")));
-                data.goods.insert(4, Good::new(4, "4".to_string(), String::from("")));
-                data.goods.insert(5, Good::new(5, "5".to_string(), String::from("")));
-                data.goods.insert(6, Good::new(6, "6".to_string(), String::from("")));
-                
-                // set up market info, including a currency.
-                let mut market = MarketHistory::new();
-                market.good_records.insert(2, GoodRecord::new().with_price(1.0));
-                market.good_records.insert(3, GoodRecord::new().with_price(1.0));
-                market.currencies.insert(3);
-                market.good_records.insert(4, GoodRecord::new().with_price(1.0));
-                market.good_records.insert(5, GoodRecord::new().with_price(1.0));
-                market.good_records.insert(6, GoodRecord::new().with_price(1.0));
-                
-                // create a pop and property.
-                let mut test_pop = Pop::new(0, 0, 0);
-                test_pop.property.insert(2, PropertyRecord::new(5.0));
-                test_pop.property.insert(3, PropertyRecord::new(10.0));
-                test_pop.property.insert(4, PropertyRecord::new(10.0));
-                test_pop.property.insert(5, PropertyRecord::new(10.0));
-                test_pop.property.insert(6, PropertyRecord::new(10.0));
-                // initial AMV = 45.0
-                // add Desires to satisfy.
-                test_pop.desires.push_back(Desire::new(Item::Good(2), 1.0, 1.0, DemandCurve::Linear { slope: 1.0 })
-                    .with_steps(0));
-                test_pop.desires.push_back(Desire::new(Item::Good(3), 1.0, 1.0, DemandCurve::Linear { slope: 1.0 })
-                    .with_steps(0));
-                test_pop.desires.push_back(Desire::new(Item::Good(4), 1.0, 1.0, DemandCurve::Linear { slope: 1.0 })
-                    .with_steps(0));
-                test_pop.desires.push_back(Desire::new(Item::Good(5), 1.0, 1.0, DemandCurve::Linear { slope: 1.0 })
-                    .with_steps(0));
-                test_pop.desires.push_back(Desire::new(Item::Good(6), 1.0, 1.0, DemandCurve::Linear { slope: 1.0 })
-                    .with_steps(0));
-                // should consume 25.0 AMV, 5 steps of each, range from 1.0 to 6.0.
+                data.wants.insert(4, Want::new(4, String::from("testWant1")));
+                data.wants.insert(5, Want::new(5, String::from("testWant2")));
+                let mut wants = HashMap::new();
+                wants.insert(4, 1.0);
+                wants.insert(5, 2.0);
+                data.add_good(Good::new(4, String::from("testGood1"), String::new())
+                    .with_ownership(wants.clone()));
+                data.add_good(Good::new(5, String::from("testGood2"), String::new())
+                    .with_uses(2.0, wants.clone())
+                    .with_consumption(1.0, wants.clone()));
 
-                // run function 
-                test_pop.try_satisfy_until_incomplete(&data, &market);
+                assert_eq!(data.get_want_sources(4, WantSource::Ownership), &[4]);
+                assert_eq!(data.get_want_sources(4, WantSource::Use), &[5]);
+                assert_eq!(data.get_want_sources(4, WantSource::Consumption), &[5]);
+                assert_eq!(data.get_want_sources(5, WantSource::Ownership), &[4]);
+                assert_eq!(data.get_want_sources(5, WantSource::Use), &[5]);
+                assert_eq!(data.get_want_sources(5, WantSource::Consumption), &[5]);
+
+                let sources = data.want_sources(5);
+                assert_eq!(sources.len(), 3);
+                assert_eq!(sources[0], (4, WantSource::Ownership, 2.0));
+                assert_eq!(sources[1], (5, WantSource::Use, 2.0));
+                assert_eq!(sources[2], (5, WantSource::Consumption, 2.0));
+            }
+        }
 
-                // test results
-                assert_eq!(test_pop.satisfaction.excess_amv, 20.0);
-                assert_eq!(test_pop.satisfaction.value, 5.0);
-                assert_eq!(test_pop.satisfaction.satisfaction, 25.0);
-                assert_eq!(test_pop.satisfaction.steps, 25.0);
+        mod remove_good_should {
+            use std::collections::HashMap;
 
-                assert_eq!(test_pop.property.get(&2).unwrap().reserved, 5.0);
-                assert_eq!(test_pop.property.get(&3).unwrap().reserved, 5.0);
-                assert_eq!(test_pop.property.get(&4).unwrap().reserved, 5.0);
-                assert_eq!(test_pop.property.get(&5).unwrap().reserved, 5.0);
-                assert_eq!(test_pop.property.get(&6).unwrap().reserved, 5.0);
+            use crate::{constants::TIME_ID, data::{Data, DataReference, RemoveMode}, demandcurve::DemandCurve, desire::Desire, good::Good, item::Item, process::{Process, ProcessInput, ProcessOutput}, species::Species, want::{Want, WantSource}};
+
+            #[test]
+            pub fn remove_good_from_want_sources_and_class() {
+                let mut data = Data::new();
+                data.wants.insert(4, Want::new(4, String::from("testWant1")));
+                let mut wants = HashMap::new();
+                wants.insert(4, 1.0);
+                data.add_good(Good::new(4, String::from("testGood1"), String::new())
+                    .in_class(4)
+                    .with_ownership(wants.clone()));
+                data.add_good(Good::new(5, String::from("testGood2"), String::new())
+                    .in_class(4)
+                    .with_consumption(1.0, wants.clone()));
+
+                // can't remove the class base while it has members.
+                assert!(data.remove_good(4, RemoveMode::Refuse).is_err());
+                // but can remove the member.
+                let removal = data.remove_good(5, RemoveMode::Refuse).unwrap();
+                assert_eq!(removal.removed.id, 5);
+                assert!(removal.cascaded.is_empty());
+                assert!(!data.goods.contains_key(&5));
+                assert!(!data.get_class(4).contains(&5));
+                assert!(data.get_want_sources(4, WantSource::Consumption).is_empty());
+                assert_eq!(data.get_want_sources(4, WantSource::Ownership), &[4]);
+                // with the member gone, base can go too.
+                data.remove_good(4, RemoveMode::Refuse).unwrap();
+                assert!(!data.classes.contains_key(&4));
+                // time always stays.
+                data.add_time();
+                assert!(data.remove_good(TIME_ID, RemoveMode::Cascade).is_err());
+                assert!(data.goods.contains_key(&TIME_ID));
+                assert!(data.get_want_sources(4, WantSource::Ownership).is_empty());
+                // and missing goods return err.
+                assert!(data.remove_good(4, RemoveMode::Refuse).is_err());
             }
 
             #[test]
-            pub fn correctly_stop_when_incomplete_found_using_self_working_desires() {
-                // set up data for goods.
+            pub fn refuse_or_cascade_dependent_references() {
                 let mut data = Data::new();
-                data.goods.insert(2, Good::new(2, "2".to_string(), 
-                    String::from("")).with_tags(vec![GoodTags::Nonexchangeable]));
-                data.goods.insert(3, Good::new(3, "3".to_string(), String::from("")));
-                data.goods.insert(4, Good::new(4, "4".to_string(), String::from("")));
-                data.goods.insert(5, Good::new(5, "5".to_string(), String::from("")));
-                // data.goods.insert(6, Good::new(6, "6".to_string(), String::from("")));
-                
-                // set up market info, including a currency.
-                let mut market = MarketHistory::new();
-                market.good_records.insert(2, GoodRecord::new().with_price(1.0));
-                market.good_records.insert(3, GoodRecord::new().with_price(1.0));
-                market.currencies.insert(3);
-                market.good_records.insert(4, GoodRecord::new().with_price(1.0));
-                market.good_records.insert(5, GoodRecord::new().with_price(1.0));
-                market.good_records.insert(6, GoodRecord::new().with_price(1.0));
-                
-                // create a pop and property.
-                let mut test_pop = Pop::new(0, 0, 0);
-                test_pop.property.insert(2, PropertyRecord::new(5.0));
-                test_pop.property.insert(3, PropertyRecord::new(10.0));
-                test_pop.property.insert(4, PropertyRecord::new(10.0));
-                test_pop.property.insert(5, PropertyRecord::new(10.0));
-                test_pop.property.insert(6, PropertyRecord::new(10.0));
-                // initial AMV = 45.0
-                // add Desires to satisfy.
-                test_pop.working_desires.push_back(Desire::new(Item::Good(2), 1.0, 1.0, DemandCurve::Linear { slope: 1.0 })
-                    .with_steps(0));
-                test_pop.working_desires.push_back(Desire::new(Item::Good(3), 1.0, 1.0, DemandCurve::Linear { slope: 1.0 })
-                    .with_steps(0));
-                test_pop.working_desires.push_back(Desire::new(Item::Good(4), 1.0, 1.0, DemandCurve::Linear { slope: 1.0 })
-                    .with_steps(0));
-                test_pop.working_desires.push_back(Desire::new(Item::Good(5), 1.0, 1.0, DemandCurve::Linear { slope: 1.0 })
-                    .with_steps(0));
-                test_pop.working_desires.push_back(Desire::new(Item::Good(6), 1.0, 1.0, DemandCurve::Linear { slope: 1.0 })
-                    .with_steps(0));
-                // should consume 25.0 AMV, 5 steps of each, range from 1.0 to 6.0.
+                data.add_good(Good::new(4, String::from("testGood1"), String::new())
+                    .in_class(4));
+                data.add_good(Good::new(5, String::from("testGood2"), String::new())
+                    .in_class(4)
+                    .decays_to(4, 1.0));
+                data.add_good(Good::new(6, String::from("testGood3"), String::new()));
+                data.processes.insert(0, Process::new(0, String::from("testProcess"), String::new())
+                    .uses_input(ProcessInput::new(4, 1.0))
+                    .uses_input(ProcessInput::new(6, 1.0))
+                    .has_output(ProcessOutput::new(Item::Good(4), 1.0))
+                    .has_output(ProcessOutput::new(Item::Good(6), 1.0)));
+                data.species.insert(0, Species::new(0, String::from("testSpecies"))
+                    .with_desire(Desire::new(Item::Good(4), 1.0, 1.0, DemandCurve::linear(-1.0)))
+                    .with_desire(Desire::new(Item::Class(4), 1.0, 2.0, DemandCurve::linear(-1.0)))
+                    .with_desire(Desire::new(Item::Good(6), 1.0, 3.0, DemandCurve::linear(-1.0))));
+
+                let expected = vec![
+                    DataReference::ClassMember { good: 5 },
+                    DataReference::DecaysTo { good: 5 },
+                    DataReference::ProcessInput { process: 0 },
+                    DataReference::ProcessOutput { process: 0 },
+                    DataReference::SpeciesDesire { species: 0, item: Item::Good(4) },
+                    DataReference::SpeciesDesire { species: 0, item: Item::Class(4) },
+                ];
+                assert_eq!(data.good_references(4), expected);
+                // refuse changes nothing.
+                assert!(data.remove_good(4, RemoveMode::Refuse).is_err());
+                assert!(data.goods.contains_key(&4));
+                assert_eq!(data.processes.get(&0).unwrap().inputs.len(), 2);
+
+                // cascade strips everything out.
+                let removal = data.remove_good(4, RemoveMode::Cascade).unwrap();
+                assert_eq!(removal.cascaded, expected);
+                assert!(!data.classes.contains_key(&4));
+                let member = data.get_good(5);
+                assert_eq!(member.class, None);
+                assert!(member.decays_to.is_none());
+                let process = data.processes.get(&0).unwrap();
+                assert_eq!(process.inputs.len(), 1);
+                assert_eq!(process.inputs[0].good, 6);
+                assert_eq!(process.outputs.len(), 1);
+                assert_eq!(process.outputs[0].item, Item::Good(6));
+                let species = data.get_species(0);
+                assert_eq!(species.desires.len(), 1);
+                assert_eq!(species.desires[0].item, Item::Good(6));
+            }
 
-                // run function 
-                test_pop.try_satisfy_until_incomplete(&data, &market);
+            #[test]
+            pub fn refuse_or_cascade_outputs_of_its_class() {
+                let mut data = Data::new();
+                data.add_good(Good::new(4, String::from("testGood1"), String::new())
+                    .in_class(4));
+                data.add_good(Good::new(6, String::from("testGood3"), String::new()));
+                let mut process = Process::new(0, String::from("testProcess"), String::new())
+                    .uses_input(ProcessInput::new(6, 1.0))
+                    .has_output(ProcessOutput::new(Item::Good(6), 1.0));
+                // has_output() won't take a class, but loaded data can still hold one.
+                process.outputs.insert(0, ProcessOutput::new(Item::Class(4), 1.0));
+                data.processes.insert(0, process);
+
+                assert_eq!(data.good_references(4), vec![DataReference::ProcessOutput { process: 0 }]);
+                assert!(data.remove_good(4, RemoveMode::Refuse).is_err());
+                assert_eq!(data.processes.get(&0).unwrap().outputs.len(), 2);
+
+                data.remove_good(4, RemoveMode::Cascade).unwrap();
+                let process = data.processes.get(&0).unwrap();
+                assert_eq!(process.outputs.len(), 1);
+                assert_eq!(process.outputs[0].item, Item::Good(6));
+            }
+        }
 
-                // test results
-                assert_eq!(test_pop.satisfaction.excess_amv, 20.0);
-                assert_eq!(test_pop.satisfaction.value, 5.0);
-                assert_eq!(test_pop.satisfaction.satisfaction, 25.0);
-                assert_eq!(test_pop.satisfaction.steps, 25.0);
+        mod try_add_layer_should {
+            use crate::{data::{Data, DataError, DataKind, DataReference, RemoveMode}, demandcurve::DemandCurve, desire::Desire, good::Good, item::Item, layer::Layer};
 
-                assert_eq!(test_pop.property.get(&2).unwrap().reserved, 5.0);
-                assert_eq!(test_pop.property.get(&3).unwrap().reserved, 5.0);
-                assert_eq!(test_pop.property.get(&4).unwrap().reserved, 5.0);
-                assert_eq!(test_pop.property.get(&5).unwrap().reserved, 5.0);
-                assert_eq!(test_pop.property.get(&6).unwrap().reserved, 5.0);
+            #[test]
+            pub fn register_layers_and_track_their_desires() {
+                let mut data = Data::new();
+                data.add_good(Good::new(4, String::from("Incense"), String::new()));
+                data.try_add_layer(Layer::new(0, String::from("Sun Worship"), String::from("Religion"))
+                    .with_desire(Desire::new(Item::Good(4), 1.0, 1.0, DemandCurve::linear(-1.0))))
+                    .unwrap();
+                assert!(data.try_add_layer(Layer::new(0, String::from("Moon Worship"), String::from("Religion"))).is_err());
+                assert_eq!(data.get_layer(0).name, "Sun Worship");
+                assert!(data.try_get_layer(1).is_none());
+                assert!(data.validate().is_ok());
+
+                assert_eq!(data.good_references(4), vec![DataReference::LayerDesire { layer: 0, item: Item::Good(4) }]);
+                data.remove_good(4, RemoveMode::Cascade).unwrap();
+                assert!(data.get_layer(0).desires.is_empty());
+
+                data.layers.get_mut(&0).unwrap().desires
+                    .push(Desire::new(Item::Good(4), 1.0, 1.0, DemandCurve::linear(-1.0)));
+                assert_eq!(data.validate().unwrap_err(), vec![
+                    DataError::MissingDesireItem { kind: DataKind::Layer, owner: 0, item: Item::Good(4) }]);
             }
         }
 
-        mod create_sell_orders_should {
-            use crate::{data::Data, good::{Good, GoodTags}, markethistory::{GoodRecord, MarketHistory}, pop::{Pop, PropertyRecord}};
+        mod remove_want_should {
+            use std::collections::HashMap;
+
+            use crate::{data::{Data, DataReference, RemoveMode}, good::Good, want::{Want, WantSource}};
 
             #[test]
-            pub fn correctly_return_available_goods_for_sale() {
+            pub fn cascade_to_goods_producing_it() {
                 let mut data = Data::new();
-                data.goods.insert(2, Good::new(2, "2".to_string(), 
-                    String::from("")).with_tags(vec![GoodTags::Nonexchangeable]));
-                data.goods.insert(3, Good::new(3, "3".to_string(), String::from("")));
-                data.goods.insert(4, Good::new(4, "4".to_string(), String::from("")));
-                data.goods.insert(5, Good::new(5, "5".to_string(), String::from("")));
-                data.goods.insert(6, Good::new(6, "6".to_string(), String::from("")));
-                
-                let mut market = MarketHistory::new();
-                market.good_records.insert(2, GoodRecord::new().with_price(1.0));
-                market.good_records.insert(3, GoodRecord::new().with_price(1.0));
-                market.currencies.insert(3);
-                market.good_records.insert(4, GoodRecord::new().with_price(1.0));
-                market.good_records.insert(5, GoodRecord::new().with_price(1.0));
-                market.good_records.insert(6, GoodRecord::new().with_price(1.0));
-                
+                data.wants.insert(4, Want::new(4, String::from("testWant1")));
+                data.wants.insert(5, Want::new(5, String::from("testWant2")));
+                let mut wants = HashMap::new();
+                wants.insert(4, 1.0);
+                wants.insert(5, 1.0);
+                data.add_good(Good::new(4, String::from("testGood1"), String::new())
+                    .with_uses(1.0, wants.clone()));
+
+                assert_eq!(data.want_references(4), 
+                    vec![DataReference::WantSource { good: 4, source: WantSource::Use }]);
+                assert!(data.remove_want(4, RemoveMode::Refuse).is_err());
+                let removal = data.remove_want(4, RemoveMode::Cascade).unwrap();
+                assert_eq!(removal.removed.id, 4);
+                assert!(!data.wants.contains_key(&4));
+                assert!(!data.get_good(4).use_wants.contains_key(&4));
+                assert!(data.get_good(4).use_wants.contains_key(&5));
+            }
+        }
+
+        mod replace_good_should {
+            use std::collections::HashMap;
+
+            use crate::{data::Data, good::Good, want::{Want, WantSource}};
+
+            #[test]
+            pub fn update_class_and_want_sources() {
+                let mut data = Data::new();
+                data.wants.insert(4, Want::new(4, String::from("testWant1")));
+                data.wants.insert(5, Want::new(5, String::from("testWant2")));
+                let mut wants = HashMap::new();
+                wants.insert(4, 1.0);
+                data.add_good(Good::new(4, String::from("testGood1"), String::new())
+                    .in_class(4));
+                data.add_good(Good::new(5, String::from("testGood2"), String::new())
+                    .in_class(4)
+                    .with_ownership(wants.clone()));
+                // base can't stop being a base while it has members.
+                assert!(data.replace_good(Good::new(4, String::from("testGood1"), String::new())).is_err());
+                // replace member, moving it out of the class and to another want.
+                let mut new_wants = HashMap::new();
+                new_wants.insert(5, 1.0);
+                let old = data.replace_good(Good::new(5, String::from("newGood2"), String::new())
+                    .with_consumption(1.0, new_wants)).unwrap();
+                assert_eq!(old.name, "testGood2");
+                assert_eq!(data.get_good(5).name, "newGood2");
+                assert!(!data.get_class(4).contains(&5));
+                assert!(data.get_class(4).contains(&4));
+                assert!(data.get_want_sources(4, WantSource::Ownership).is_empty());
+                assert_eq!(data.get_want_sources(5, WantSource::Consumption), &[5]);
+                // replacing the base while keeping it as base keeps the class whole.
+                data.replace_good(Good::new(5, String::from("newGood2"), String::new())
+                    .in_class(4)).unwrap();
+                data.replace_good(Good::new(4, String::from("newGood1"), String::new())
+                    .in_class(4)).unwrap();
+                assert_eq!(data.get_class(4).len(), 2);
+            }
+
+            #[test]
+            pub fn keep_the_old_good_when_the_new_one_is_invalid() {
+                let mut data = Data::new();
+                data.add_good(Good::new(4, String::from("testGood1"), String::new()));
+                data.add_good(Good::new(5, String::from("testGood2"), String::new())
+                    .in_class(5));
+                data.add_good(Good::new(6, String::from("testGood3"), String::new())
+                    .in_class(5));
+                // 4 is not a class base.
+                assert!(data.replace_good(Good::new(6, String::from("newGood3"), String::new())
+                    .in_class(4)).is_err());
+                assert!(data.try_add_good(Good::new(7, String::from("testGood4"), String::new())
+                    .in_class(4)).is_err());
+                // nor do wants which don't exist.
+                let mut wants = HashMap::new();
+                wants.insert(9, 1.0);
+                assert!(data.replace_good(Good::new(6, String::from("newGood3"), String::new())
+                    .with_ownership(wants)).is_err());
+                assert_eq!(data.get_good(6).name, "testGood3");
+                assert!(data.get_class(5).contains(&6));
+                assert!(!data.classes.contains_key(&4));
+            }
+        }
+
+        mod validate_should {
+            use std::collections::HashMap;
+
+            use crate::{culture::Culture, data::{Data, DataError, DataKind}, demandcurve::DemandCurve, desire::Desire, good::Good, item::Item, process::{Process, ProcessInput, ProcessOutput}, want::Want};
+
+            #[test]
+            pub fn return_ok_for_consistent_data() {
+                let mut data = Data::new();
+                data.add_time();
+                data.wants.insert(4, Want::new(4, String::from("testWant1")));
+                let mut wants = HashMap::new();
+                wants.insert(4, 1.0);
+                data.add_good(Good::new(4, String::from("testGood1"), String::new())
+                    .in_class(4)
+                    .with_consumption(1.0, wants));
+                data.add_good(Good::new(5, String::from("testGood2"), String::new())
+                    .in_class(4)
+                    .decays_to(4, 1.0));
+                // time -> 5 -> 4 (by decay)
+                data.processes.insert(0, Process::new(0, String::from("testProcess"), String::new())
+                    .uses_input(ProcessInput::new(0, 1.0))
+                    .has_output(ProcessOutput::new(Item::Good(5), 1.0)));
+                data.processes.insert(1, Process::new(1, String::from("testProcess2"), String::new())
+                    .uses_input(ProcessInput::new(4, 1.0))
+                    .has_output(ProcessOutput::new(Item::Want(4), 1.0)));
+
+                assert!(data.validate().is_ok());
+                assert!(data.try_get_good(4).is_some());
+                assert!(data.try_get_good(6).is_none());
+                assert!(data.try_get_class(4).is_some());
+                assert!(data.try_get_want(5).is_none());
+                assert!(data.try_get_species(0).is_none());
+                assert!(data.try_get_culture(0).is_none());
+                assert!(data.try_get_process(1).is_some());
+            }
+
+            #[test]
+            pub fn return_every_problem_found() {
+                let mut data = Data::new();
+                data.add_time();
+                data.wants.insert(4, Want::new(4, String::from("testWant1")));
+                // bypass add_good to get bad data in.
+                let mut wants = HashMap::new();
+                wants.insert(4, 1.0);
+                wants.insert(9, 1.0);
+                data.goods.insert(4, Good::new(4, String::from("testGood1"), String::new())
+                    .in_class(8)
+                    .decays_to(7, 1.0)
+                    .with_ownership(wants));
+                data.goods.insert(5, Good::new(5, String::from("testGood2"), String::new()));
+                data.classes.insert(6, [6].into_iter().collect());
+                data.processes.insert(0, Process::new(0, String::from("testProcess"), String::new())
+                    .uses_input(ProcessInput::new(5, 1.0))
+                    .uses_input(ProcessInput::new(7, 1.0))
+                    .has_output(ProcessOutput::new(Item::Want(9), 1.0))
+                    .has_parent(3));
+                data.culture.insert(2, Culture::new(1, String::from("testCulture"))
+                    .with_desire(Desire::new(Item::Class(8), 1.0, 1.0, DemandCurve::linear(-1.0))));
+
+                let errors = data.validate().unwrap_err();
+                assert_eq!(errors, vec![
+                    DataError::MissingClass { good: 4, class: 8 },
+                    DataError::MissingDecayTarget { good: 4, target: 7 },
+                    DataError::WantSourceMismatch { want: 4, good: 4, source: crate::want::WantSource::Ownership },
+                    DataError::MissingWant { good: 4, want: 9 },
+                    DataError::OrphanClass { class: 6 },
+                    DataError::ClassMemberMismatch { class: 6, good: 6 },
+                    DataError::MissingProcessParent { process: 0, parent: 3 },
+                    DataError::UnproducibleInput { process: 0, good: 5 },
+                    DataError::MissingProcessInput { process: 0, good: 7 },
+                    DataError::MissingProcessOutput { process: 0, item: Item::Want(9) },
+                    DataError::IdMismatch { kind: DataKind::Culture, key: 2, id: 1 },
+                    DataError::MissingDesireItem { kind: DataKind::Culture, owner: 2, item: Item::Class(8) },
+                ]);
+            }
+        }
+
+        mod cheapest_want_sources_should {
+            use std::collections::HashMap;
+
+            use crate::{data::Data, good::Good, markethistory::{GoodRecord, MarketHistory}, want::{Want, WantSource}};
+
+            #[test]
+            pub fn rank_sources_by_price_per_unit_of_want() {
+                let mut data = Data::new();
+                data.wants.insert(4, Want::new(4, String::from("testWant1")));
+                let mut weak = HashMap::new();
+                weak.insert(4, 1.0);
+                let mut strong = HashMap::new();
+                strong.insert(4, 4.0);
+                data.add_good(Good::new(4, String::from("testGood1"), String::new())
+                    .with_ownership(weak.clone()));
+                data.add_good(Good::new(5, String::from("testGood2"), String::new())
+                    .with_consumption(1.0, strong.clone()));
+                data.add_good(Good::new(6, String::from("testGood3"), String::new())
+                    .with_uses(1.0, weak.clone()));
+                data.add_good(Good::new(7, String::from("testGood4"), String::new())
+                    .with_uses(1.0, weak.clone()));
+
+                let mut market = MarketHistory::new();
+                market.good_records.insert(4, GoodRecord::new().with_price(2.0));
+                market.good_records.insert(5, GoodRecord::new().with_price(4.0));
+                market.good_records.insert(6, GoodRecord::new().with_price(0.5));
+                // 7 has no price, so it should be excluded.
+
+                let result = data.cheapest_want_sources(4, &market);
+                assert_eq!(result.len(), 3);
+                assert_eq!(result[0], (6, WantSource::Use, 0.5));
+                assert_eq!(result[1], (5, WantSource::Consumption, 1.0));
+                assert_eq!(result[2], (4, WantSource::Ownership, 2.0));
+            }
+        }
+        mod reload_should {
+            use std::collections::HashMap;
+
+            use crate::{constants::TIME_ID, data::{Data, DataError, DataKind, DataReload}, demandcurve::DemandCurve, desire::{Desire, DesireTag}, good::Good, household::HouseholdMember, item::Item, layer::Layer, process::{Process, ProcessInput, ProcessOutput}, species::Species, want::{Want, WantSource}};
+
+            fn test_data() -> Data {
+                let mut data = Data::new();
+                data.add_time();
+                data.wants.insert(4, Want::new(4, String::from("Rest")));
+                data.add_good(Good::new(5, String::from("Bed"), String::new())
+                    .with_uses(1.0, HashMap::from([(4, 1.0)])));
+                data.processes.insert(6, Process::new(6, String::from("Carpentry"), String::new())
+                    .uses_input(ProcessInput::new(TIME_ID, 1.0))
+                    .has_output(ProcessOutput::new(Item::Good(5), 1.0)));
+                data.species.insert(7, Species::new(7, String::from("Human"))
+                    .with_desire(Desire::new(Item::Want(4), 1.0, 1.0, DemandCurve::linear(-1.0))));
+                data
+            }
+
+            #[test]
+            pub fn report_only_changed_definitions() {
+                let mut data = test_data();
+                let reload = DataReload::new()
+                    // unchanged
+                    .with_good(Good::new(5, String::from("Bed"), String::new())
+                        .with_uses(1.0, HashMap::from([(4, 1.0)])))
+                    // new
+                    .with_good(Good::new(8, String::from("Chair"), String::new())
+                        .with_uses(1.0, HashMap::from([(4, 0.5)])))
+                    .with_process(Process::new(6, String::from("Carpentry"), String::new())
+                        .uses_input(ProcessInput::new(TIME_ID, 2.0))
+                        .has_output(ProcessOutput::new(Item::Good(5), 1.0)))
+                    .with_species_desires(7, vec![
+                        Desire::new(Item::Want(4), 2.0, 1.0, DemandCurve::linear(-1.0))]);
+                let report = data.reload(reload);
+                assert_eq!(report.goods_added, vec![8]);
+                assert!(report.goods_changed.is_empty());
+                assert_eq!(report.processes_changed, vec![6]);
+                assert!(report.processes_added.is_empty());
+                assert_eq!(report.species_changed, vec![7]);
+                assert!(report.rejected.is_empty());
+                assert_eq!(data.get_want_sources(4, WantSource::Use), &[5, 8]);
+                assert_eq!(data.try_get_process(6).unwrap().inputs[0].amount, 2.0);
+                assert_eq!(data.get_species(7).desires[0].amount, 2.0);
+
+                // reloading the same desires again changes nothing.
+                let report = data.reload(DataReload::new()
+                    .with_species_desires(7, vec![
+                        Desire::new(Item::Want(4), 2.0, 1.0, DemandCurve::linear(-1.0))]));
+                assert!(report.species_changed.is_empty());
+            }
+
+            #[test]
+            pub fn reject_time_and_missing_items() {
+                let mut data = test_data();
+                let report = data.reload(DataReload::new()
+                    .with_good(Good::new(TIME_ID, String::from("Clock"), String::new()))
+                    .with_process(Process::new(9, String::from("Magic"), String::new())
+                        .has_output(ProcessOutput::new(Item::Good(10), 1.0)))
+                    .with_species_desires(7, vec![
+                        Desire::new(Item::Good(10), 1.0, 1.0, DemandCurve::linear(-1.0))])
+                    .with_culture_desires(3, vec![]));
+                assert_eq!(report.rejected.len(), 4);
+                assert_eq!(report.rejected[0].0, DataKind::Good);
+                assert_eq!(report.rejected[1].0, DataKind::Process);
+                assert_eq!(report.rejected[2].0, DataKind::Species);
+                assert_eq!(report.rejected[3].0, DataKind::Culture);
+                assert_eq!(data.get_good(TIME_ID).name, "Time");
+                assert!(data.try_get_process(9).is_none());
+                assert_eq!(data.get_species(7).desires[0].item, Item::Want(4));
+            }
+
+            #[test]
+            pub fn reject_invalid_desires_and_reload_layers() {
+                let mut data = test_data();
+                data.try_add_layer(Layer::new(2, String::from("Monks"), String::from("Religion")))
+                    .unwrap();
+                // a LifeNeed without an end, which with_tag() would refuse.
+                let mut endless = Desire::new(Item::Want(4), 1.0, 1.0, DemandCurve::linear(-1.0))
+                    .with_steps(0);
+                endless.tags.push(DesireTag::LifeNeed(0.1));
+                // tags which can't be together.
+                let mut clashing = Desire::new(Item::Good(5), 1.0, 1.0, DemandCurve::linear(-1.0));
+                clashing.tags = vec![DesireTag::HouseholdNeed, DesireTag::HouseMemberNeed(HouseholdMember::Adult)];
+                let report = data.reload(DataReload::new()
+                    .with_species_desires(7, vec![endless.clone()])
+                    .with_layer_desires(2, vec![clashing.clone()]));
+                assert_eq!(report.rejected.len(), 2);
+                assert_eq!(report.rejected[0].0, DataKind::Species);
+                assert_eq!(report.rejected[1].0, DataKind::Layer);
+                assert!(!report.any_desires_changed());
+                assert_eq!(data.get_species(7).desires[0].item, Item::Want(4));
+                assert!(data.get_layer(2).desires.is_empty());
+                assert!(data.validate().is_ok());
+                // the same checks as adding.
+                assert!(data.try_add_species(Species::new(8, String::from("Elf"))
+                    .with_desire(endless.clone())).is_err());
+                // and validate catches them if they get in some other way.
+                data.species.get_mut(&7).unwrap().desires.push(endless);
+                assert_eq!(data.validate().unwrap_err(), vec![
+                    DataError::InvalidDesire { kind: DataKind::Species, owner: 7, item: Item::Want(4) }]);
+
+                // valid layer desires reload like the rest.
+                let report = data.reload(DataReload::new()
+                    .with_layer_desires(2, vec![Desire::new(Item::Good(5), 1.0, 1.0, DemandCurve::linear(-1.0))]));
+                assert_eq!(report.layers_changed, vec![2]);
+                assert!(report.any_desires_changed());
+                assert_eq!(data.get_layer(2).desires[0].item, Item::Good(5));
+            }
+        }
+    }
+
+    mod datapack_tests {
+        mod load_pack_should {
+            use std::collections::HashMap;
+
+            use crate::{constants::TIME_ID, data::{Data, DataKind, DataReference}, datapack::{DataPack, PackConflict, PackEntry, PackRegistry}, demandcurve::DemandCurve, desire::Desire, good::Good, item::Item, process::{Process, ProcessInput, ProcessOutput}, species::Species, want::{Want, WantSource}};
+
+            fn base_pack() -> DataPack {
+                let mut wants = HashMap::new();
+                wants.insert(7, 1.0);
+                DataPack::new(String::from("base"))
+                    .with_want(PackEntry::Add(Want::new(7, String::from("Rest"))))
+                    .with_good(PackEntry::Add(Good::new(3, String::from("Bed"), String::new())
+                        .with_uses(1.0, wants)))
+                    .with_good(PackEntry::Add(Good::new(4, String::from("Wood"), String::new())))
+                    .with_process(PackEntry::Add(Process::new(2, String::from("Carpentry"), String::new())
+                        .uses_input(ProcessInput::new(4, 2.0))
+                        .uses_input(ProcessInput::new(TIME_ID, 1.0))
+                        .has_output(ProcessOutput::new(Item::Good(3), 1.0))))
+                    .with_process(PackEntry::Add(Process::new(5, String::from("Gathering"), String::new())
+                        .uses_input(ProcessInput::new(TIME_ID, 1.0))
+                        .has_output(ProcessOutput::new(Item::Good(4), 1.0))))
+                    .with_species(PackEntry::Add(Species::new(9, String::from("Human"))
+                        .with_desire(Desire::new(Item::Want(7), 1.0, 1.0,
+                            DemandCurve::Geometric { factor: 0.9 }))))
+            }
+
+            #[test]
+            pub fn map_local_ids_by_name() {
+                let mut data = Data::new();
+                let mut registry = PackRegistry::new();
+                let report = registry.load_pack(&mut data, base_pack());
+                assert!(report.is_clean(), "{:?}", report.conflicts);
+                assert_eq!(report.added.len(), 6);
+
+                let rest = registry.id_of(DataKind::Want, "Rest").unwrap();
+                let bed = registry.id_of(DataKind::Good, "Bed").unwrap();
+                let wood = registry.id_of(DataKind::Good, "Wood").unwrap();
+                let carpentry = registry.id_of(DataKind::Process, "Carpentry").unwrap();
+                let human = registry.id_of(DataKind::Species, "Human").unwrap();
+                assert_ne!(bed, TIME_ID);
+                assert_ne!(wood, TIME_ID);
+                assert_eq!(registry.name_of(DataKind::Good, bed), Some("Bed"));
+                assert!(data.get_good(bed).use_wants.contains_key(&rest));
+                assert_eq!(data.get_want_sources(rest, WantSource::Use), &[bed]);
+                let process = data.try_get_process(carpentry).unwrap();
+                assert!(process.inputs.iter().any(|x| x.good == wood));
+                assert!(process.inputs.iter().any(|x| x.good == TIME_ID));
+                assert_eq!(process.outputs[0].item, Item::Good(bed));
+                assert_eq!(data.get_species(human).desires[0].item, Item::Want(rest));
+                assert_eq!(data.get_good(TIME_ID).name, "Time");
+                assert_eq!(data.validate(), Ok(()));
+            }
+
+            #[test]
+            pub fn override_and_remove_from_later_packs() {
+                let mut data = Data::new();
+                let mut registry = PackRegistry::new();
+                registry.load_pack(&mut data, base_pack());
+                let bed = registry.id_of(DataKind::Good, "Bed").unwrap();
+                let wood = registry.id_of(DataKind::Good, "Wood").unwrap();
+                let carpentry = registry.id_of(DataKind::Process, "Carpentry").unwrap();
+                let gathering = registry.id_of(DataKind::Process, "Gathering").unwrap();
+
+                // override bed with a new local id, referencing rest by import.
+                let mut wants = HashMap::new();
+                wants.insert(0, 2.0);
+                let pack = DataPack::new(String::from("mod"))
+                    .imports(DataKind::Want, 0, String::from("Rest"))
+                    .with_good(PackEntry::Override(Good::new(1, String::from("Bed"), String::new())
+                        .with_uses(1.0, wants)))
+                    .with_good(PackEntry::Remove(String::from("Wood")))
+                    .with_good(PackEntry::Add(Good::new(2, String::from("Stone"), String::new())));
+                let report = registry.load_pack(&mut data, pack);
+                assert_eq!(report.overridden, vec![(DataKind::Good, String::from("Bed"))]);
+                assert_eq!(report.removed, vec![(DataKind::Good, String::from("Wood"))]);
+                assert_eq!(report.conflicts, vec![PackConflict::Cascaded { kind: DataKind::Good,
+                    name: String::from("Wood"),
+                    references: vec![DataReference::ProcessInput { process: carpentry },
+                        DataReference::ProcessOutput { process: gathering }] }]);
+                assert_eq!(registry.id_of(DataKind::Good, "Bed"), Some(bed));
+                assert_eq!(*data.get_good(bed).use_wants.values().next().unwrap(), 2.0);
+                assert!(data.try_get_good(wood).is_none());
+                assert!(data.get_good(registry.id_of(DataKind::Good, "Stone").unwrap()).name == "Stone");
+                assert_eq!(data.validate(), Ok(()));
+
+                // re-adding gets the same id back.
+                let report = registry.load_pack(&mut data, DataPack::new(String::from("again"))
+                    .with_good(PackEntry::Add(Good::new(5, String::from("Wood"), String::new()))));
+                assert!(report.is_clean());
+                assert!(data.try_get_good(wood).is_some());
+                assert_eq!(registry.loaded, vec![String::from("base"), String::from("mod"), String::from("again")]);
+            }
+
+            #[test]
+            pub fn report_conflicts_and_reserve_time() {
+                let mut data = Data::new();
+                let mut registry = PackRegistry::new();
+                registry.load_pack(&mut data, base_pack());
+
+                let pack = DataPack::new(String::from("bad"))
+                    .imports(DataKind::Want, 0, String::from("Missing"))
+                    .with_want(PackEntry::Add(Want::new(1, String::from("Rest"))))
+                    .with_want(PackEntry::Override(Want::new(2, String::from("Fun"))))
+                    .with_good(PackEntry::Remove(String::from("Time")))
+                    .with_good(PackEntry::Add(Good::new(TIME_ID, String::from("Clock"), String::new())))
+                    .with_good(PackEntry::Add(Good::new(5, String::from("Gem"), String::new())))
+                    .with_good(PackEntry::Add(Good::new(6, String::from("Gem"), String::new())))
+                    .with_good(PackEntry::Add(Good::new(7, String::from("Toy"), String::new())
+                        .with_ownership(HashMap::from([(8, 1.0)]))));
+                let report = registry.load_pack(&mut data, pack);
+                assert!(report.conflicts.contains(&PackConflict::UnknownImport {
+                    kind: DataKind::Want, name: String::from("Missing") }));
+                assert!(report.conflicts.contains(&PackConflict::AlreadyExists {
+                    kind: DataKind::Want, name: String::from("Rest") }));
+                assert!(report.conflicts.contains(&PackConflict::NotFound {
+                    kind: DataKind::Want, name: String::from("Fun") }));
+                assert!(report.conflicts.contains(&PackConflict::ReservedTime { name: String::from("Time") }));
+                assert!(report.conflicts.contains(&PackConflict::ReservedTime { name: String::from("Clock") }));
+                assert!(report.conflicts.contains(&PackConflict::Duplicate {
+                    kind: DataKind::Good, name: String::from("Gem") }));
+                assert!(report.conflicts.iter().any(|x| matches!(x,
+                    PackConflict::Rejected { kind: DataKind::Good, name, .. } if name == "Toy")));
+                assert_eq!(report.added, vec![(DataKind::Good, String::from("Gem"))]);
+                assert_eq!(data.get_good(TIME_ID).name, "Time");
+                assert_eq!(data.validate(), Ok(()));
+            }
+        }
+    }
+
+    mod world_tests {
+        mod reload_data_should {
+            use std::collections::{HashMap, HashSet};
+
+            use crate::{constants::TIME_ID, culture::Culture, data::{Data, DataReload}, demandcurve::DemandCurve, desire::Desire, drow::DRow, good::Good, item::Item, layer::Layer, market::Market, pop::Pop, species::Species, world::World};
+
+            #[test]
+            pub fn rebuild_affected_pops_and_refresh_markets() {
+                let mut data = Data::new();
+                data.add_time();
+                data.add_good(Good::new(5, String::from("Bread"), String::new()));
+                data.species.insert(1, Species::new(1, String::from("Human"))
+                    .with_desire(Desire::new(Item::Good(5), 1.0, 1.0, DemandCurve::linear(-1.0))));
+                data.species.insert(2, Species::new(2, String::from("Elf")));
+                data.culture.insert(3, Culture::new(3, String::from("Farmer")));
+                data.layers.insert(4, Layer::new(4, String::from("Monks"), String::from("Religion")));
+
+                let mut world = World { markets: HashMap::new(), pops: HashMap::new(), firms: HashMap::new() };
+                world.pops.insert(10, Pop::new(10, 0, 0).add_demo(DRow::new(1.0, 1)));
+                world.pops.insert(11, Pop::new(11, 0, 0).add_demo(DRow::new(1.0, 2).has_culture(3)));
+                world.pops.insert(12, Pop::new(12, 0, 0).add_demo(DRow::new(1.0, 2)));
+                world.pops.insert(13, Pop::new(13, 0, 0).add_demo(DRow::new(1.0, 2).with_layer(4)));
+                world.markets.insert(0, Market {
+                    id: 0,
+                    name: String::from("Town"),
+                    connections: HashMap::new(),
+                    goods_info: HashMap::new(),
+                    monies: HashSet::new(),
+                    good_trade_priority: vec![TIME_ID, 5],
+                    pops: HashSet::from([10, 11, 12, 13]),
+                    jobs: HashSet::new(),
+                    merchants: HashSet::new(),
+                    unclaimed: HashMap::new(),
+                });
+
+                let report = world.reload_data(&mut data, DataReload::new()
+                    .with_culture_desires(3, vec![
+                        Desire::new(Item::Good(5), 2.0, 1.0, DemandCurve::linear(-1.0))]));
+                assert_eq!(report.cultures_changed, vec![3]);
+                assert_eq!(report.pops_rebuilt, vec![11]);
+                assert!(report.markets_refreshed.is_empty());
+                assert_eq!(world.pops[&11].desires.len(), 1);
+                assert!(world.pops[&12].desires.is_empty());
+
+                let report = world.reload_data(&mut data, DataReload::new()
+                    .with_layer_desires(4, vec![
+                        Desire::new(Item::Good(5), 1.0, 1.0, DemandCurve::linear(-1.0))]));
+                assert_eq!(report.layers_changed, vec![4]);
+                assert_eq!(report.pops_rebuilt, vec![13]);
+                assert_eq!(world.pops[&13].desires.len(), 1);
+
+                let report = world.reload_data(&mut data, DataReload::new()
+                    .with_good(Good::new(6, String::from("Cake"), String::new())));
+                assert_eq!(report.goods_added, vec![6]);
+                assert!(report.pops_rebuilt.is_empty());
+                assert_eq!(report.markets_refreshed, vec![0]);
+                assert!(world.markets[&0].good_trade_priority.is_empty());
+            }
+        }
+
+        mod update_positional_desires_should {
+            use std::collections::{HashMap, HashSet};
+
+            use crate::{constants::TIME_ID, data::Data, demandcurve::DemandCurve, desire::{Desire, DesireTag, PositionalMeasure}, drow::DRow, good::Good, household::Household, item::Item, market::Market, pop::{Pop, PropertyRecord}, species::Species, world::World};
+
+            fn test_pop(data: &Data, id: usize, jewelry: f64) -> Pop {
+                // 1 household of 2 people.
+                let mut pop = Pop::new(id, 0, 0).add_demo(DRow { 
+                    household: Household::new(1.0, 2.0, 0.0, 0.0), 
+                    species: 1, 
+                    culture: None,
+                    layers: vec![],
+                });
+                pop.combine_households(data);
+                pop.update_desires_full(data);
+                pop.property.insert(5, PropertyRecord::new(jewelry));
+                pop
+            }
+
+            #[test]
+            pub fn scale_desires_to_other_pops_in_market() {
+                let mut data = Data::new();
+                data.add_time();
+                data.add_good(Good::new(5, String::from("Jewelry"), String::new()));
+                data.add_good(Good::new(6, String::from("Bread"), String::new()));
+                data.species.insert(1, Species::new(1, String::from("Human"))
+                    .with_desire(Desire::new(Item::Good(5), 1.0, 5.0, DemandCurve::linear(-1.0))
+                        .with_steps(2)
+                        .with_tag(DesireTag::positional(PositionalMeasure::Mean)))
+                    .with_desire(Desire::new(Item::Good(6), 1.0, 10.0, DemandCurve::linear(-1.0))));
+
+                let mut world = World { markets: HashMap::new(), pops: HashMap::new(), firms: HashMap::new() };
+                // 1, 3, and 0 jewelry per person.
+                world.pops.insert(10, test_pop(&data, 10, 2.0));
+                world.pops.insert(11, test_pop(&data, 11, 6.0));
+                world.pops.insert(12, test_pop(&data, 12, 0.0));
+                // in another market, so not compared.
+                world.pops.insert(13, test_pop(&data, 13, 100.0));
+                world.markets.insert(0, Market {
+                    id: 0,
+                    name: String::from("Town"),
+                    connections: HashMap::new(),
+                    goods_info: HashMap::new(),
+                    monies: HashSet::new(),
+                    good_trade_priority: vec![TIME_ID, 5, 6],
+                    pops: HashSet::from([10, 11, 12]),
+                    jobs: HashSet::new(),
+                    merchants: HashSet::new(),
+                    unclaimed: HashMap::new(),
+                });
+
+                let amount_of = |world: &World, pop: usize, item: Item| 
+                    world.pops[&pop].desires.iter().find(|x| x.item == item).unwrap().amount;
+                assert_eq!(amount_of(&world, 10, Item::Good(5)), 2.0);
+
+                world.update_positional_desires(&data);
+                // mean of others, per person, times the 2 people.
+                assert_eq!(amount_of(&world, 10, Item::Good(5)), 3.0);
+                assert_eq!(amount_of(&world, 11, Item::Good(5)), 1.0);
+                assert_eq!(amount_of(&world, 12, Item::Good(5)), 4.0);
+                assert_eq!(amount_of(&world, 13, Item::Good(5)), 2.0);
+                assert_eq!(world.pops[&10].positions[&Item::Good(5)], 1.5);
+                // non-positional desires are untouched.
+                assert_eq!(amount_of(&world, 10, Item::Good(6)), 2.0);
+
+                // re-evaluating without changes doesn't compound.
+                world.update_positional_desires(&data);
+                assert_eq!(amount_of(&world, 10, Item::Good(5)), 3.0);
+                // and positions persist through rebuilding desires.
+                world.pops.get_mut(&10).unwrap().update_desires_full(&data);
+                assert_eq!(amount_of(&world, 10, Item::Good(5)), 3.0);
+
+                // no one holding anything floors the reference.
+                for pop in world.pops.values_mut() {
+                    pop.property.clear();
+                }
+                world.update_positional_desires(&data);
+                assert_eq!(amount_of(&world, 10, Item::Good(5)), 0.02);
+            }
+
+            #[test]
+            pub fn measure_percentiles_between_pops() {
+                let holdings = [4.0, 0.0, 2.0];
+                assert_eq!(PositionalMeasure::Percentile(0.0).reference(&holdings), Some(0.0));
+                assert_eq!(PositionalMeasure::Percentile(0.5).reference(&holdings), Some(2.0));
+                assert_eq!(PositionalMeasure::Percentile(0.75).reference(&holdings), Some(3.0));
+                assert_eq!(PositionalMeasure::Percentile(1.0).reference(&holdings), Some(4.0));
+                assert_eq!(PositionalMeasure::Mean.reference(&holdings), Some(2.0));
+                assert_eq!(PositionalMeasure::Mean.reference(&[]), None);
+            }
+        }
+
+        mod settle_inheritance_should {
+            use std::collections::{HashMap, HashSet};
+
+            use crate::{firm::Firm, inheritance::{Heir, Inheritance}, market::Market, pop::{Pop, PropertyRecord}, world::World};
+
+            fn dead_pop(id: usize, heir: Option<Heir>) -> Pop {
+                let mut pop = Pop::new(id, 0, 0);
+                pop.heir = heir;
+                pop.property.insert(5, PropertyRecord::new(10.0));
+                pop
+            }
+
+            #[test]
+            pub fn move_estates_of_dead_pops_to_their_heirs() {
+                let mut world = World { markets: HashMap::new(), pops: HashMap::new(), firms: HashMap::new() };
+                world.markets.insert(0, Market {
+                    id: 0,
+                    name: String::from("Town"),
+                    connections: HashMap::new(),
+                    goods_info: HashMap::new(),
+                    monies: HashSet::new(),
+                    good_trade_priority: vec![],
+                    pops: HashSet::from([10, 11, 12, 13]),
+                    jobs: HashSet::new(),
+                    merchants: HashSet::new(),
+                    unclaimed: HashMap::new(),
+                });
+                world.firms.insert(4, Firm::new(4, String::from("Farm"), 0));
+                world.pops.insert(10, dead_pop(10, None));
+                world.pops.insert(11, dead_pop(11, Some(Heir::Firm(4))));
+                world.pops.insert(12, dead_pop(12, Some(Heir::Firm(99))));
+                world.pops.insert(13, dead_pop(13, None));
+                for id in [10, 11, 12] {
+                    let inheritance = world.pops[&id].inheritance(2.0, 2.0).unwrap();
+                    world.settle_inheritance(&inheritance).unwrap();
+                    assert!(world.pops[&id].property.is_empty());
+                }
+                // the market took 10 and 12, whose heir is gone, the firm 11.
+                assert_eq!(world.markets[&0].unclaimed[&5], 20.0);
+                assert_eq!(world.firms[&4].property[&5], 10.0);
+
+                // survivors keep what they inherit.
+                let mut survivors = Inheritance::new(13, 0, Heir::Survivors);
+                survivors.goods.insert(5, 5.0);
+                world.settle_inheritance(&survivors).unwrap();
+                assert_eq!(world.pops[&13].property[&5].owned, 10.0);
+                // and nothing moves without a market.
+                let lost = Inheritance { pop: 13, market: 7, heir: Heir::Market(7), goods: HashMap::from([(5, 10.0)]) };
+                assert!(world.settle_inheritance(&lost).is_err());
+                assert_eq!(world.pops[&13].property[&5].owned, 10.0);
+
+                // records which still have a target are kept.
+                let mut planner = dead_pop(14, None);
+                planner.property.get_mut(&5).unwrap().target = 4.0;
+                world.pops.insert(14, planner);
+                let inheritance = world.pops[&14].inheritance(2.0, 2.0).unwrap();
+                world.settle_inheritance(&inheritance).unwrap();
+                assert_eq!(world.pops[&14].property[&5].owned, 0.0);
+                assert_eq!(world.pops[&14].property[&5].target, 4.0);
+            }
+        }
+
+        mod day_end_should {
+            use std::collections::HashMap;
+
+            use crate::{tests::pop_tests::inheritance_should::setup, world::World};
+
+            #[test]
+            pub fn end_the_day_of_pops_and_record_failures() {
+                let mut world = World { markets: HashMap::new(), pops: HashMap::new(), firms: HashMap::new() };
+                let (data, history, mut pop) = setup(1.0);
+                let desire = pop.desires.pop_front().unwrap();
+                pop.working_desires.push_back(desire);
+                world.pops.insert(0, pop);
+                // has no history to work with.
+                let (_, _, mut unseen) = setup(1.0);
+                unseen.id = 3;
+                unseen.market = 8;
+                let desire = unseen.desires.pop_front().unwrap();
+                unseen.working_desires.push_back(desire);
+                world.pops.insert(3, unseen);
+                let histories = HashMap::from([(2, history)]);
+
+                let failed = world.day_end(&data, &histories);
+                assert_eq!(failed.len(), 1);
+                assert_eq!(failed[0].0, 3);
+                assert!(world.pops[&0].working_desires.is_empty());
+                assert_eq!(world.pops[&3].working_desires.len(), 1);
+                // no one dies until growth is applied.
+                assert_eq!(world.pops[&0].households.count, 10.0);
+            }
+        }
+
+        mod apply_growth_should {
+            use std::collections::{HashMap, HashSet};
+
+            use crate::{market::Market, tests::pop_tests::inheritance_should::setup, world::World};
+
+            #[test]
+            pub fn settle_the_inheritance_of_the_dead_and_record_failures() {
+                let mut world = World { markets: HashMap::new(), pops: HashMap::new(), firms: HashMap::new() };
+                world.markets.insert(2, Market {
+                    id: 2,
+                    name: String::from("Town"),
+                    connections: HashMap::new(),
+                    goods_info: HashMap::new(),
+                    monies: HashSet::new(),
+                    good_trade_priority: vec![],
+                    pops: HashSet::from([0, 1]),
+                    jobs: HashSet::new(),
+                    merchants: HashSet::new(),
+                    unclaimed: HashMap::new(),
+                });
+                // dies out from thirst, leaving it all to the market.
+                let (data, _, pop) = setup(1.0);
+                // dies out in a market which doesn't exist.
+                let mut lost = pop.clone();
+                lost.id = 1;
+                lost.market = 7;
+                world.pops.insert(0, pop);
+                world.pops.insert(1, lost);
+
+                let (reports, failed) = world.apply_growth(&data);
+                assert_eq!(reports.len(), 2);
+                assert!(reports[&0].inheritance.is_some());
+                assert_eq!(world.markets[&2].unclaimed[&5], 30.0);
+                assert_eq!(world.markets[&2].unclaimed[&6], 6.0);
+                assert!(!world.pops[&0].property.contains_key(&5));
+                // the other keeps it's property.
+                assert_eq!(failed.len(), 1);
+                assert_eq!(failed[0].0, 1);
+                assert_eq!(world.pops[&1].property[&5].owned, 30.0);
+            }
+        }
+
+        mod observe_trade_should {
+            use std::collections::{HashMap, HashSet};
+
+            use crate::{market::Market, pop::Pop, tests::fixtures::flat_history, world::World};
+
+            #[test]
+            pub fn move_the_expectations_of_onlookers_in_the_market() {
+                let mut world = World { markets: HashMap::new(), pops: HashMap::new(), firms: HashMap::new() };
+                world.markets.insert(0, Market {
+                    id: 0,
+                    name: String::from("Town"),
+                    connections: HashMap::new(),
+                    goods_info: HashMap::new(),
+                    monies: HashSet::new(),
+                    good_trade_priority: vec![],
+                    pops: HashSet::from([1, 2]),
+                    jobs: HashSet::new(),
+                    merchants: HashSet::new(),
+                    unclaimed: HashMap::new(),
+                });
+                for id in [1, 2, 3] {
+                    world.pops.insert(id, Pop::new(id, 0, 0));
+                }
+                let history = flat_history(&[5, 6]);
+
+                let request = HashMap::from([(5, 2.0)]);
+                let offer = HashMap::from([(6, 4.0)]);
+                world.observe_trade(0, &[2], &request, &offer, &history);
+                assert_eq!(world.pops[&1].expected_price(5, &history), 1.25);
+                assert_eq!(world.pops[&1].expected_price(6, &history), 0.875);
+                // the trader and pops in other markets don't see it here.
+                assert!(world.pops[&2].prices.prices.is_empty());
+                assert!(world.pops[&3].prices.prices.is_empty());
+            }
+        }
+    }
+
+    mod household_tests {
+        mod age_cohorts_should {
+            use crate::household::{CohortAging, Household};
+
+            #[test]
+            pub fn kill_elders_first_then_age_members_up() {
+                // 20 children, 20 adults, 10 elders.
+                let household = Household::new(10.0, 2.0, 2.0, 1.0);
+                let aging = CohortAging::new(0.1, 0.05);
+                let result = household.age_cohorts(&aging, 5.0, 10.0);
+                // all elders die, 25 children, 2.5 mature, 1.0 of the adults age.
+                assert!((result.total_children() - 22.5).abs() < 0.000001);
+                assert!((result.total_adults() - 21.5).abs() < 0.000001);
+                assert!((result.total_elders() - 1.0).abs() < 0.000001);
+                assert!((result.population() - 45.0).abs() < 0.000001);
+                // households form around adults.
+                assert_eq!(result.adults, 2.0);
+                assert!((result.count - 10.75).abs() < 0.000001);
+                // and labor follows the new makeup.
+                assert!((result.labor() - (21.5 + 22.5 * 0.3 + 1.0 * 0.5) * 24.0).abs() < 0.000001);
+            }
+
+            #[test]
+            pub fn spread_remaining_deaths_over_the_young() {
+                let household = Household::new(10.0, 2.0, 2.0, 1.0);
+                let aging = CohortAging::new(0.0, 0.0);
+                let result = household.age_cohorts(&aging, 0.0, 14.0);
+                // 10 elders, then 4 of the 40 young.
+                assert_eq!(result.total_elders(), 0.0);
+                assert!((result.total_children() - 18.0).abs() < 0.000001);
+                assert!((result.total_adults() - 18.0).abs() < 0.000001);
+                assert!((result.count - 9.0).abs() < 0.000001);
+            }
+
+            #[test]
+            pub fn empty_households_with_no_adults() {
+                let household = Household::new(10.0, 0.0, 2.0, 0.0);
+                let result = household.age_cohorts(&CohortAging::new(0.5, 0.0), 0.0, 0.0);
+                assert_eq!(result.count, 0.0);
+            }
+        }
+
+        mod birth_multiplier_should {
+            use crate::household::CohortAging;
+
+            #[test]
+            pub fn boom_when_satisfied_and_bust_when_not() {
+                let aging = CohortAging::new(0.1, 0.1).with_birth_sensitivity(0.5);
+                assert_eq!(aging.birth_multiplier(0.0), 1.5);
+                assert_eq!(aging.birth_multiplier(0.5), 1.0);
+                assert_eq!(aging.birth_multiplier(1.0), 0.5);
+                let harsh = CohortAging::new(0.1, 0.1).with_birth_sensitivity(2.0);
+                assert_eq!(harsh.birth_multiplier(1.0), 0.0);
+            }
+        }
+
+        mod effective_mortality_should {
+            use crate::{constants::{DAYS_PER_TURN, POP_GROWTH_PERIOD}, household::Household};
+
+            #[test]
+            pub fn scale_life_need_mortality_to_the_growth_period() {
+                let household = Household::new(1.0, 2.0, 0.0, 1.0);
+                // the elder's 0.1 spread over 3 people.
+                assert!((household.mortality() - 0.1 / 3.0).abs() < 0.000001);
+                assert_eq!(household.effective_mortality(0.0), household.mortality());
+                assert!((household.effective_mortality(0.0001) - 
+                    (household.mortality() + 0.0001 * POP_GROWTH_PERIOD)).abs() < 0.000001);
+                assert_eq!(household.effective_mortality(2.0), POP_GROWTH_PERIOD / DAYS_PER_TURN);
+                assert_eq!(Household::zeroed_household().mortality(), 0.0);
+            }
+        }
+    }
+
+    mod price_expectations_tests {
+        mod starting_price_should {
+            use crate::{constants::PRICE_EXPECTATION_NOISE, markethistory::{GoodRecord, MarketHistory}, priceexpectations::PriceExpectations};
+
+            #[test]
+            pub fn stay_within_noise_of_market_price_and_repeat() {
+                let mut market = MarketHistory::new();
+                for good in 0..20 {
+                    market.good_records.insert(good, GoodRecord::new().with_price(10.0));
+                }
+                let prices = PriceExpectations::new(3).with_noise(PRICE_EXPECTATION_NOISE);
+                let mut differs = false;
+                for good in 0..20 {
+                    let price = prices.starting_price(good, &market);
+                    assert!((9.5..=10.5).contains(&price));
+                    assert_eq!(price, PriceExpectations::new(3).with_noise(PRICE_EXPECTATION_NOISE).starting_price(good, &market));
+                    if price != PriceExpectations::new(4).with_noise(PRICE_EXPECTATION_NOISE).starting_price(good, &market) {
+                        differs = true;
+                    }
+                }
+                assert!(differs);
+                // by default, a price taker.
+                let taker = PriceExpectations::new(3);
+                assert_eq!(taker.starting_price(5, &market), 10.0);
+                assert_eq!(taker.expected(5, &market), 10.0);
+                // goods without a record start at a new record's price.
+                assert_eq!(prices.expected(25, &market), 0.0);
+            }
+        }
+
+        mod observe_should {
+            use crate::{markethistory::{GoodRecord, MarketHistory}, priceexpectations::{ExpectationRule, PriceExpectations}};
+
+            #[test]
+            pub fn move_adaptive_expectations_towards_prices_seen() {
+                let mut market = MarketHistory::new();
+                market.good_records.insert(0, GoodRecord::new().with_price(10.0));
+                let mut prices = PriceExpectations::new(0)
+                    .with_rule(ExpectationRule::Adaptive { rate: 0.5 });
+                prices.observe(0, 20.0, &market);
+                assert_eq!(prices.expected(0, &market), 15.0);
+                prices.observe(0, 20.0, &market);
+                assert_eq!(prices.expected(0, &market), 17.5);
+                prices.observe(0, -100.0, &market);
+                assert_eq!(prices.expected(0, &market), 0.0);
+            }
+
+            #[test]
+            pub fn extrapolate_trends_when_trend_following() {
+                let mut market = MarketHistory::new();
+                market.good_records.insert(0, GoodRecord::new().with_price(10.0));
+                let mut prices = PriceExpectations::new(0)
+                    .with_rule(ExpectationRule::TrendFollowing { rate: 0.5, weight: 1.0 });
+                prices.observe(0, 20.0, &market);
+                // trend 5.0, 10 + 5 + 5
+                assert_eq!(*prices.trends.get(&0).unwrap(), 5.0);
+                assert_eq!(prices.expected(0, &market), 20.0);
+                prices.observe(0, 20.0, &market);
+                // seen no change, trend decays to 2.5
+                assert_eq!(*prices.trends.get(&0).unwrap(), 2.5);
+                assert_eq!(prices.expected(0, &market), 22.5);
+            }
+        }
+    }
+
+    mod pop_tests {
+        mod observe_trade_should {
+            use std::collections::HashMap;
+
+            use crate::{priceexpectations::{ExpectationRule, PriceExpectations}, tests::fixtures::{flat_history, owning_pop}};
+
+            #[test]
+            pub fn raise_what_was_bought_dear_and_lower_what_was_paid() {
+                let market = flat_history(&[0, 1]);
+                let mut test_pop = owning_pop(&[]);
+                test_pop.prices = PriceExpectations::new(0)
+                    .with_rule(ExpectationRule::Adaptive { rate: 0.5 });
+                // 2 units of 1 were given for 1 unit of 0, so 0 is worth more 
+                // than expected and 1 less.
+                let request = HashMap::from([(0, 1.0)]);
+                let offer = HashMap::from([(1, 2.0)]);
+                test_pop.observe_trade(&request, &offer, &market);
+                assert_eq!(test_pop.expected_price(0, &market), 1.5);
+                assert_eq!(test_pop.expected_price(1, &market), 0.75);
+                // the market itself is untouched.
+                assert_eq!(market.get_record(0).price, 1.0);
+                // nothing to learn from a worthless side.
+                let before = test_pop.prices.prices.clone();
+                test_pop.observe_trade(&request, &HashMap::new(), &market);
+                assert_eq!(test_pop.prices.prices, before);
+            }
+        }
+
+        mod complete_trade_should {
+            use std::collections::HashMap;
+
+            use crate::{offerresult::{AcceptReason, OfferResult}, tests::fixtures::{flat_history, goods_data, owning_pop}};
+
+            #[test]
+            pub fn exchange_goods_and_learn_from_an_accepted_offer() {
+                let data = goods_data(&[(5, "Salt"), (6, "Fish")]);
+                let market = flat_history(&[5, 6]);
+                let mut test_pop = owning_pop(&[(5, 10.0)]);
+
+                // 4 fish for 2 salt.
+                let request = HashMap::from([(5, 2.0)]);
+                let offer = HashMap::from([(6, 4.0)]);
+                let result = test_pop.check_offer(&request, &offer, &data, &market);
+                assert_eq!(result, OfferResult::Accept(AcceptReason::AMV));
+                test_pop.complete_trade(&request, &offer, &market);
+
+                assert_eq!(test_pop.property[&5].owned, 8.0);
+                assert_eq!(test_pop.property[&5].traded, 2.0);
+                assert_eq!(test_pop.property[&6].owned, 4.0);
+                // salt sold for twice what we expected, fish for half.
+                assert_eq!(test_pop.expected_price(5, &market), 1.25);
+                assert_eq!(test_pop.expected_price(6, &market), 0.875);
+            }
+        }
+
+        mod try_satisfy_until_incomplete_should {
+            use crate::{data::Data, demandcurve::DemandCurve, desire::Desire, good::{Good, GoodTags}, item::Item, markethistory::{GoodRecord, MarketHistory}, pop::{Pop, PropertyRecord}};
+
+            #[test]
+            pub fn correctly_stop_when_incomplete_found() {
+                // set up data for goods.
+                let mut data = Data::new();
+                data.goods.insert(2, Good::new(2, "2".to_string(), 
+                    String::from("")).with_tags(vec![GoodTags::Nonexchangeable]));
+                data.goods.insert(3, Good::new(3, "3".to_string(), String::from("")));
+                data.goods.insert(4, Good::new(4, "4".to_string(), String::from("")));
+                data.goods.insert(5, Good::new(5, "5".to_string(), String::from("")));
+                data.goods.insert(6, Good::new(6, "6".to_string(), String::from("")));
+                
+                // set up market info, including a currency.
+                let mut market = MarketHistory::new();
+                market.good_records.insert(2, GoodRecord::new().with_price(1.0));
+                market.good_records.insert(3, GoodRecord::new().with_price(1.0));
+                market.currencies.insert(3);
+                market.good_records.insert(4, GoodRecord::new().with_price(1.0));
+                market.good_records.insert(5, GoodRecord::new().with_price(1.0));
+                market.good_records.insert(6, GoodRecord::new().with_price(1.0));
+                
+                // create a pop and property.
                 let mut test_pop = Pop::new(0, 0, 0);
-                test_pop.property.insert(2, PropertyRecord::new(10.0));
+                test_pop.property.insert(2, PropertyRecord::new(5.0));
                 test_pop.property.insert(3, PropertyRecord::new(10.0));
                 test_pop.property.insert(4, PropertyRecord::new(10.0));
                 test_pop.property.insert(5, PropertyRecord::new(10.0));
-                test_pop.property.get_mut(&5).unwrap().target = 5.0;
                 test_pop.property.insert(6, PropertyRecord::new(10.0));
-                test_pop.property.get_mut(&6).unwrap().target = 10.0;
+                // initial AMV = 45.0
+                // add Desires to satisfy.
+                test_pop.desires.push_back(Desire::new(Item::Good(2), 1.0, 1.0, DemandCurve::Linear { slope: 1.0 })
+                    .with_steps(0));
+                test_pop.desires.push_back(Desire::new(Item::Good(3), 1.0, 1.0, DemandCurve::Linear { slope: 1.0 })
+                    .with_steps(0));
+                test_pop.desires.push_back(Desire::new(Item::Good(4), 1.0, 1.0, DemandCurve::Linear { slope: 1.0 })
+                    .with_steps(0));
+                test_pop.desires.push_back(Desire::new(Item::Good(5), 1.0, 1.0, DemandCurve::Linear { slope: 1.0 })
+                    .with_steps(0));
+                test_pop.desires.push_back(Desire::new(Item::Good(6), 1.0, 1.0, DemandCurve::Linear { slope: 1.0 })
+                    .with_steps(0));
+                // should consume 25.0 AMV, 5 steps of each, range from 1.0 to 6.0.
+
+                // run function 
+                test_pop.try_satisfy_until_incomplete(&data, &market);
+
+                // test results
+                assert_eq!(test_pop.satisfaction.excess_amv, 20.0);
+                assert_eq!(test_pop.satisfaction.value, 5.0);
+                assert_eq!(test_pop.satisfaction.satisfaction, 25.0);
+                assert_eq!(test_pop.satisfaction.steps, 25.0);
+
+                assert_eq!(test_pop.property.get(&2).unwrap().reserved, 5.0);
+                assert_eq!(test_pop.property.get(&3).unwrap().reserved, 5.0);
+                assert_eq!(test_pop.property.get(&4).unwrap().reserved, 5.0);
+                assert_eq!(test_pop.property.get(&5).unwrap().reserved, 5.0);
+                assert_eq!(test_pop.property.get(&6).unwrap().reserved, 5.0);
+            }
+
+            #[test]
+            pub fn correctly_stop_when_incomplete_found_using_self_working_desires() {
+                // set up data for goods.
+                let mut data = Data::new();
+                data.goods.insert(2, Good::new(2, "2".to_string(), 
+                    String::from("")).with_tags(vec![GoodTags::Nonexchangeable]));
+                data.goods.insert(3, Good::new(3, "3".to_string(), String::from("")));
+                data.goods.insert(4, Good::new(4, "4".to_string(), String::from("")));
+                data.goods.insert(5, Good::new(5, "5".to_string(), String::from("")));
+                // data.goods.insert(6, Good::new(6, "6".to_string(), String::from("")));
+                
+                // set up market info, including a currency.
+                let mut market = MarketHistory::new();
+                market.good_records.insert(2, GoodRecord::new().with_price(1.0));
+                market.good_records.insert(3, GoodRecord::new().with_price(1.0));
+                market.currencies.insert(3);
+                market.good_records.insert(4, GoodRecord::new().with_price(1.0));
+                market.good_records.insert(5, GoodRecord::new().with_price(1.0));
+                market.good_records.insert(6, GoodRecord::new().with_price(1.0));
+                
+                // create a pop and property.
+                let mut test_pop = Pop::new(0, 0, 0);
+                test_pop.property.insert(2, PropertyRecord::new(5.0));
+                test_pop.property.insert(3, PropertyRecord::new(10.0));
+                test_pop.property.insert(4, PropertyRecord::new(10.0));
+                test_pop.property.insert(5, PropertyRecord::new(10.0));
+                test_pop.property.insert(6, PropertyRecord::new(10.0));
+                // initial AMV = 45.0
+                // add Desires to satisfy.
+                test_pop.working_desires.push_back(Desire::new(Item::Good(2), 1.0, 1.0, DemandCurve::Linear { slope: 1.0 })
+                    .with_steps(0));
+                test_pop.working_desires.push_back(Desire::new(Item::Good(3), 1.0, 1.0, DemandCurve::Linear { slope: 1.0 })
+                    .with_steps(0));
+                test_pop.working_desires.push_back(Desire::new(Item::Good(4), 1.0, 1.0, DemandCurve::Linear { slope: 1.0 })
+                    .with_steps(0));
+                test_pop.working_desires.push_back(Desire::new(Item::Good(5), 1.0, 1.0, DemandCurve::Linear { slope: 1.0 })
+                    .with_steps(0));
+                test_pop.working_desires.push_back(Desire::new(Item::Good(6), 1.0, 1.0, DemandCurve::Linear { slope: 1.0 })
+                    .with_steps(0));
+                // should consume 25.0 AMV, 5 steps of each, range from 1.0 to 6.0.
+
+                // run function 
+                test_pop.try_satisfy_until_incomplete(&data, &market);
+
+                // test results
+                assert_eq!(test_pop.satisfaction.excess_amv, 20.0);
+                assert_eq!(test_pop.satisfaction.value, 5.0);
+                assert_eq!(test_pop.satisfaction.satisfaction, 25.0);
+                assert_eq!(test_pop.satisfaction.steps, 25.0);
+
+                assert_eq!(test_pop.property.get(&2).unwrap().reserved, 5.0);
+                assert_eq!(test_pop.property.get(&3).unwrap().reserved, 5.0);
+                assert_eq!(test_pop.property.get(&4).unwrap().reserved, 5.0);
+                assert_eq!(test_pop.property.get(&5).unwrap().reserved, 5.0);
+                assert_eq!(test_pop.property.get(&6).unwrap().reserved, 5.0);
+            }
+        }
+
+        mod enslave_should {
+            use std::collections::HashMap;
+
+            use crate::{constants::TIME_ID, data::Data, demandcurve::DemandCurve, desire::Desire, firm::{Firm, WorkerInfo, WorkerType}, item::Item, markethistory::MarketHistory, offerresult::{OfferResult, RejectReason}, pop::{Pop, PopTag}, tests::fixtures::{flat_history, goods_data, owning_pop}};
+
+            pub fn setup() -> (Data, MarketHistory, Pop, Firm) {
+                let data = goods_data(&[(5, "Bread"), (6, "Silver")]);
+                let market = flat_history(&[TIME_ID, 5, 6]);
+                let mut test_pop = owning_pop(&[(TIME_ID, 5.0), (5, 10.0)]);
+                test_pop.id = 1;
+                test_pop.firm = 4;
+                test_pop.property.get_mut(&5).unwrap().saved = 2.0;
+                let mut firm = Firm::new(4, String::from("Mine"), 0);
+                let mut info = WorkerInfo::new(WorkerType::Worker);
+                info.wage.insert(6, 1.0);
+                firm.workers.insert(1, info);
+                (data, market, test_pop, firm)
+            }
+
+            #[test]
+            pub fn make_the_pop_a_slave_worker_of_the_firm() {
+                let (_, _, mut test_pop, mut firm) = setup();
+                assert!(test_pop.enslave(&mut firm, None).is_ok());
+                assert!(test_pop.tags.contains(&PopTag::Slave));
+                assert!(test_pop.is_slave());
+                assert_eq!(test_pop.firm, 4);
+                assert_eq!(firm.workers[&1].worker_type, WorkerType::Slave);
+                assert!(firm.workers[&1].wage.is_empty());
+                // not twice
+                assert!(test_pop.enslave(&mut firm, None).is_err());
+                // new workers get added.
+                let mut other = Pop::new(3, 0, 0);
+                other.enslave(&mut firm, None).unwrap();
+                assert_eq!(firm.workers[&3].worker_type, WorkerType::Slave);
+            }
+
+            #[test]
+            pub fn stop_the_pop_from_trading() {
+                let (data, market, mut test_pop, mut firm) = setup();
+                assert_eq!(test_pop.create_sell_orders(&data, &market)[&5], 10.0);
+                test_pop.enslave(&mut firm, None).unwrap();
+
+                assert!(test_pop.create_sell_orders(&data, &market).is_empty());
+                let request = HashMap::from([(5, 1.0)]);
+                assert!(test_pop.make_offer(&HashMap::from([(6, 1.0)]), &data, &market, &HashMap::new()).is_empty());
+                let offer = HashMap::from([(6, 100.0)]);
+                assert_eq!(test_pop.check_offer(&request, &offer, &data, &market), 
+                    OfferResult::Reject(RejectReason::Enslaved));
+            }
+
+            #[test]
+            pub fn hand_excess_to_the_owner_at_day_end() {
+                let (data, market, mut test_pop, mut firm) = setup();
+                // free pops keep everything.
+                test_pop.day_end(&data, Some(&mut firm), &market);
+                assert_eq!(test_pop.property[&5].owned, 10.0);
+                assert!(firm.property.is_empty());
+
+                test_pop.enslave(&mut firm, None).unwrap();
+                test_pop.day_end(&data, Some(&mut firm), &market);
+                // keeps what it saved and it's time.
+                assert_eq!(test_pop.property[&5].owned, 2.0);
+                assert_eq!(firm.property[&5], 8.0);
+                assert!(!firm.property.contains_key(&TIME_ID));
+            }
+
+            #[test]
+            pub fn keep_what_its_desires_need_when_handing_over() {
+                let (data, market, mut test_pop, mut firm) = setup();
+                test_pop.desires.push_back(Desire::new(Item::Good(5), 1.0, 1.0, DemandCurve::linear(-0.1))
+                    .with_steps(3));
+                test_pop.property.get_mut(&5).unwrap().reserved = 3.0;
+                test_pop.enslave(&mut firm, None).unwrap();
+                test_pop.day_end(&data, Some(&mut firm), &market);
+                // eats 3, keeps 3 for tomorrow and the 2 saved.
+                assert_eq!(test_pop.property[&5].owned, 5.0);
+                assert_eq!(test_pop.property[&5].reserved, 0.0);
+                assert_eq!(firm.property[&5], 2.0);
+            }
+
+            #[test]
+            pub fn hand_nothing_over_without_an_owner() {
+                let (data, market, mut test_pop, mut firm) = setup();
+                test_pop.enslave(&mut firm, None).unwrap();
+                test_pop.day_end(&data, None, &market);
+                assert_eq!(test_pop.property[&5].owned, 10.0);
+                assert!(firm.property.is_empty());
+            }
+
+            #[test]
+            pub fn remove_the_pop_from_its_old_firm() {
+                let (_, _, mut test_pop, mut firm) = setup();
+                let mut farm = Firm::new(5, String::from("Farm"), 0);
+                farm.workers.insert(1, WorkerInfo::new(WorkerType::Worker));
+                // not the pop's firm.
+                assert!(test_pop.enslave(&mut farm, Some(&mut Firm::new(6, String::new(), 0))).is_err());
+                assert!(!test_pop.is_slave());
+
+                assert!(test_pop.enslave(&mut farm, Some(&mut firm)).is_ok());
+                assert!(!firm.workers.contains_key(&1));
+                assert_eq!(farm.workers[&1].worker_type, WorkerType::Slave);
+                assert_eq!(test_pop.firm, 5);
+            }
+        }
+
+        mod manumit_should {
+            use crate::{firm::{Firm, WorkerType}, pop::{Pop, PopTag}};
+
+            use super::enslave_should::setup;
+
+            #[test]
+            pub fn free_the_pop_to_work_for_the_firm() {
+                let (_, _, mut test_pop, mut firm) = setup();
+                assert!(test_pop.manumit(&mut firm).is_err());
+                test_pop.enslave(&mut firm, None).unwrap();
+                let mut other = Firm::new(5, String::from("Farm"), 0);
+                assert!(test_pop.manumit(&mut other).is_err());
+                assert!(test_pop.is_slave());
+
+                assert!(test_pop.manumit(&mut firm).is_ok());
+                assert!(!test_pop.tags.contains(&PopTag::Slave));
+                assert_eq!(test_pop.firm, 4);
+                assert_eq!(firm.workers[&1].worker_type, WorkerType::Worker);
+                let mut free = Pop::new(3, 0, 4);
+                assert!(free.manumit(&mut firm).is_err());
+            }
+        }
+
+        mod create_sell_orders_should {
+            use crate::{data::Data, good::{Good, GoodTags}, markethistory::{GoodRecord, MarketHistory}, pop::{Pop, PropertyRecord}};
+
+            #[test]
+            pub fn correctly_return_available_goods_for_sale() {
+                let mut data = Data::new();
+                data.goods.insert(2, Good::new(2, "2".to_string(), 
+                    String::from("")).with_tags(vec![GoodTags::Nonexchangeable]));
+                data.goods.insert(3, Good::new(3, "3".to_string(), String::from("")));
+                data.goods.insert(4, Good::new(4, "4".to_string(), String::from("")));
+                data.goods.insert(5, Good::new(5, "5".to_string(), String::from("")));
+                data.goods.insert(6, Good::new(6, "6".to_string(), String::from("")));
+                
+                let mut market = MarketHistory::new();
+                market.good_records.insert(2, GoodRecord::new().with_price(1.0));
+                market.good_records.insert(3, GoodRecord::new().with_price(1.0));
+                market.currencies.insert(3);
+                market.good_records.insert(4, GoodRecord::new().with_price(1.0));
+                market.good_records.insert(5, GoodRecord::new().with_price(1.0));
+                market.good_records.insert(6, GoodRecord::new().with_price(1.0));
+                
+                let mut test_pop = Pop::new(0, 0, 0);
+                test_pop.property.insert(2, PropertyRecord::new(10.0));
+                test_pop.property.insert(3, PropertyRecord::new(10.0));
+                test_pop.property.insert(4, PropertyRecord::new(10.0));
+                test_pop.property.insert(5, PropertyRecord::new(10.0));
+                test_pop.property.get_mut(&5).unwrap().target = 5.0;
+                test_pop.property.insert(6, PropertyRecord::new(10.0));
+                test_pop.property.get_mut(&6).unwrap().target = 10.0;
 
                 let result = test_pop.create_sell_orders(&data, &market);
                 assert!(!result.contains_key(&2));
@@ -1235,259 +2886,1415 @@ mod tests {
             }
         }
 
-        mod check_offer_should {
-            use std::collections::HashMap;
+        mod check_offer_should {
+            use std::collections::HashMap;
+
+            use crate::{data::Data, demandcurve::DemandCurve, desire::Desire, good::Good, item::Item, markethistory::{GoodRecord, MarketHistory}, offerresult::{AcceptReason, OfferResult, RejectReason}, pop::{Pop, PropertyRecord}};
+
+            #[test]
+            pub fn reject_offer_due_to_hard_threshold() {
+                // Set up Data
+                let mut data = Data::new();
+                data.goods.insert(2, Good::new(2, "2".to_string(), String::from("")));
+                data.goods.insert(3, Good::new(3, "3".to_string(), String::from("")));
+                data.goods.insert(4, Good::new(4, "4".to_string(), String::from("")));
+                data.goods.insert(5, Good::new(5, "5".to_string(), String::from("")));
+                data.goods.insert(6, Good::new(6, "6".to_string(), String::from("")));
+                data.goods.insert(7, Good::new(7, "7".to_string(), String::from("")));
+                // Set up good prices.
+                let mut market = MarketHistory::new();
+                market.good_records.insert(2, GoodRecord::new().with_price(1.0));
+                market.good_records.insert(3, GoodRecord::new().with_price(1.0));
+                market.good_records.insert(4, GoodRecord::new().with_price(1.0));
+                market.good_records.insert(5, GoodRecord::new().with_price(1.0));
+                market.good_records.insert(6, GoodRecord::new().with_price(1.0));
+                market.good_records.insert(7, GoodRecord::new().with_price(1.0));
+
+                // Set up desires.
+                // Good 3,  2x, 1-3(5)
+                // Good 4,  2x, 2-6(10)
+                // Good 7, inf, 0 -> 20n
+                let mut test_pop = Pop::new(0, 0, 0);
+                test_pop.desires.push_back(Desire::new(Item::Good(3), 1.0, 1.0, 
+                    DemandCurve::Linear { slope: 2.0 })
+                    .with_steps(2));
+                test_pop.desires.push_back(Desire::new(Item::Good(4), 1.0, 2.0, 
+                    DemandCurve::Linear { slope: 4.0 })
+                    .with_steps(2));
+                test_pop.desires.push_back(Desire::new(Item::Good(7), 1.0, 0.0, 
+                    DemandCurve::Linear { slope: 20.0 })
+                    .with_steps(0));
+                // Add in enough property for our needs.
+                test_pop.property.insert(2, PropertyRecord::new(2.0));
+                test_pop.property.insert(5, PropertyRecord::new(2.0));
+                test_pop.property.insert(6, PropertyRecord::new(2.0));
+                test_pop.try_satisfy_all_desires(&data, &market);
+
+                let mut request = HashMap::new();
+                request.insert(2, 5.0);
+
+                let mut offer = HashMap::new();
+                offer.insert(3, 1.0);
+
+                let result = test_pop.check_offer(&request, &offer, &data, &market);
+                assert_eq!(result, OfferResult::Reject(RejectReason::HardThresholdFailure));
+            }
+
+            #[test]
+            pub fn reject_offer_for_no_positive_benefit_amv_neutral() {
+                // Set up Data
+                let mut data = Data::new();
+                data.goods.insert(2, Good::new(2, "2".to_string(), String::from("")));
+                data.goods.insert(3, Good::new(3, "3".to_string(), String::from("")));
+                data.goods.insert(4, Good::new(4, "4".to_string(), String::from("")));
+                data.goods.insert(5, Good::new(5, "5".to_string(), String::from("")));
+                data.goods.insert(6, Good::new(6, "6".to_string(), String::from("")));
+                data.goods.insert(7, Good::new(7, "7".to_string(), String::from("")));
+                // Set up good prices.
+                let mut market = MarketHistory::new();
+                market.good_records.insert(2, GoodRecord::new().with_price(1.0));
+                market.good_records.insert(3, GoodRecord::new().with_price(1.0));
+                market.good_records.insert(4, GoodRecord::new().with_price(1.0));
+                market.good_records.insert(5, GoodRecord::new().with_price(1.0));
+                market.good_records.insert(6, GoodRecord::new().with_price(1.0));
+                market.good_records.insert(7, GoodRecord::new().with_price(1.0));
+
+                // Set up desires.
+                // Good 3,  2x, 1-3(5)
+                // Good 4,  2x, 2-6(10)
+                // Good 7, inf, 0 -> 20n
+                let mut test_pop = Pop::new(0, 0, 0);
+                test_pop.desires.push_back(Desire::new(Item::Good(3), 1.0, 1.0, 
+                    DemandCurve::Linear { slope: 2.0 })
+                    .with_steps(2));
+                test_pop.desires.push_back(Desire::new(Item::Good(4), 1.0, 2.0, 
+                    DemandCurve::Linear { slope: 4.0 })
+                    .with_steps(2));
+                test_pop.desires.push_back(Desire::new(Item::Good(7), 1.0, 0.0, 
+                    DemandCurve::Linear { slope: 20.0 })
+                    .with_steps(0));
+                // Add in enough property for our needs.
+                test_pop.property.insert(2, PropertyRecord::new(2.0));
+                test_pop.property.insert(5, PropertyRecord::new(2.0));
+                test_pop.property.insert(6, PropertyRecord::new(2.0));
+                test_pop.try_satisfy_all_desires(&data, &market);
+
+                let mut request = HashMap::new();
+                request.insert(2, 1.0);
+
+                let mut offer = HashMap::new();
+                offer.insert(5, 1.0);
+
+                let result = test_pop.check_offer(&request, &offer, &data, &market);
+                assert_eq!(result, OfferResult::Reject(RejectReason::NotAccepted));
+            }
+
+            #[test]
+            pub fn accept_offer_because_of_satisfaction_gain() {
+                // Set up Data
+                let mut data = Data::new();
+                data.goods.insert(2, Good::new(2, "2".to_string(), String::from("")));
+                data.goods.insert(3, Good::new(3, "3".to_string(), String::from("")));
+                data.goods.insert(4, Good::new(4, "4".to_string(), String::from("")));
+                data.goods.insert(5, Good::new(5, "5".to_string(), String::from("")));
+                data.goods.insert(6, Good::new(6, "6".to_string(), String::from("")));
+                data.goods.insert(7, Good::new(7, "7".to_string(), String::from("")));
+                // Set up good prices.
+                let mut market = MarketHistory::new();
+                market.good_records.insert(2, GoodRecord::new().with_price(1.0));
+                market.good_records.insert(3, GoodRecord::new().with_price(1.0));
+                market.good_records.insert(4, GoodRecord::new().with_price(1.0));
+                market.good_records.insert(5, GoodRecord::new().with_price(1.0));
+                market.good_records.insert(6, GoodRecord::new().with_price(1.0));
+                market.good_records.insert(7, GoodRecord::new().with_price(1.0));
+
+                // Set up desires.
+                // Good 3,  2x, 1-3(5)
+                // Good 4,  2x, 2-6(10)
+                // Good 7, inf, 0 -> 20n
+                let mut test_pop = Pop::new(0, 0, 0);
+                test_pop.desires.push_back(Desire::new(Item::Good(3), 1.0, 1.0, 
+                    DemandCurve::Linear { slope: 2.0 })
+                    .with_steps(2));
+                test_pop.desires.push_back(Desire::new(Item::Good(4), 1.0, 2.0, 
+                    DemandCurve::Linear { slope: 4.0 })
+                    .with_steps(2));
+                test_pop.desires.push_back(Desire::new(Item::Good(7), 1.0, 0.0, 
+                    DemandCurve::Linear { slope: 20.0 })
+                    .with_steps(0));
+                // Add in enough property for our needs.
+                test_pop.property.insert(2, PropertyRecord::new(2.0));
+                test_pop.property.insert(5, PropertyRecord::new(2.0));
+                test_pop.property.insert(6, PropertyRecord::new(2.0));
+                test_pop.try_satisfy_all_desires(&data, &market);
+
+                let mut request = HashMap::new();
+                request.insert(2, 2.0);
+
+                let mut offer = HashMap::new();
+                offer.insert(4, 2.0);
+
+                let result = test_pop.check_offer(&request, &offer, &data, &market);
+                assert_eq!(result, OfferResult::Accept(AcceptReason::Satisfaction));
+            }
+
+            #[test]
+            pub fn accept_offer_because_of_density_gain() {
+                // Set up Data
+                let mut data = Data::new();
+                data.goods.insert(2, Good::new(2, "2".to_string(), String::from("")));
+                data.goods.insert(3, Good::new(3, "3".to_string(), String::from("")));
+                data.goods.insert(4, Good::new(4, "4".to_string(), String::from("")));
+                data.goods.insert(5, Good::new(5, "5".to_string(), String::from("")));
+                data.goods.insert(6, Good::new(6, "6".to_string(), String::from("")));
+                data.goods.insert(7, Good::new(7, "7".to_string(), String::from("")));
+                // Set up good prices.
+                let mut market = MarketHistory::new();
+                market.good_records.insert(2, GoodRecord::new().with_price(1.0));
+                market.good_records.insert(3, GoodRecord::new().with_price(1.0));
+                market.good_records.insert(4, GoodRecord::new().with_price(1.0));
+                market.good_records.insert(5, GoodRecord::new().with_price(1.0));
+                market.good_records.insert(6, GoodRecord::new().with_price(1.0));
+                market.good_records.insert(7, GoodRecord::new().with_price(1.0));
+
+                // Set up desires.
+                // Good 3,  2x, 1-3(5)
+                // Good 4,  2x, 2-6(10)
+                // Good 7, inf, 0 -> 20n
+                let mut test_pop = Pop::new(0, 0, 0);
+                test_pop.desires.push_back(Desire::new(Item::Good(3), 1.0, 1.0, 
+                    DemandCurve::Linear { slope: 2.0 })
+                    .with_steps(2));
+                test_pop.desires.push_back(Desire::new(Item::Good(4), 1.0, 2.0, 
+                    DemandCurve::Linear { slope: 4.0 })
+                    .with_steps(2));
+                test_pop.desires.push_back(Desire::new(Item::Good(7), 1.0, 0.0, 
+                    DemandCurve::Linear { slope: 20.0 })
+                    .with_steps(0));
+                // Add in enough property for our needs.
+                test_pop.property.insert(3, PropertyRecord::new(1.0));
+                test_pop.property.insert(4, PropertyRecord::new(1.0));
+                test_pop.property.insert(7, PropertyRecord::new(3.0));
+                test_pop.try_satisfy_all_desires(&data, &market);
+
+                let mut request = HashMap::new();
+                request.insert(7, 3.0);
+
+                let mut offer = HashMap::new();
+                offer.insert(3, 1.0);
+
+                let result = test_pop.check_offer(&request, &offer, &data, &market);
+                // finishing Good 3's desire outweighs 3 of Good 7's endless 
+                // steps in normalized satisfaction.
+                assert_eq!(result, OfferResult::Accept(AcceptReason::Satisfaction));
+            }
+
+            #[test]
+            pub fn accept_offer_because_of_amv_gain() {
+                // Set up Data
+                let mut data = Data::new();
+                data.goods.insert(2, Good::new(2, "2".to_string(), String::from("")));
+                data.goods.insert(3, Good::new(3, "3".to_string(), String::from("")));
+                data.goods.insert(4, Good::new(4, "4".to_string(), String::from("")));
+                data.goods.insert(5, Good::new(5, "5".to_string(), String::from("")));
+                data.goods.insert(6, Good::new(6, "6".to_string(), String::from("")));
+                data.goods.insert(7, Good::new(7, "7".to_string(), String::from("")));
+                // Set up good prices.
+                let mut market = MarketHistory::new();
+                market.good_records.insert(2, GoodRecord::new().with_price(1.0));
+                market.good_records.insert(3, GoodRecord::new().with_price(1.0));
+                market.good_records.insert(4, GoodRecord::new().with_price(1.0));
+                market.good_records.insert(5, GoodRecord::new().with_price(1.0));
+                market.good_records.insert(6, GoodRecord::new().with_price(1.0));
+                market.good_records.insert(7, GoodRecord::new().with_price(1.0));
+
+                // Set up desires.
+                // Good 3,  2x, 1-3(5)
+                // Good 4,  2x, 2-6(10)
+                // Good 7, inf, 0 -> 20n
+                let mut test_pop = Pop::new(0, 0, 0);
+                test_pop.desires.push_back(Desire::new(Item::Good(3), 1.0, 1.0, 
+                    DemandCurve::Linear { slope: 2.0 })
+                    .with_steps(2));
+                test_pop.desires.push_back(Desire::new(Item::Good(4), 1.0, 2.0, 
+                    DemandCurve::Linear { slope: 4.0 })
+                    .with_steps(2));
+                test_pop.desires.push_back(Desire::new(Item::Good(7), 1.0, 0.0, 
+                    DemandCurve::Linear { slope: 20.0 })
+                    .with_steps(0));
+                // Add in enough property for our needs.
+                test_pop.property.insert(5, PropertyRecord::new(1.0));
+                test_pop.property.insert(6, PropertyRecord::new(1.0));
+                test_pop.property.insert(7, PropertyRecord::new(3.0));
+                test_pop.try_satisfy_all_desires(&data, &market);
+                println!("Base Satisfaction.");
+                println!("Range: {}", test_pop.satisfaction.value);
+                println!("Steps: {}", test_pop.satisfaction.steps);
+                println!("Satisfaction: {}", test_pop.satisfaction.satisfaction);
+                println!("AMV: {}", test_pop.satisfaction.excess_amv);
+
+                let mut request = HashMap::new();
+                request.insert(5, 1.0);
+
+                let mut offer = HashMap::new();
+                offer.insert(6, 2.0);
+
+                let result = test_pop.check_offer(&request, &offer, &data, &market);
+                assert_eq!(result, OfferResult::Accept(AcceptReason::AMV));
+            }
+        }
+
+        mod satisfaction_change_should {
+            use std::collections::HashMap;
+
+            use crate::{constants::TIME_ID, data::Data, demandcurve::DemandCurve, desire::Desire, good::Good, item::Item, markethistory::{GoodRecord, MarketHistory}, pop::{Pop, SatisfactionValues}, tests::fixtures::{goods_data, owning_pop}, want::Want};
+
+            fn setup() -> (Data, MarketHistory, Pop) {
+                let mut data = goods_data(&[(25, "Stone"), (26, "Coin")]);
+                data.wants.insert(10, Want::new(10, String::from("Rest")));
+                data.wants.insert(11, Want::new(11, String::from("Warmth")));
+                data.add_good(Good::new(20, String::from("Bed"), String::new())
+                    .with_uses(1.0, HashMap::from([(10, 1.0)])));
+                data.add_good(Good::new(21, String::from("Blanket"), String::new())
+                    .with_ownership(HashMap::from([(11, 1.0), (10, 0.5)])));
+                data.add_good(Good::new(22, String::from("Firewood"), String::new())
+                    .with_consumption(0.5, HashMap::from([(11, 2.0)])));
+                data.add_good(Good::new(23, String::from("Bread"), String::new())
+                    .in_class(23));
+                data.add_good(Good::new(24, String::from("Roll"), String::new())
+                    .in_class(23));
+
+                let mut market = MarketHistory::new();
+                market.good_records.insert(TIME_ID, GoodRecord::new().with_price(0.5));
+                for good in 20..28 {
+                    market.good_records.insert(good, GoodRecord::new().with_price(good as f64 - 19.0));
+                }
+
+                let mut test_pop = owning_pop(&[(TIME_ID, 10.0), (20, 1.0), (21, 1.0), (22, 2.0),
+                    (23, 1.0), (24, 2.0), (25, 2.0), (26, 1.0)]);
+                test_pop.desires.push_back(Desire::new(Item::Want(10), 1.0, 10.0,
+                    DemandCurve::linear(-1.0))
+                    .with_steps(5));
+                test_pop.desires.push_back(Desire::new(Item::Want(11), 2.0, 9.0,
+                    DemandCurve::linear(-1.0))
+                    .with_steps(3));
+                test_pop.desires.push_back(Desire::new(Item::Class(23), 1.0, 8.0,
+                    DemandCurve::linear(-1.0))
+                    .with_steps(4));
+                test_pop.desires.push_back(Desire::new(Item::Good(25), 1.0, 7.0,
+                    DemandCurve::linear(-1.0))
+                    .with_steps(3));
+                test_pop.desires.push_back(Desire::new(Item::Good(26), 0.5, 2.0,
+                    DemandCurve::linear(-0.5))
+                    .with_steps(4));
+                test_pop.try_satisfy_all_desires(&data, &market);
+                (data, market, test_pop)
+            }
+
+            fn assert_same(incremental: &SatisfactionValues, full: &SatisfactionValues) {
+                assert!((incremental.value - full.value).abs() < 0.000001);
+                assert!((incremental.steps - full.steps).abs() < 0.000001);
+                assert!((incremental.normalized - full.normalized).abs() < 0.000001);
+                assert!((incremental.satisfaction - full.satisfaction).abs() < 0.000001);
+                assert!((incremental.excess_amv - full.excess_amv).abs() < 0.000001);
+                assert_eq!(incremental.ranges, full.ranges);
+                assert_eq!(incremental.lost_ranges, full.lost_ranges);
+            }
+
+            #[test]
+            pub fn match_full_recalculation() {
+                let (data, market, test_pop) = setup();
+                let changes = [
+                    HashMap::from([(25, 1.0)]),
+                    HashMap::from([(23, -1.0), (26, 3.0)]),
+                    HashMap::from([(22, 1.0), (25, -2.0)]),
+                    HashMap::from([(21, -1.0)]),
+                    HashMap::from([(20, 2.0), (24, 1.0)]),
+                    HashMap::from([(TIME_ID, -8.0)]),
+                    HashMap::from([(27, 4.0)]),
+                ];
+                for change in changes.iter() {
+                    let incremental = test_pop.satisfaction_change(change, &data, &market);
+                    let full = test_pop.satisfaction_change_full(change, &data, &market);
+                    assert_same(&incremental, &full);
+                }
+            }
+
+            #[test]
+            pub fn match_full_recalculation_with_working_desires() {
+                let (data, market, mut test_pop) = setup();
+                let desire = test_pop.desires.pop_back().unwrap();
+                test_pop.working_desires.push_back(desire);
+                let change = HashMap::from([(26, 2.0), (22, -1.0)]);
+
+                let incremental = test_pop.satisfaction_change(&change, &data, &market);
+                let full = test_pop.satisfaction_change_full(&change, &data, &market);
+                assert_same(&incremental, &full);
+            }
+
+            #[test]
+            pub fn keep_lost_and_gain_consistent_with_change() {
+                let (data, market, test_pop) = setup();
+                let change = HashMap::from([(24, 1.0), (25, 1.0)]);
+                let removing = HashMap::from([(24, 1.0), (25, 1.0)]);
+
+                let gain = test_pop.satisfaction_gain(&change, &data, &market);
+                let full = test_pop.satisfaction_change_full(&change, &data, &market);
+                assert!((gain.normalized - full.normalized).abs() < 0.000001);
+                assert_eq!(gain.ranges, full.ranges);
+
+                let lost = test_pop.satisfaction_lost(&removing, &data, &market);
+                let removing = HashMap::from([(24, -1.0), (25, -1.0)]);
+                let full = test_pop.satisfaction_change_full(&removing, &data, &market);
+                assert!((lost.normalized + full.normalized).abs() < 0.000001);
+                assert_eq!(lost.lost_ranges, full.lost_ranges);
+                assert!(lost.normalized > 0.0);
+            }
+        }
+
+        mod update_financial_mood_should {
+            use crate::{data::Data, demandcurve::DemandCurve, desire::Desire, household::Household, item::Item, markethistory::MarketHistory, pop::{Pop, PropertyRecord}, tests::fixtures::{add_humans, flat_history, goods_data, human_pop}};
+
+            fn setup() -> (Data, MarketHistory, Pop) {
+                let mut data = goods_data(&[(5, "Bread")]);
+                add_humans(&mut data, vec![Desire::new(Item::Good(5), 1.0, 10.0, DemandCurve::linear(-1.0))
+                    .with_steps(2)]);
+                let mut market = flat_history(&[5]);
+                market.good_records.get_mut(&5).unwrap().volatility = 0.5;
+
+                // 1 household of 2 people.
+                let mut test_pop = human_pop(&data, 0, vec![Household::new(1.0, 2.0, 0.0, 0.0)]);
+                test_pop.financials.base_saving_rate = 0.2;
+                test_pop.financials.saving_rate_cap = 0.5;
+                test_pop.financials.base_investment_rate = 0.2;
+                test_pop.financials.investment_rate_cap = 0.5;
+                test_pop.financials.base_interest_rate = 0.05;
+                test_pop.financials.interest_rate_cap = 0.02;
+                (data, market, test_pop)
+            }
+
+            fn assert_near(value: f64, expected: f64) {
+                assert!((value - expected).abs() < 0.000001, "{} != {}", value, expected);
+            }
+
+            #[test]
+            pub fn grow_uncertain_and_impatient_when_desires_go_unmet() {
+                let (_, market, mut test_pop) = setup();
+                test_pop.update_financial_mood(&market);
+                let financials = &test_pop.financials;
+                // nothing satisfied, no goods to be volatile.
+                assert_near(financials.uncertainty, 0.1);
+                assert_near(financials.risk_tolerance, 0.0);
+                assert_near(financials.time_preference, 0.105);
+                assert_near(financials.curr_saving_rate, 0.22);
+                assert_near(financials.current_investment_rate, 0.18);
+                assert_near(financials.current_interest_rate, 0.205);
+            }
+
+            #[test]
+            pub fn grow_risk_tolerant_when_satisfied() {
+                let (data, market, mut test_pop) = setup();
+                test_pop.property.insert(5, PropertyRecord::new(4.0));
+                test_pop.try_satisfy_all_desires(&data, &market);
+                test_pop.update_financial_mood(&market);
+                let financials = &test_pop.financials;
+                // only uncertainty from the bread's price.
+                assert_near(financials.uncertainty, 0.05);
+                assert_near(financials.risk_tolerance, 0.05);
+                assert_near(financials.time_preference, 0.005);
+                assert_near(financials.curr_saving_rate, 0.2);
+                assert_near(financials.current_investment_rate, 0.2);
+                assert_near(financials.current_interest_rate, 0.02);
+            }
+
+            #[test]
+            pub fn track_wealth_trends_and_feed_into_trade_thresholds() {
+                let (_, _, mut test_pop) = setup();
+                for wealth in [10.0, 20.0, 30.0] {
+                    test_pop.financials.wealth_history.push_back(wealth);
+                }
+                assert_near(test_pop.financials.update_average_wealth(), 20.0);
+                // 10 a day on an average of 20.
+                assert_near(test_pop.financials.wealth_inertia, 0.5);
+                test_pop.financials.update_mood(0.0, 0.0);
+                // sqrt(200 / 3) / 20 volatility, 0.5 + 0.5 risk tolerance.
+                assert_near(test_pop.financials.uncertainty, (200.0f64 / 3.0).sqrt() / 20.0 * 0.1);
+                assert_near(test_pop.financials.risk_tolerance, 0.1);
+
+                test_pop.financials.uncertainty = 1.0;
+                test_pop.financials.risk_tolerance = 0.0;
+                assert_near(test_pop.financials.hard_loss_threshold(), 0.5);
+                test_pop.financials.risk_tolerance = 2.0;
+                assert_near(test_pop.financials.hard_loss_threshold(), 0.0);
+            }
+        }
+
+        mod day_end_should {
+            use crate::{constants::TIME_ID, demandcurve::DemandCurve, desire::Desire, good::{Good, GoodTags}, household::Household, item::Item, markethistory::GoodRecord, pop::{PropertyRecord, WantRecord}, tests::fixtures::{add_humans, flat_history, goods_data, human_pop}, want::Want};
+
+            #[test]
+            pub fn consume_decay_and_close_out_the_day() {
+                let mut data = goods_data(&[(5, "Bread")]);
+                data.wants.insert(3, Want::new(3, String::from("Comfort")).decays_by(0.5));
+                data.wants.insert(4, Want::new(4, String::from("Joy")).decays_by(0.5));
+                data.add_good(Good::new(6, String::from("Haircut"), String::new())
+                    .with_tags(vec![GoodTags::Service]));
+                add_humans(&mut data, vec![Desire::new(Item::Good(5), 1.0, 10.0, DemandCurve::linear(-1.0))
+                    .with_steps(2)]);
+                let mut market = flat_history(&[TIME_ID, 5]);
+                market.good_records.insert(6, GoodRecord::new().with_price(3.0));
+
+                // 1 household of 2 people.
+                let mut test_pop = human_pop(&data, 0, vec![Household::new(1.0, 2.0, 0.0, 0.0)]);
+                test_pop.property.insert(5, PropertyRecord::new(5.0));
+                test_pop.property.insert(6, PropertyRecord::new(2.0));
+                let mut comfort = WantRecord::new();
+                comfort.owned = 1.0;
+                test_pop.wants.insert(3, comfort);
+                let mut joy = WantRecord::new();
+                joy.owned = 0.0015;
+                test_pop.wants.insert(4, joy);
+                test_pop.try_satisfy_until_incomplete(&data, &market);
+                test_pop.financials.wealth = 10.0;
+                test_pop.financials.income = 4.0;
+                test_pop.financials.dividends = 1.0;
+
+                test_pop.day_end(&data, None, &market);
+                // desires are consumed and working desires cleared out.
+                assert!(test_pop.working_desires.is_empty());
+                assert_eq!(test_pop.desires.len(), 1);
+                assert_eq!(test_pop.desires[0].satisfied_steps(), 2.0);
+                assert_eq!(test_pop.property[&5].owned, 1.0);
+                // wants decay, and those too small are dropped.
+                assert_eq!(test_pop.wants[&3].owned, 0.5);
+                assert!(!test_pop.wants.contains_key(&4));
+                // services expire.
+                assert_eq!(test_pop.property[&6].owned, 0.0);
+                assert_eq!(test_pop.financials.decay, 6.0);
+                // financials are recorded.
+                assert_eq!(test_pop.financials.amv_end, 1.0);
+                assert_eq!(test_pop.financials.wealth_history.back(), Some(&10.0));
+                assert_eq!(test_pop.financials.income_history.back(), Some(&4.0));
+                assert_eq!(test_pop.financials.dividend_history.back(), Some(&1.0));
+                assert_eq!(test_pop.financials.average_wealth, 10.0);
+                assert_eq!(test_pop.financials.average_income, 4.0);
+                assert_eq!(test_pop.financials.income, 0.0);
+                assert_eq!(test_pop.financials.dividends, 0.0);
+
+                // averages are the mean of history.
+                test_pop.financials.wealth = 20.0;
+                test_pop.day_end(&data, None, &market);
+                assert_eq!(test_pop.financials.average_wealth, 15.0);
+                assert_eq!(test_pop.financials.average_income, 2.0);
+            }
+        }
+
+        mod split_fraction_should {
+            use crate::{data::Data, demandcurve::DemandCurve, desire::Desire, drow::DRow, household::Household, item::Item, pop::{Pop, PopTag, PropertyRecord, WantRecord}, species::Species, tests::fixtures::goods_data};
+
+            pub fn setup() -> (Data, Pop) {
+                let mut data = goods_data(&[]);
+                for id in [1, 2] {
+                    data.species.insert(id, Species::new(id, format!("Species {}", id))
+                        .with_desire(Desire::new(Item::Good(5), 1.0, 10.0, DemandCurve::linear(-1.0))));
+                }
+                // 30 people of species 1, 10 of species 2.
+                let mut test_pop = Pop::new(0, 2, 3)
+                    .add_demo(DRow { household: Household::new(10.0, 2.0, 1.0, 0.0), species: 1, culture: None, layers: vec![] })
+                    .add_demo(DRow { household: Household::new(5.0, 1.0, 1.0, 0.0), species: 2, culture: None, layers: vec![] });
+                test_pop.combine_households(&data);
+                test_pop.update_desires_full(&data);
+                let mut bread = PropertyRecord::new(40.0);
+                bread.saved = 4.0;
+                test_pop.property.insert(5, bread);
+                let mut comfort = WantRecord::new();
+                comfort.owned = 8.0;
+                test_pop.wants.insert(3, comfort);
+                test_pop.financials.wealth = 100.0;
+                test_pop.financials.uncertainty = 0.3;
+                test_pop.financials.wealth_history.push_back(10.0);
+                test_pop.financials.wealth_history.push_back(20.0);
+                test_pop.tags.insert(PopTag::Slave);
+                (data, test_pop)
+            }
+
+            #[test]
+            pub fn give_new_pop_a_share_of_everything() {
+                let (data, mut test_pop) = setup();
+                let new_pop = test_pop.split_fraction(1, 0.25, &data).unwrap();
+
+                assert_eq!(new_pop.id, 1);
+                assert_eq!(new_pop.market, 2);
+                assert_eq!(new_pop.firm, 3);
+                assert!(new_pop.tags.contains(&PopTag::Slave));
+                // rows
+                assert_eq!(new_pop.demo_breakdown.len(), 2);
+                assert_eq!(new_pop.demo_breakdown[0].household.count, 2.5);
+                assert_eq!(new_pop.demo_breakdown[1].household.count, 1.25);
+                assert_eq!(test_pop.demo_breakdown[0].household.count, 7.5);
+                assert_eq!(test_pop.demo_breakdown[1].household.count, 3.75);
+                assert_eq!(new_pop.households.count + test_pop.households.count, 15.0);
+                assert_eq!(new_pop.households.population(), 10.0);
+                // property and wants
+                assert_eq!(new_pop.property[&5].owned, 10.0);
+                assert_eq!(new_pop.property[&5].saved, 1.0);
+                assert_eq!(test_pop.property[&5].owned, 30.0);
+                assert_eq!(test_pop.property[&5].saved, 3.0);
+                assert_eq!(new_pop.wants[&3].owned, 2.0);
+                assert_eq!(test_pop.wants[&3].owned, 6.0);
+                // financials
+                assert_eq!(new_pop.financials.wealth, 25.0);
+                assert_eq!(test_pop.financials.wealth, 75.0);
+                assert_eq!(new_pop.financials.uncertainty, 0.3);
+                assert_eq!(new_pop.financials.wealth_history.iter().copied().collect::<Vec<f64>>(), vec![2.5, 5.0]);
+                // desires rebuilt for each.
+                assert_eq!(new_pop.desires[0].amount, 10.0);
+                assert_eq!(test_pop.desires[0].amount, 30.0);
+            }
+
+            #[test]
+            pub fn reject_fractions_outside_zero_and_one() {
+                let (data, mut test_pop) = setup();
+                for fraction in [0.0, 1.0, -0.5, 1.5, f64::NAN] {
+                    assert!(test_pop.split_fraction(1, fraction, &data).is_err());
+                    assert!(test_pop.split_to_firm(1, fraction, 9, &data).is_err());
+                }
+                // left unchanged.
+                assert_eq!(test_pop.households.count, 15.0);
+                assert_eq!(test_pop.property[&5].owned, 40.0);
+                assert_eq!(test_pop.financials.wealth, 100.0);
+            }
+        }
+
+        mod split_rows_should {
+            use crate::pop::PopTag;
+
+            use super::split_fraction_should::setup;
+
+            #[test]
+            pub fn move_whole_rows_and_a_share_by_population() {
+                let (data, mut test_pop) = setup();
+                // species 2 is the smaller row, 10 of 40 people.
+                let new_pop = test_pop.split_rows(1, &[1, 7], &data);
+
+                assert_eq!(new_pop.demo_breakdown.len(), 1);
+                assert_eq!(new_pop.demo_breakdown[0].species, 2);
+                assert_eq!(new_pop.demo_breakdown[0].household.count, 5.0);
+                assert_eq!(test_pop.demo_breakdown.len(), 1);
+                assert_eq!(test_pop.demo_breakdown[0].species, 1);
+                assert_eq!(new_pop.property[&5].owned, 10.0);
+                assert_eq!(test_pop.property[&5].owned, 30.0);
+                assert!(new_pop.tags.contains(&PopTag::Slave));
+
+                let other = test_pop.split_to_firm(2, 0.5, 9, &data).unwrap();
+                assert_eq!(other.firm, 9);
+                assert_eq!(test_pop.firm, 3);
+                assert_eq!(other.households.count, 5.0);
+            }
+        }
+
+        mod merge_should {
+            use std::collections::HashMap;
+
+            use crate::{constants::TIME_ID, item::Item, pop::{Pop, PopTag, PropertyRecord}};
+
+            use super::split_fraction_should::setup;
+
+            #[test]
+            pub fn recombine_split_pops() {
+                let (data, mut test_pop) = setup();
+                let mut new_pop = test_pop.split_fraction(1, 0.25, &data).unwrap();
+                new_pop.financials.uncertainty = 0.7;
+                new_pop.prices.prices.insert(9, 4.0);
+                test_pop.merge(new_pop, &data).unwrap();
+
+                assert_eq!(test_pop.demo_breakdown.len(), 2);
+                assert_eq!(test_pop.demo_breakdown[0].household.count, 10.0);
+                assert_eq!(test_pop.demo_breakdown[1].household.count, 5.0);
+                assert_eq!(test_pop.households.count, 15.0);
+                assert_eq!(test_pop.property[&5].owned, 40.0);
+                assert_eq!(test_pop.property[&5].saved, 4.0);
+                assert_eq!(test_pop.wants[&3].owned, 8.0);
+                assert_eq!(test_pop.financials.wealth, 100.0);
+                assert_eq!(test_pop.financials.wealth_history.iter().copied().collect::<Vec<f64>>(), vec![10.0, 20.0]);
+                // mood weighted by population, 0.25 of the pop at 0.7.
+                assert!((test_pop.financials.uncertainty - 0.4).abs() < 0.000001);
+                assert_eq!(test_pop.prices.prices[&9], 4.0);
+                assert_eq!(test_pop.desires[0].amount, 40.0);
+            }
+
+            #[test]
+            pub fn add_new_rows_and_property() {
+                let (data, mut test_pop) = setup();
+                let mut new_pop = test_pop.split_rows(1, &[1], &data);
+                new_pop.property.insert(7, PropertyRecord::new(3.0));
+                test_pop.merge(new_pop, &data).unwrap();
+                assert_eq!(test_pop.demo_breakdown.len(), 2);
+                assert_eq!(test_pop.demo_breakdown[1].species, 2);
+                assert_eq!(test_pop.households.count, 15.0);
+                assert_eq!(test_pop.property[&5].owned, 40.0);
+                assert_eq!(test_pop.property[&7].owned, 3.0);
+            }
+
+            #[test]
+            pub fn reject_incompatible_pops() {
+                let (data, mut test_pop) = setup();
+                let other_market = Pop::new(1, 1, 3);
+                assert!(test_pop.merge(other_market, &data).is_err());
+                let other_firm = Pop::new(1, 2, 4);
+                assert!(test_pop.merge(other_firm, &data).is_err());
+                let free = Pop::new(1, 2, 3);
+                assert!(test_pop.merge(free, &data).is_err());
+                let mut same = Pop::new(0, 2, 3);
+                same.tags.insert(PopTag::Slave);
+                assert!(test_pop.merge(same, &data).is_err());
+                // nothing changed
+                assert_eq!(test_pop.households.count, 15.0);
+                assert_eq!(test_pop.property.iter().map(|(g, r)| (*g, r.owned)).collect::<HashMap<usize, f64>>(), 
+                    HashMap::from([(5, 40.0)]));
+            }
+            #[test]
+            pub fn leave_us_unchanged_when_households_would_be_lost() {
+                let (data, mut test_pop) = setup();
+                let mut new_pop = test_pop.split_fraction(1, 0.25, &data).unwrap();
+                let count = test_pop.households.count;
+                let owned = test_pop.property[&5].owned;
+                // out of sync with it's rows.
+                new_pop.households.count += 1.0;
+                assert!(test_pop.merge(new_pop, &data).is_err());
+                assert_eq!(test_pop.households.count, count);
+                assert_eq!(test_pop.property[&5].owned, owned);
+            }
+
+            #[test]
+            pub fn release_the_time_of_others_pending_order() {
+                let (data, mut test_pop) = setup();
+                let mut new_pop = test_pop.split_fraction(1, 0.25, &data).unwrap();
+                test_pop.property.insert(TIME_ID, PropertyRecord::new(10.0));
+                let mut time = PropertyRecord::new(10.0);
+                time.reserved = 4.0;
+                new_pop.property.insert(TIME_ID, time);
+                new_pop.pending_order = Some((Item::Good(5), 4.0));
+                test_pop.merge(new_pop, &data).unwrap();
+                assert_eq!(test_pop.pending_order, None);
+                assert_eq!(test_pop.property[&TIME_ID].owned, 20.0);
+                assert_eq!(test_pop.property[&TIME_ID].reserved, 0.0);
+            }
+        }
+
+        pub mod inheritance_should {
+            use crate::{constants::DAYS_PER_TURN, data::Data, demandcurve::DemandCurve, desire::{Desire, DesireTag}, household::Household, inheritance::Heir, item::Item, markethistory::MarketHistory, pop::{Pop, PropertyRecord}, tests::fixtures::{add_humans, flat_history, goods_data, human_pop}};
+
+            pub fn setup(mortality: f64) -> (Data, MarketHistory, Pop) {
+                let mut data = goods_data(&[(5, "Bread"), (6, "Tools"), (7, "Water")]);
+                add_humans(&mut data, vec![Desire::new(Item::Good(7), 1.0, 10.0, DemandCurve::linear(-1.0))
+                    .with_steps(1)
+                    .with_tag(DesireTag::life_need(mortality))]);
+                let market = flat_history(&[5, 6, 7]);
+                // 10 households of 3 people.
+                let mut test_pop = human_pop(&data, 2, vec![Household::new(10.0, 2.0, 1.0, 0.0)]);
+                test_pop.property.insert(5, PropertyRecord::new(30.0));
+                test_pop.property.insert(6, PropertyRecord::new(6.0));
+                (data, market, test_pop)
+            }
+
+            #[test]
+            pub fn leave_the_share_of_the_dead_to_survivors() {
+                let (data, _, mut test_pop) = setup(0.2);
+                let report = test_pop.apply_growth(&data);
+                // 30 people and their newborns at 0.2 a day.
+                let deaths = (30.0 + report.births) * 0.2 * DAYS_PER_TURN;
+                assert!((report.deaths - deaths).abs() < 0.000001);
+                let inheritance = report.inheritance.unwrap();
+                assert_eq!(inheritance.heir, Heir::Survivors);
+                assert_eq!(inheritance.pop, 0);
+                assert!((inheritance.goods[&5] - 30.0 * deaths / 30.0).abs() < 0.000001);
+                assert!((inheritance.goods[&6] - 6.0 * deaths / 30.0).abs() < 0.000001);
+                // and they keep it.
+                assert_eq!(test_pop.property[&5].owned, 30.0);
+            }
+
+            #[test]
+            pub fn leave_everything_to_the_heir_when_the_pop_dies_out() {
+                let (data, market, mut test_pop) = setup(1.0);
+                test_pop.day_end(&data, None, &market);
+                let report = test_pop.apply_growth(&data);
+                assert!(test_pop.demo_breakdown.is_empty());
+                let inheritance = report.inheritance.unwrap();
+                assert_eq!(inheritance.heir, Heir::Market(2));
+                assert_eq!(inheritance.market, 2);
+                assert_eq!(inheritance.goods[&5], 30.0);
+                assert_eq!(inheritance.goods[&6], 6.0);
+
+                let (data, _, mut test_pop) = setup(1.0);
+                test_pop.heir = Some(Heir::Firm(4));
+                let report = test_pop.apply_growth(&data);
+                assert_eq!(report.inheritance.unwrap().heir, Heir::Firm(4));
+            }
+
+            #[test]
+            pub fn record_nothing_when_no_one_dies() {
+                let (data, market, mut test_pop) = setup(0.2);
+                test_pop.property.insert(7, PropertyRecord::new(30.0));
+                test_pop.try_satisfy_all_desires(&data, &market);
+                assert!(test_pop.inheritance(0.0, 30.0).is_none());
+                assert!(test_pop.apply_growth(&data).inheritance.is_none());
+            }
+        }
+
+        pub mod apply_growth_should {
+            use crate::{constants::{DAYS_PER_TURN, POP_GROWTH_PERIOD}, data::Data, demandcurve::DemandCurve, desire::{Desire, DesireTag}, household::{CohortAging, Household}, inheritance::Heir, item::Item, pop::{Pop, PropertyRecord}, tests::fixtures::{add_humans, flat_history, goods_data, human_pop}};
+
+            pub fn setup(rows: Vec<Household>) -> (Data, Pop) {
+                let mut data = goods_data(&[]);
+                add_humans(&mut data, vec![Desire::new(Item::Good(5), 1.0, 10.0, DemandCurve::linear(-1.0))]);
+                let test_pop = human_pop(&data, 0, rows);
+                (data, test_pop)
+            }
+
+            #[test]
+            pub fn apply_births_and_deaths_and_resort_rows() {
+                let (data, mut test_pop) = setup(vec![
+                    Household::new(100.0, 2.0, 2.0, 0.0),
+                    Household::new(100.03, 2.0, 0.0, 2.0),
+                ]);
+                // largest first.
+                assert_eq!(test_pop.demo_breakdown[0].household.elders, 2.0);
+                let scale = DAYS_PER_TURN / POP_GROWTH_PERIOD;
+
+                let report = test_pop.apply_growth(&data);
+                // 400 people with 0.2 birthrate, 400.12 with 0.05 mortality.
+                assert!((report.births - 400.0 * 0.2 * scale).abs() < 0.000001);
+                assert!((report.deaths - 400.12 * 0.05 * scale).abs() < 0.000001);
+                assert!(report.causes.is_empty());
+                assert!((report.net() - (report.births - report.deaths)).abs() < 0.000001);
+                // the growing row is now the largest.
+                assert_eq!(test_pop.demo_breakdown[0].household.children, 2.0);
+                assert!((test_pop.demo_breakdown[0].household.count - 100.0 * (1.0 + 0.2 * scale)).abs() < 0.000001);
+                assert!((test_pop.demo_breakdown[1].household.count - 100.03 * (1.0 - 0.05 * scale)).abs() < 0.000001);
+                assert!((test_pop.households.count - test_pop.demo_breakdown.iter()
+                    .map(|x| x.household.count).sum::<f64>()).abs() < 0.000001);
+                // too small a change to rebuild desires.
+                assert_eq!(test_pop.desires[0].amount, 800.12);
+            }
+
+            #[test]
+            pub fn rebuild_desires_once_population_changes_materially() {
+                let (data, mut test_pop) = setup(vec![Household::new(100.0, 2.0, 2.0, 0.0)]);
+                assert_eq!(test_pop.desires[0].amount, 400.0);
+                let mut turns = 0;
+                while test_pop.desires[0].amount == 400.0 {
+                    test_pop.apply_growth(&data);
+                    turns += 1;
+                    assert!(turns < 100);
+                }
+                // 0.05% growth each turn takes 20 turns to pass 1%.
+                assert_eq!(turns, 20);
+                assert!((test_pop.desires[0].amount - test_pop.households.population()).abs() < 0.000001);
+                assert_eq!(test_pop.desire_population, test_pop.desires[0].amount);
+            }
+
+            #[test]
+            pub fn age_cohorts_with_births_following_satisfaction() {
+                let setup = |bread: f64| {
+                    let mut data = goods_data(&[(5, "Bread")]);
+                    add_humans(&mut data, vec![Desire::new(Item::Good(5), 1.0, 10.0, DemandCurve::linear(-1.0))
+                        .with_steps(1)]);
+                    data.species.get_mut(&1).unwrap().aging = Some(CohortAging::new(0.1, 0.05).with_birth_sensitivity(0.5));
+                    let market = flat_history(&[5]);
+                    let mut test_pop = human_pop(&data, 0, vec![Household::new(100.0, 2.0, 2.0, 1.0)]);
+                    test_pop.property.insert(5, PropertyRecord::new(bread));
+                    test_pop.try_satisfy_all_desires(&data, &market);
+                    (data, test_pop)
+                };
+                let scale = DAYS_PER_TURN / POP_GROWTH_PERIOD;
+                let (data, mut fed) = setup(500.0);
+                let fed_report = fed.apply_growth(&data);
+                let (data, mut hungry) = setup(0.0);
+                let hungry_report = hungry.apply_growth(&data);
+                // 500 people, 0.2 birthrate, boomed or busted by half.
+                assert!((fed_report.births - 500.0 * 0.2 * scale * 1.5).abs() < 0.000001);
+                assert!((hungry_report.births - 500.0 * 0.2 * scale * 0.5).abs() < 0.000001);
+                assert_eq!(fed_report.deaths, hungry_report.deaths);
+                // the elder's 0.1 spread over 5 people.
+                assert!((fed_report.deaths - 500.0 * 0.02 * scale).abs() < 0.000001);
+                // the makeup of the households changed, but not their adults.
+                let household = fed.demo_breakdown[0].household;
+                assert_eq!(household.adults, 2.0);
+                let children = 200.0 + fed_report.births;
+                let adults = 200.0 + children * 0.1 - 200.0 * 0.05;
+                assert!((household.total_children() - children * 0.9).abs() < 0.000001);
+                assert!((household.total_adults() - adults).abs() < 0.000001);
+                assert!((household.count - adults / 2.0).abs() < 0.000001);
+                assert!((fed.households.total_children() - household.total_children()).abs() < 0.000001);
+                assert!(fed.households.labor() > hungry.households.labor());
+            }
+
+            #[test]
+            pub fn count_those_left_without_adults_as_deaths() {
+                let (mut data, mut test_pop) = setup(vec![Household::new(10.0, 1.0, 1.0, 0.0)]);
+                // every adult becomes an elder, leaving no households.
+                data.species.get_mut(&1).unwrap().aging = Some(CohortAging::new(0.0, 1.0));
+                test_pop.property.insert(5, PropertyRecord::new(10.0));
+                let report = test_pop.apply_growth(&data);
+                assert!(report.births > 0.0);
+                assert!((report.deaths - (20.0 + report.births)).abs() < 0.000001);
+                assert!(test_pop.demo_breakdown.is_empty());
+                let inheritance = report.inheritance.unwrap();
+                assert_eq!(inheritance.heir, Heir::Market(0));
+                assert_eq!(inheritance.goods[&5], 10.0);
+            }
+
+            #[test]
+            pub fn kill_off_pop_from_missing_life_needs() {
+                let life_need_setup = |bread: f64| {
+                    let mut data = goods_data(&[(5, "Bread")]);
+                    add_humans(&mut data, vec![Desire::new(Item::Good(5), 1.0, 10.0, DemandCurve::linear(-1.0))
+                        .with_steps(4)
+                        .with_tag(DesireTag::life_need(0.1))]);
+                    let market = flat_history(&[5]);
+                    // 10 households of 3 people, one an elder.
+                    let mut test_pop = human_pop(&data, 0, vec![Household::new(10.0, 2.0, 0.0, 1.0)]);
+                    test_pop.property.insert(5, PropertyRecord::new(bread));
+                    test_pop.try_satisfy_all_desires(&data, &market);
+                    test_pop.day_end(&data, None, &market);
+                    (data, test_pop)
+                };
+                let scale = DAYS_PER_TURN / POP_GROWTH_PERIOD;
+                let (data, mut test_pop) = life_need_setup(60.0);
+                assert_eq!(test_pop.desires[0].amount, 30.0);
+
+                let report = test_pop.apply_growth(&data);
+                // 2 of 4 steps missing at 0.1 a day, on top of the elder's.
+                let hungry = 0.2 * DAYS_PER_TURN;
+                let rate = hungry + 0.1 / 3.0 * scale;
+                assert!((report.deaths - 30.0 * rate).abs() < 0.000001);
+                assert!((report.causes[&Item::Good(5)] - 30.0 * hungry).abs() < 0.000001);
+                assert!((test_pop.demo_breakdown[0].household.count - 10.0 * (1.0 - rate)).abs() < 0.000001);
+                assert!((test_pop.households.count - 10.0 * (1.0 - rate)).abs() < 0.000001);
+                assert!((test_pop.desires[0].amount - 30.0 * (1.0 - rate)).abs() < 0.000001);
+
+                // fed, only the elders die.
+                let (data, mut test_pop) = life_need_setup(120.0);
+                let report = test_pop.apply_growth(&data);
+                assert!((report.deaths - 30.0 * 0.1 / 3.0 * scale).abs() < 0.000001);
+                assert!(report.causes.is_empty());
+            }
+
+            #[test]
+            pub fn remove_rows_which_die_out() {
+                let (data, mut test_pop) = setup(vec![
+                    Household::new(10.0, 1.0, 0.0, 0.0),
+                    Household::new(0.0, 1.0, 0.0, 0.0),
+                ]);
+                let report = test_pop.apply_growth(&data);
+                assert_eq!(report.births, 0.0);
+                assert_eq!(report.deaths, 0.0);
+                assert_eq!(test_pop.demo_breakdown.len(), 1);
+                assert_eq!(test_pop.demo_breakdown[0].household.count, 10.0);
+            }
+        }
+
+        mod update_habits_should {
+            use crate::{data::Data, demandcurve::DemandCurve, desire::{Desire, DesireTag}, household::Household, item::Item, markethistory::MarketHistory, pop::{Pop, PropertyRecord}, tests::fixtures::{add_humans, flat_history, goods_data, human_pop}};
+
+            fn setup() -> (Data, MarketHistory, Pop) {
+                let mut data = goods_data(&[(5, "Tea"), (6, "Bread")]);
+                add_humans(&mut data, vec![
+                    Desire::new(Item::Good(5), 1.0, 5.0, DemandCurve::linear(-1.0))
+                        .with_steps(2)
+                        .with_tag(DesireTag::habituation(0.5, 3)),
+                    Desire::new(Item::Good(6), 1.0, 10.0, DemandCurve::linear(-1.0))
+                        .with_steps(2),
+                ]);
+                let market = flat_history(&[5, 6]);
+
+                // 1 household of 2 people.
+                let test_pop = human_pop(&data, 0, vec![Household::new(1.0, 2.0, 0.0, 0.0)]);
+                (data, market, test_pop)
+            }
+
+            fn live_day(test_pop: &mut Pop, data: &Data, market: &MarketHistory, tea: f64) {
+                test_pop.reset_property();
+                test_pop.property.insert(5, PropertyRecord::new(tea));
+                test_pop.property.insert(6, PropertyRecord::new(100.0));
+                test_pop.try_satisfy_all_desires(data, market);
+                test_pop.day_end(data, None, market);
+            }
+
+            fn amount_of(test_pop: &Pop, item: Item) -> f64 {
+                test_pop.desires.iter().find(|x| x.item == item).unwrap().amount
+            }
+
+            #[test]
+            pub fn raise_amount_after_sustained_satisfaction() {
+                let (data, market, mut test_pop) = setup();
+                assert_eq!(amount_of(&test_pop, Item::Good(5)), 2.0);
+                live_day(&mut test_pop, &data, &market, 100.0);
+                live_day(&mut test_pop, &data, &market, 100.0);
+                assert_eq!(amount_of(&test_pop, Item::Good(5)), 2.0);
+                live_day(&mut test_pop, &data, &market, 100.0);
+                assert_eq!(amount_of(&test_pop, Item::Good(5)), 3.0);
+                assert_eq!(test_pop.habits[&Item::Good(5)].multiplier, 1.5);
+                // other desires don't change.
+                assert_eq!(amount_of(&test_pop, Item::Good(6)), 2.0);
+                assert!(!test_pop.habits.contains_key(&Item::Good(6)));
+
+                // habits persist through rebuilding desires.
+                test_pop.update_desires_full(&data);
+                assert_eq!(amount_of(&test_pop, Item::Good(5)), 3.0);
+            }
+
+            #[test]
+            pub fn lower_amount_after_sustained_deprivation() {
+                let (data, market, mut test_pop) = setup();
+                live_day(&mut test_pop, &data, &market, 1.0);
+                live_day(&mut test_pop, &data, &market, 100.0);
+                live_day(&mut test_pop, &data, &market, 1.0);
+                // broken up, so no change.
+                assert_eq!(amount_of(&test_pop, Item::Good(5)), 2.0);
+                live_day(&mut test_pop, &data, &market, 1.0);
+                live_day(&mut test_pop, &data, &market, 0.0);
+                assert_eq!(amount_of(&test_pop, Item::Good(5)), 1.0);
+                // can't go below the minimum.
+                for _ in 0..6 {
+                    live_day(&mut test_pop, &data, &market, 0.0);
+                }
+                assert_eq!(amount_of(&test_pop, Item::Good(5)), 1.0);
+            }
+
+            #[test]
+            pub fn share_one_habit_between_desires_for_the_same_item() {
+                let mut test_pop = Pop::new(0, 0, 0);
+                test_pop.desires.push_back(Desire::new(Item::Good(5), 1.0, 5.0, DemandCurve::linear(-1.0))
+                    .with_steps(2)
+                    .with_tag(DesireTag::habituation(0.5, 3)));
+                test_pop.desires.push_back(Desire::new(Item::Good(5), 2.0, 3.0, DemandCurve::linear(-1.0))
+                    .with_steps(1)
+                    .with_tag(DesireTag::habituation(0.5, 3)));
+                for day in 0..3 {
+                    for desire in test_pop.desires.iter_mut() {
+                        desire.satisfaction = desire.max_satisfaction().unwrap();
+                    }
+                    test_pop.update_habits();
+                    assert_eq!(test_pop.habits[&Item::Good(5)].history.len(), (day + 1) % 3);
+                }
+                // both desires are raised together, once.
+                assert_eq!(test_pop.habits[&Item::Good(5)].multiplier, 1.5);
+                assert_eq!(test_pop.desires[0].amount, 1.5);
+                assert_eq!(test_pop.desires[1].amount, 3.0);
+            }
+        }
+
+        mod free_time_action_should {
+            use std::collections::HashMap;
+
+            use crate::{constants::{POP_PLANNING_TIME, TIME_ID}, data::Data, demandcurve::DemandCurve, desire::{Desire, DesireTag}, freetimeaction::FreeTimeAction, good::Good, household::Household, item::Item, markethistory::{GoodRecord, MarketHistory}, pop::{Pop, PropertyRecord}, tests::fixtures::{add_humans, flat_history, goods_data, human_pop}, want::Want};
+
+            fn setup() -> (Data, MarketHistory, Pop) {
+                let mut data = goods_data(&[]);
+                data.wants.insert(3, Want::new(3, String::from("Food")));
+                data.add_good(Good::new(5, String::from("Bread"), String::new())
+                    .with_consumption(0.5, HashMap::from([(3, 1.0)])));
+                add_humans(&mut data, vec![Desire::new(Item::Want(3), 1.0, 10.0, DemandCurve::linear(-1.0))
+                    .with_tag(DesireTag::life_need(0.1))]);
+                let market = flat_history(&[TIME_ID, 5]);
+
+                // 1 household of 2 people.
+                let mut test_pop = human_pop(&data, 0, vec![Household::new(1.0, 2.0, 0.0, 0.0)]);
+                test_pop.property.insert(TIME_ID, PropertyRecord::new(10.0));
+                (data, market, test_pop)
+            }
+
+            #[test]
+            pub fn buy_desires_then_plan() {
+                let (data, market, mut test_pop) = setup();
+                match test_pop.free_time_action(&data, &market) {
+                    FreeTimeAction::BuyOrder { good, amount } => {
+                        assert_eq!(good, Item::Want(3));
+                        assert_eq!(amount, 2.0);
+                    },
+                    other => panic!("Expected BuyOrder, got {:?}", other),
+                }
+                // time to eat the bread is set aside.
+                assert_eq!(test_pop.property[&TIME_ID].reserved, 1.0);
+                assert_eq!(test_pop.pending_order, Some((Item::Want(3), 1.0)));
+
+                // the market fills the order, the pop eats and has nothing else to buy.
+                test_pop.property.insert(5, PropertyRecord::new(2.0));
+                assert!(matches!(test_pop.free_time_action(&data, &market), FreeTimeAction::GetActive));
+                assert!(test_pop.working_desires.is_empty());
+                assert_eq!(test_pop.property[&5].reserved, 2.0);
+                // eating and being active each took their time.
+                assert_eq!(test_pop.property[&TIME_ID].available(), 9.0 - POP_PLANNING_TIME);
+                // it stays active until it has no time left, then it's done.
+                let mut active = 1;
+                while let FreeTimeAction::GetActive = test_pop.free_time_action(&data, &market) {
+                    active += 1;
+                    assert!(active < 20);
+                }
+                assert_eq!(active, 9);
+                assert_eq!(test_pop.property[&TIME_ID].available(), 0.0);
+                assert!(matches!(test_pop.free_time_action(&data, &market), FreeTimeAction::End));
+            }
+
+            #[test]
+            pub fn take_the_time_to_use_goods_and_classes() {
+                let (mut data, mut market, test_pop) = setup();
+                data.add_good(Good::new(6, String::from("Bed"), String::new())
+                    .with_uses(2.0, HashMap::from([(3, 1.0)])));
+                data.add_good(Good::new(7, String::from("Stone"), String::new()));
+                data.add_good(Good::new(8, String::from("Rye"), String::new())
+                    .in_class(8)
+                    .with_consumption(1.0, HashMap::from([(3, 1.0)])));
+                data.add_good(Good::new(9, String::from("Wheat"), String::new())
+                    .in_class(8)
+                    .with_consumption(3.0, HashMap::from([(3, 1.0)])));
+                market.good_records.insert(8, GoodRecord::new().with_price(2.0));
+                market.good_records.insert(9, GoodRecord::new().with_price(1.0));
+                assert_eq!(test_pop.time_per_unit(Item::Good(5), &data, &market), 0.5);
+                assert_eq!(test_pop.time_per_unit(Item::Good(6), &data, &market), 2.0);
+                // only owned, so no time at all.
+                assert_eq!(test_pop.time_per_unit(Item::Good(7), &data, &market), 0.0);
+                // wheat is the cheaper of the class.
+                assert_eq!(test_pop.time_per_unit(Item::Class(8), &data, &market), 3.0);
+            }
+
+            #[test]
+            pub fn only_buy_what_it_has_time_for() {
+                let (data, market, mut test_pop) = setup();
+                test_pop.property.get_mut(&TIME_ID).unwrap().owned = 0.5;
+                match test_pop.free_time_action(&data, &market) {
+                    FreeTimeAction::BuyOrder { amount, .. } => assert_eq!(amount, 1.0),
+                    other => panic!("Expected BuyOrder, got {:?}", other),
+                }
+                assert_eq!(test_pop.property[&TIME_ID].available(), 0.0);
+            }
+
+            #[test]
+            pub fn migrate_when_market_cannot_fill_life_needs() {
+                let (data, market, mut test_pop) = setup();
+                assert!(matches!(test_pop.free_time_action(&data, &market), FreeTimeAction::BuyOrder { .. }));
+                // nothing came of the order, so it doesn't ask again.
+                match test_pop.free_time_action(&data, &market) {
+                    FreeTimeAction::SeekMigration(value) => assert_eq!(value, 0.1),
+                    other => panic!("Expected SeekMigration, got {:?}", other),
+                }
+                assert!(test_pop.pending_order.is_none());
+                assert_eq!(test_pop.property[&TIME_ID].available(), 10.0 - POP_PLANNING_TIME);
+                // it tries the market again between plans, until it's out of time.
+                let mut migrations = 1;
+                for _ in 0..20 {
+                    match test_pop.free_time_action(&data, &market) {
+                        FreeTimeAction::BuyOrder { .. } => {},
+                        FreeTimeAction::SeekMigration(_) => migrations += 1,
+                        FreeTimeAction::End => break,
+                        other => panic!("Expected BuyOrder or SeekMigration, got {:?}", other),
+                    }
+                }
+                assert_eq!(migrations, 10);
+                assert!(matches!(test_pop.free_time_action(&data, &market), FreeTimeAction::End));
+            }
+        }
+
+        mod build_savings_should {
+            use std::collections::{HashMap, HashSet};
+
+            use crate::{constants::TIME_ID, data::Data, good::Good, market::{GoodData, Market}, markethistory::MarketHistory, pop::Pop, popfinancials::PopFinancials, tests::fixtures::{flat_history, goods_data, owning_pop}};
+
+            fn setup() -> (Data, Market, MarketHistory, Pop) {
+                let mut data = goods_data(&[(5, "Silver"), (6, "Salt")]);
+                data.add_good(Good::new(7, String::from("Fish"), String::new())
+                    .with_decay_rate(0.5));
+                let market = Market {
+                    id: 0,
+                    name: String::from("Town"),
+                    connections: HashMap::new(),
+                    goods_info: HashMap::from([
+                        (5, GoodData { amv: 1.0, salability: 0.95 }),
+                        (6, GoodData { amv: 1.0, salability: 0.8 }),
+                        (7, GoodData { amv: 1.0, salability: 0.8 }),
+                    ]),
+                    monies: HashSet::from([5]),
+                    good_trade_priority: vec![5, 6, 7],
+                    pops: HashSet::from([0]),
+                    jobs: HashSet::new(),
+                    merchants: HashSet::new(),
+                    unclaimed: HashMap::new(),
+                };
+                let history = flat_history(&[TIME_ID, 5, 6, 7]);
+                let mut test_pop = owning_pop(&[(TIME_ID, 10.0), (5, 2.0), (6, 4.0), (7, 4.0)]);
+                test_pop.financials.curr_saving_rate = 0.5;
+                (data, market, history, test_pop)
+            }
 
-            use crate::{data::Data, demandcurve::DemandCurve, desire::Desire, good::Good, item::Item, markethistory::{GoodRecord, MarketHistory}, offerresult::{AcceptReason, OfferResult, RejectReason}, pop::{Pop, PropertyRecord}};
+            #[test]
+            pub fn value_goods_at_expected_prices() {
+                let (data, market, history, mut test_pop) = setup();
+                // silver is expected to be worth 3, not 1.
+                test_pop.prices.prices.insert(5, 3.0);
+                test_pop.build_savings(&data, &market, &history);
+                // 7 of 14 AMV saved, all the silver then a quarter of the salt.
+                assert_eq!(test_pop.property[&5].saved, 2.0);
+                assert_eq!(test_pop.property[&6].saved, 1.0);
+                assert_eq!(test_pop.property[&7].saved, 0.0);
+                assert_eq!(test_pop.financials.saved, 7.0);
+            }
 
             #[test]
-            pub fn reject_offer_due_to_hard_threshold() {
-                // Set up Data
-                let mut data = Data::new();
-                data.goods.insert(2, Good::new(2, "2".to_string(), String::from("")));
-                data.goods.insert(3, Good::new(3, "3".to_string(), String::from("")));
-                data.goods.insert(4, Good::new(4, "4".to_string(), String::from("")));
-                data.goods.insert(5, Good::new(5, "5".to_string(), String::from("")));
-                data.goods.insert(6, Good::new(6, "6".to_string(), String::from("")));
-                data.goods.insert(7, Good::new(7, "7".to_string(), String::from("")));
-                // Set up good prices.
-                let mut market = MarketHistory::new();
-                market.good_records.insert(2, GoodRecord::new().with_price(1.0));
-                market.good_records.insert(3, GoodRecord::new().with_price(1.0));
-                market.good_records.insert(4, GoodRecord::new().with_price(1.0));
-                market.good_records.insert(5, GoodRecord::new().with_price(1.0));
-                market.good_records.insert(6, GoodRecord::new().with_price(1.0));
-                market.good_records.insert(7, GoodRecord::new().with_price(1.0));
+            pub fn save_monies_first_then_salable_durable_goods() {
+                let (data, market, history, mut test_pop) = setup();
+                test_pop.build_savings(&data, &market, &history);
+                // 5 of 10 AMV saved, time doesn't count.
+                assert_eq!(test_pop.property[&5].saved, 2.0);
+                assert_eq!(test_pop.property[&6].saved, 3.0);
+                assert_eq!(test_pop.property[&7].saved, 0.0);
+                assert_eq!(test_pop.property[&TIME_ID].saved, 0.0);
+                assert_eq!(test_pop.financials.saved, 5.0);
+
+                // already at target, nothing changes.
+                test_pop.build_savings(&data, &market, &history);
+                assert_eq!(test_pop.property[&6].saved, 3.0);
+                assert_eq!(test_pop.financials.saved, 5.0);
+            }
 
-                // Set up desires.
-                // Good 3,  2x, 1-3(5)
-                // Good 4,  2x, 2-6(10)
-                // Good 7, inf, 0 -> 20n
-                let mut test_pop = Pop::new(0, 0, 0);
-                test_pop.desires.push_back(Desire::new(Item::Good(3), 1.0, 1.0, 
-                    DemandCurve::Linear { slope: 2.0 })
-                    .with_steps(2));
-                test_pop.desires.push_back(Desire::new(Item::Good(4), 1.0, 2.0, 
-                    DemandCurve::Linear { slope: 4.0 })
-                    .with_steps(2));
-                test_pop.desires.push_back(Desire::new(Item::Good(7), 1.0, 0.0, 
-                    DemandCurve::Linear { slope: 20.0 })
-                    .with_steps(0));
-                // Add in enough property for our needs.
-                test_pop.property.insert(2, PropertyRecord::new(2.0));
-                test_pop.property.insert(5, PropertyRecord::new(2.0));
-                test_pop.property.insert(6, PropertyRecord::new(2.0));
-                test_pop.try_satisfy_all_desires(&data, &market);
+            #[test]
+            pub fn count_existing_savings_towards_target() {
+                let (data, market, history, mut test_pop) = setup();
+                // stockpiled fish.
+                test_pop.property.get_mut(&7).unwrap().saved = 2.0;
+                // reserved goods aren't part of the savings base.
+                test_pop.property.get_mut(&6).unwrap().reserved = 2.0;
+                test_pop.build_savings(&data, &market, &history);
+                // 8 AMV unreserved, 4 to save, 2 already saved.
+                assert_eq!(test_pop.property[&5].saved, 2.0);
+                assert_eq!(test_pop.property[&6].saved, 0.0);
+                assert_eq!(test_pop.property[&7].saved, 2.0);
+                assert_eq!(test_pop.financials.saved, 4.0);
+            }
 
-                let mut request = HashMap::new();
-                request.insert(2, 5.0);
+            #[test]
+            pub fn save_with_default_financials_after_a_day() {
+                let (data, market, history, mut test_pop) = setup();
+                test_pop.financials = PopFinancials::new();
+                test_pop.day_end(&data, None, &history);
+                assert!(test_pop.financials.curr_saving_rate > 0.0);
+                test_pop.build_savings(&data, &market, &history);
+                assert!(test_pop.financials.saved > 0.0);
+                assert_eq!(test_pop.property[&5].saved, test_pop.financials.saved);
+            }
+        }
 
-                let mut offer = HashMap::new();
-                offer.insert(3, 1.0);
+        mod plan_stockpiles_should {
+            use std::collections::HashMap;
 
-                let result = test_pop.check_offer(&request, &offer, &data, &market);
-                assert_eq!(result, OfferResult::Reject(RejectReason::HardThresholdFailure));
+            use crate::{constants::TIME_ID, data::Data, demandcurve::DemandCurve, desire::{Desire, DesireTag}, freetimeaction::FreeTimeAction, good::Good, household::Household, item::Item, markethistory::{GoodRecord, MarketHistory}, pop::{Pop, PropertyRecord}, tests::fixtures::{add_humans, flat_history, goods_data, human_pop}, want::Want};
+
+            fn setup(decay_rate: f64) -> (Data, MarketHistory, Pop) {
+                let mut data = goods_data(&[(6, "Cloth")]);
+                data.add_good(Good::new(5, String::from("Grain"), String::new())
+                    .with_decay_rate(decay_rate));
+                add_humans(&mut data, vec![
+                    Desire::new(Item::Good(5), 1.0, 10.0, DemandCurve::linear(-1.0))
+                        .with_tag(DesireTag::life_need(0.1)),
+                    // no end, so not planned for.
+                    Desire::new(Item::Good(6), 1.0, 5.0, DemandCurve::linear(-1.0))
+                        .with_steps(0),
+                ]);
+                let market = flat_history(&[5, 6]);
+
+                // 1 household of 2 people.
+                let mut test_pop = human_pop(&data, 0, vec![Household::new(1.0, 2.0, 0.0, 0.0)]);
+                test_pop.financials.stockpile_days = 3;
+                (data, market, test_pop)
             }
 
             #[test]
-            pub fn reject_offer_for_no_positive_benefit_amv_neutral() {
-                // Set up Data
-                let mut data = Data::new();
-                data.goods.insert(2, Good::new(2, "2".to_string(), String::from("")));
-                data.goods.insert(3, Good::new(3, "3".to_string(), String::from("")));
-                data.goods.insert(4, Good::new(4, "4".to_string(), String::from("")));
-                data.goods.insert(5, Good::new(5, "5".to_string(), String::from("")));
-                data.goods.insert(6, Good::new(6, "6".to_string(), String::from("")));
-                data.goods.insert(7, Good::new(7, "7".to_string(), String::from("")));
-                // Set up good prices.
-                let mut market = MarketHistory::new();
-                market.good_records.insert(2, GoodRecord::new().with_price(1.0));
-                market.good_records.insert(3, GoodRecord::new().with_price(1.0));
-                market.good_records.insert(4, GoodRecord::new().with_price(1.0));
-                market.good_records.insert(5, GoodRecord::new().with_price(1.0));
-                market.good_records.insert(6, GoodRecord::new().with_price(1.0));
-                market.good_records.insert(7, GoodRecord::new().with_price(1.0));
+            pub fn save_for_days_ahead() {
+                let (data, market, mut test_pop) = setup(0.0);
+                test_pop.property.insert(5, PropertyRecord::new(10.0));
+                test_pop.property.insert(6, PropertyRecord::new(10.0));
+                test_pop.try_satisfy_all_desires(&data, &market);
+                test_pop.plan_stockpiles(&data, &market);
+
+                // 2 a day for 3 days, on top of today's 2.
+                let grain = test_pop.property[&5];
+                assert_eq!(grain.reserved, 2.0);
+                assert_eq!(grain.saved, 6.0);
+                assert_eq!(grain.target, 6.0);
+                assert_eq!(grain.available(), 2.0);
+                let cloth = test_pop.property[&6];
+                assert_eq!(cloth.saved, 0.0);
+                assert_eq!(cloth.target, 0.0);
+
+                // short on grain, save what we can and aim to buy the rest.
+                test_pop.reset_property();
+                test_pop.property.get_mut(&5).unwrap().owned = 5.0;
+                test_pop.release_stockpiles();
+                test_pop.try_satisfy_all_desires(&data, &market);
+                test_pop.plan_stockpiles(&data, &market);
+                let grain = test_pop.property[&5];
+                assert_eq!(grain.reserved, 2.0);
+                assert_eq!(grain.saved, 3.0);
+                assert_eq!(grain.target, 6.0);
+                assert_eq!(test_pop.stockpile_shortfall(&data, &market), Some((Item::Good(5), 3.0)));
+            }
 
-                // Set up desires.
-                // Good 3,  2x, 1-3(5)
-                // Good 4,  2x, 2-6(10)
-                // Good 7, inf, 0 -> 20n
-                let mut test_pop = Pop::new(0, 0, 0);
-                test_pop.desires.push_back(Desire::new(Item::Good(3), 1.0, 1.0, 
-                    DemandCurve::Linear { slope: 2.0 })
-                    .with_steps(2));
-                test_pop.desires.push_back(Desire::new(Item::Good(4), 1.0, 2.0, 
-                    DemandCurve::Linear { slope: 4.0 })
-                    .with_steps(2));
-                test_pop.desires.push_back(Desire::new(Item::Good(7), 1.0, 0.0, 
-                    DemandCurve::Linear { slope: 20.0 })
-                    .with_steps(0));
-                // Add in enough property for our needs.
-                test_pop.property.insert(2, PropertyRecord::new(2.0));
-                test_pop.property.insert(5, PropertyRecord::new(2.0));
-                test_pop.property.insert(6, PropertyRecord::new(2.0));
+            #[test]
+            pub fn save_less_when_goods_decay_or_future_is_discounted() {
+                let (data, market, mut test_pop) = setup(0.5);
+                test_pop.property.insert(5, PropertyRecord::new(10.0));
                 test_pop.try_satisfy_all_desires(&data, &market);
+                test_pop.plan_stockpiles(&data, &market);
+                // 2 * (0.5 + 0.25 + 0.125)
+                assert_eq!(test_pop.property[&5].saved, 1.75);
 
-                let mut request = HashMap::new();
-                request.insert(2, 1.0);
+                let (data, market, mut test_pop) = setup(0.0);
+                test_pop.financials.time_preference = 1.0;
+                test_pop.property.insert(5, PropertyRecord::new(10.0));
+                test_pop.try_satisfy_all_desires(&data, &market);
+                test_pop.plan_stockpiles(&data, &market);
+                assert_eq!(test_pop.property[&5].saved, 1.75);
 
-                let mut offer = HashMap::new();
-                offer.insert(5, 1.0);
+                // services can't be stockpiled at all.
+                let (data, market, mut test_pop) = setup(1.0);
+                test_pop.property.insert(5, PropertyRecord::new(10.0));
+                test_pop.try_satisfy_all_desires(&data, &market);
+                test_pop.plan_stockpiles(&data, &market);
+                assert_eq!(test_pop.property[&5].saved, 0.0);
+            }
 
-                let result = test_pop.check_offer(&request, &offer, &data, &market);
-                assert_eq!(result, OfferResult::Reject(RejectReason::NotAccepted));
+            #[test]
+            pub fn replace_the_last_plan_and_keep_other_targets() {
+                let (data, market, mut test_pop) = setup(0.0);
+                test_pop.property.insert(5, PropertyRecord::new(10.0));
+                test_pop.property.get_mut(&5).unwrap().target = 1.0;
+                test_pop.try_satisfy_all_desires(&data, &market);
+                test_pop.plan_stockpiles(&data, &market);
+                assert_eq!(test_pop.property[&5].target, 7.0);
+                assert_eq!(test_pop.property[&5].planned, 6.0);
+
+                // planning again doesn't pile up.
+                test_pop.release_stockpiles();
+                test_pop.plan_stockpiles(&data, &market);
+                assert_eq!(test_pop.property[&5].target, 7.0);
+                assert_eq!(test_pop.property[&5].saved, 6.0);
             }
 
             #[test]
-            pub fn accept_offer_because_of_satisfaction_gain() {
-                // Set up Data
+            pub fn plan_for_classes_and_wants_through_their_goods() {
                 let mut data = Data::new();
-                data.goods.insert(2, Good::new(2, "2".to_string(), String::from("")));
-                data.goods.insert(3, Good::new(3, "3".to_string(), String::from("")));
-                data.goods.insert(4, Good::new(4, "4".to_string(), String::from("")));
-                data.goods.insert(5, Good::new(5, "5".to_string(), String::from("")));
-                data.goods.insert(6, Good::new(6, "6".to_string(), String::from("")));
-                data.goods.insert(7, Good::new(7, "7".to_string(), String::from("")));
-                // Set up good prices.
+                data.add_time();
+                data.wants.insert(3, Want::new(3, String::from("Warmth")));
+                let mut warmth = HashMap::new();
+                warmth.insert(3, 2.0);
+                data.add_good(Good::new(5, String::from("Wheat"), String::new())
+                    .in_class(5));
+                data.add_good(Good::new(6, String::from("Rye"), String::new())
+                    .in_class(5));
+                data.add_good(Good::new(7, String::from("Firewood"), String::new())
+                    .with_consumption(0.0, warmth));
                 let mut market = MarketHistory::new();
-                market.good_records.insert(2, GoodRecord::new().with_price(1.0));
-                market.good_records.insert(3, GoodRecord::new().with_price(1.0));
-                market.good_records.insert(4, GoodRecord::new().with_price(1.0));
-                market.good_records.insert(5, GoodRecord::new().with_price(1.0));
+                market.good_records.insert(5, GoodRecord::new().with_price(2.0));
                 market.good_records.insert(6, GoodRecord::new().with_price(1.0));
                 market.good_records.insert(7, GoodRecord::new().with_price(1.0));
 
-                // Set up desires.
-                // Good 3,  2x, 1-3(5)
-                // Good 4,  2x, 2-6(10)
-                // Good 7, inf, 0 -> 20n
                 let mut test_pop = Pop::new(0, 0, 0);
-                test_pop.desires.push_back(Desire::new(Item::Good(3), 1.0, 1.0, 
-                    DemandCurve::Linear { slope: 2.0 })
-                    .with_steps(2));
-                test_pop.desires.push_back(Desire::new(Item::Good(4), 1.0, 2.0, 
-                    DemandCurve::Linear { slope: 4.0 })
-                    .with_steps(2));
-                test_pop.desires.push_back(Desire::new(Item::Good(7), 1.0, 0.0, 
-                    DemandCurve::Linear { slope: 20.0 })
-                    .with_steps(0));
-                // Add in enough property for our needs.
-                test_pop.property.insert(2, PropertyRecord::new(2.0));
+                test_pop.desires.push_back(Desire::new(Item::Class(5), 2.0, 10.0, DemandCurve::linear(-1.0))
+                    .with_steps(1));
+                test_pop.desires.push_back(Desire::new(Item::Want(3), 4.0, 5.0, DemandCurve::linear(-1.0))
+                    .with_steps(1));
+                test_pop.financials.stockpile_days = 1;
+
+                // owns none of the class, so the cheapest member.
+                assert_eq!(test_pop.stockpile_needs(&data, &market), vec![(6, 2.0), (7, 2.0)]);
+                // otherwise, what it already has.
+                test_pop.property.insert(5, PropertyRecord::new(3.0));
+                assert_eq!(test_pop.stockpile_needs(&data, &market), vec![(5, 2.0), (7, 2.0)]);
+
+                test_pop.plan_stockpiles(&data, &market);
+                assert_eq!(test_pop.property[&5].saved, 2.0);
+                assert_eq!(test_pop.property[&7].target, 2.0);
+                assert_eq!(test_pop.property[&7].saved, 0.0);
+            }
+
+            #[test]
+            pub fn buy_life_needs_short_in_stockpiles_first() {
+                let (data, mut market, mut test_pop) = setup(0.0);
+                market.good_records.insert(TIME_ID, GoodRecord::new().with_price(1.0));
+                test_pop.desires.iter_mut().find(|x| x.item == Item::Good(6)).unwrap()
+                    .steps = std::num::NonZero::new(1);
+                // cloth comes first in our desires, but grain is a LifeNeed.
+                test_pop.desires.make_contiguous().sort_by_key(|x| x.item != Item::Good(6));
+                assert_eq!(test_pop.stockpile_needs(&data, &market), vec![(5, 2.0), (6, 2.0)]);
+
+                // enough for today, nothing saved.
+                test_pop.property.insert(TIME_ID, PropertyRecord::new(10.0));
                 test_pop.property.insert(5, PropertyRecord::new(2.0));
                 test_pop.property.insert(6, PropertyRecord::new(2.0));
                 test_pop.try_satisfy_all_desires(&data, &market);
+                test_pop.plan_stockpiles(&data, &market);
+                assert!(test_pop.working_desires.is_empty());
 
-                let mut request = HashMap::new();
-                request.insert(2, 2.0);
-
-                let mut offer = HashMap::new();
-                offer.insert(4, 2.0);
-
-                let result = test_pop.check_offer(&request, &offer, &data, &market);
-                assert_eq!(result, OfferResult::Accept(AcceptReason::Satisfaction));
+                let action = test_pop.free_time_action(&data, &market);
+                assert!(matches!(action, FreeTimeAction::BuyOrder { good: Item::Good(5), amount } if amount == 6.0));
             }
+        }
 
-            #[test]
-            pub fn accept_offer_because_of_density_gain() {
-                // Set up Data
-                let mut data = Data::new();
-                data.goods.insert(2, Good::new(2, "2".to_string(), String::from("")));
-                data.goods.insert(3, Good::new(3, "3".to_string(), String::from("")));
-                data.goods.insert(4, Good::new(4, "4".to_string(), String::from("")));
-                data.goods.insert(5, Good::new(5, "5".to_string(), String::from("")));
-                data.goods.insert(6, Good::new(6, "6".to_string(), String::from("")));
-                data.goods.insert(7, Good::new(7, "7".to_string(), String::from("")));
-                // Set up good prices.
-                let mut market = MarketHistory::new();
-                market.good_records.insert(2, GoodRecord::new().with_price(1.0));
-                market.good_records.insert(3, GoodRecord::new().with_price(1.0));
-                market.good_records.insert(4, GoodRecord::new().with_price(1.0));
-                market.good_records.insert(5, GoodRecord::new().with_price(1.0));
-                market.good_records.insert(6, GoodRecord::new().with_price(1.0));
-                market.good_records.insert(7, GoodRecord::new().with_price(1.0));
+        mod explain_offer_should {
+            use std::collections::HashMap;
 
-                // Set up desires.
-                // Good 3,  2x, 1-3(5)
-                // Good 4,  2x, 2-6(10)
-                // Good 7, inf, 0 -> 20n
-                let mut test_pop = Pop::new(0, 0, 0);
-                test_pop.desires.push_back(Desire::new(Item::Good(3), 1.0, 1.0, 
-                    DemandCurve::Linear { slope: 2.0 })
-                    .with_steps(2));
-                test_pop.desires.push_back(Desire::new(Item::Good(4), 1.0, 2.0, 
-                    DemandCurve::Linear { slope: 4.0 })
-                    .with_steps(2));
-                test_pop.desires.push_back(Desire::new(Item::Good(7), 1.0, 0.0, 
-                    DemandCurve::Linear { slope: 20.0 })
+            use crate::{data::Data, demandcurve::DemandCurve, desire::Desire, item::Item, markethistory::MarketHistory, offerresult::{AcceptReason, OfferResult, RejectReason}, pop::Pop, tests::fixtures::{flat_history, goods_data, owning_pop}};
+
+            fn setup() -> (Data, MarketHistory, Pop) {
+                let data = goods_data(&[(3, "3"), (4, "4")]);
+                let market = flat_history(&[3, 4]);
+                let mut test_pop = owning_pop(&[(3, 2.0)]);
+                test_pop.desires.push_back(Desire::new(Item::Good(3), 1.0, 10.0,
+                    DemandCurve::linear(-1.0))
+                    .with_steps(0));
+                test_pop.desires.push_back(Desire::new(Item::Good(4), 1.0, 8.0,
+                    DemandCurve::linear(-1.0))
                     .with_steps(0));
-                // Add in enough property for our needs.
-                test_pop.property.insert(3, PropertyRecord::new(1.0));
-                test_pop.property.insert(4, PropertyRecord::new(1.0));
-                test_pop.property.insert(7, PropertyRecord::new(3.0));
                 test_pop.try_satisfy_all_desires(&data, &market);
+                (data, market, test_pop)
+            }
 
-                let mut request = HashMap::new();
-                request.insert(7, 3.0);
+            #[test]
+            pub fn record_ranges_gained_and_lost() {
+                let (data, market, test_pop) = setup();
+                assert_eq!(test_pop.satisfaction.ranges, vec![(10.0, 8.0)]);
 
+                let mut request = HashMap::new();
+                request.insert(3, 1.0);
                 let mut offer = HashMap::new();
-                offer.insert(3, 1.0);
+                offer.insert(4, 2.0);
 
-                let result = test_pop.check_offer(&request, &offer, &data, &market);
-                assert_eq!(result, OfferResult::Accept(AcceptReason::Density));
+                let result = test_pop.explain_offer(&request, &offer, &data, &market);
+                assert_eq!(result.result, OfferResult::Accept(AcceptReason::Satisfaction));
+                assert_eq!(result.amv_gain, 2.0);
+                assert_eq!(result.amv_loss, 1.0);
+                let change = result.change.unwrap();
+                assert_eq!(change.ranges, vec![(8.0, 6.0)]);
+                assert_eq!(change.lost_ranges, vec![(9.0, 8.0)]);
+
+                let text = format!("{}", test_pop.explain_offer(&request, &offer, &data, &market));
+                assert!(text.contains("Gained: 8 to 6"));
+                assert!(text.contains("Lost: 9 to 8"));
             }
 
             #[test]
-            pub fn accept_offer_because_of_amv_gain() {
-                // Set up Data
-                let mut data = Data::new();
-                data.goods.insert(2, Good::new(2, "2".to_string(), String::from("")));
-                data.goods.insert(3, Good::new(3, "3".to_string(), String::from("")));
-                data.goods.insert(4, Good::new(4, "4".to_string(), String::from("")));
-                data.goods.insert(5, Good::new(5, "5".to_string(), String::from("")));
-                data.goods.insert(6, Good::new(6, "6".to_string(), String::from("")));
-                data.goods.insert(7, Good::new(7, "7".to_string(), String::from("")));
-                // Set up good prices.
-                let mut market = MarketHistory::new();
-                market.good_records.insert(2, GoodRecord::new().with_price(1.0));
-                market.good_records.insert(3, GoodRecord::new().with_price(1.0));
-                market.good_records.insert(4, GoodRecord::new().with_price(1.0));
-                market.good_records.insert(5, GoodRecord::new().with_price(1.0));
-                market.good_records.insert(6, GoodRecord::new().with_price(1.0));
-                market.good_records.insert(7, GoodRecord::new().with_price(1.0));
-
-                // Set up desires.
-                // Good 3,  2x, 1-3(5)
-                // Good 4,  2x, 2-6(10)
-                // Good 7, inf, 0 -> 20n
-                let mut test_pop = Pop::new(0, 0, 0);
-                test_pop.desires.push_back(Desire::new(Item::Good(3), 1.0, 1.0, 
-                    DemandCurve::Linear { slope: 2.0 })
-                    .with_steps(2));
-                test_pop.desires.push_back(Desire::new(Item::Good(4), 1.0, 2.0, 
-                    DemandCurve::Linear { slope: 4.0 })
-                    .with_steps(2));
-                test_pop.desires.push_back(Desire::new(Item::Good(7), 1.0, 0.0, 
-                    DemandCurve::Linear { slope: 20.0 })
-                    .with_steps(0));
-                // Add in enough property for our needs.
-                test_pop.property.insert(5, PropertyRecord::new(1.0));
-                test_pop.property.insert(6, PropertyRecord::new(1.0));
-                test_pop.property.insert(7, PropertyRecord::new(3.0));
-                test_pop.try_satisfy_all_desires(&data, &market);
-                println!("Base Satisfaction.");
-                println!("Range: {}", test_pop.satisfaction.value);
-                println!("Steps: {}", test_pop.satisfaction.steps);
-                println!("Satisfaction: {}", test_pop.satisfaction.satisfaction);
-                println!("AMV: {}", test_pop.satisfaction.excess_amv);
+            pub fn skip_satisfaction_when_below_hard_threshold() {
+                let (data, market, test_pop) = setup();
 
                 let mut request = HashMap::new();
-                request.insert(5, 1.0);
-
+                request.insert(3, 2.0);
                 let mut offer = HashMap::new();
-                offer.insert(6, 2.0);
+                offer.insert(4, 0.1);
 
-                let result = test_pop.check_offer(&request, &offer, &data, &market);
-                assert_eq!(result, OfferResult::Accept(AcceptReason::AMV));
+                let result = test_pop.explain_offer(&request, &offer, &data, &market);
+                assert_eq!(result.result, OfferResult::Reject(RejectReason::HardThresholdFailure));
+                assert!(result.change.is_none());
             }
         }
 
@@ -2640,6 +5447,49 @@ mod tests {
             }
         }
 
+        mod update_desires_full_should {
+            use crate::{culture::Culture, data::Data, demandcurve::DemandCurve, desire::Desire, drow::DRow, good::Good, household::HouseholdMod, item::Item, layer::Layer, pop::Pop, species::Species};
+
+            #[test]
+            pub fn merge_desires_and_households_from_layers() {
+                let mut data = Data::new();
+                data.add_good(Good::new(4, String::from("Bread"), String::new()));
+                data.add_good(Good::new(5, String::from("Incense"), String::new()));
+                data.add_good(Good::new(6, String::from("Tools"), String::new()));
+                data.species.insert(0, Species::new(0, String::from("Human"))
+                    .with_desire(Desire::new(Item::Good(4), 1.0, 10.0, DemandCurve::linear(-1.0))));
+                data.culture.insert(0, Culture::new(0, String::from("Farmer")));
+                data.try_add_layer(Layer::new(0, String::from("Sun Worship"), String::from("Religion"))
+                    .with_household_mod(HouseholdMod { adults: 0.0, elders: 0.0, children: 1.0 })
+                    .with_desire(Desire::new(Item::Good(5), 1.0, 5.0, DemandCurve::linear(-1.0))))
+                    .unwrap();
+                data.try_add_layer(Layer::new(1, String::from("Moon Worship"), String::from("Religion")))
+                    .unwrap();
+                data.try_add_layer(Layer::new(2, String::from("Smith"), String::from("Profession"))
+                    .with_desire(Desire::new(Item::Good(6), 2.0, 3.0, DemandCurve::linear(-1.0))))
+                    .unwrap();
+
+                let mut row = DRow::new(1.0, 0).has_culture(0);
+                assert_eq!(row.set_layer(1, &data), None);
+                assert_eq!(row.set_layer(2, &data), None);
+                // replaces the other religion.
+                assert_eq!(row.set_layer(0, &data), Some(1));
+                assert_eq!(row.layers, vec![0, 2]);
+                row.update_household(&data);
+
+                let mut test_pop = Pop::new(0, 0, 0).add_demo(row);
+                test_pop.combine_households(&data);
+                test_pop.update_desires_full(&data);
+                let expected_size = Species::new(0, String::new()).household_mod.net_change() + 1.0;
+                assert_eq!(test_pop.households.household_size, expected_size);
+                assert_eq!(test_pop.desires.len(), 3);
+                let amount_of = |item: Item| test_pop.desires.iter().find(|x| x.item == item).unwrap().amount;
+                assert_eq!(amount_of(Item::Good(4)), expected_size);
+                assert_eq!(amount_of(Item::Good(5)), expected_size);
+                assert_eq!(amount_of(Item::Good(6)), 2.0 * expected_size);
+            }
+        }
+
         mod integrate_desires_should {
             use crate::{demandcurve::DemandCurve, desire::{Desire, DesireTag}, drow::DRow, household::{Household, HouseholdMember}, item::Item, pop::Pop};
 
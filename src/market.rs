@@ -35,6 +35,10 @@ pub struct Market {
     /// This may be one merchant job for an entire market or multiple, one 
     /// for each connection the market has, this is to be determined.
     pub merchants: HashSet<usize>,
+
+    /// Goods in the market which no one owns, such as the estates of pops 
+    /// which died out with no other heir.
+    pub unclaimed: HashMap<usize, f64>,
 }
 
 impl Market {
@@ -1,8 +1,11 @@
+use std::fmt;
+
+use crate::pop::SatisfactionValues;
 
 /// # Offer Result
 /// 
 /// Offer result is a shared output for offers and 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum OfferResult {
     /// Accept, no further work needs done.
     Accept(AcceptReason),
@@ -43,7 +46,7 @@ pub enum OfferResult {
 /// The reason for why an offer was rejected. 
 /// 
 /// Used for both testing and updating AMV based on this reason.
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum RejectReason {
     /// Rejected because the AMV loss was way to large relative to 
     /// the AMV gained from the offer. 
@@ -55,6 +58,8 @@ pub enum RejectReason {
     /// 
     /// Failed to increase Satisfaction, Density, or AMV
     NotAccepted,
+    /// Rejected because the pop is a slave and cannot trade away anything it has.
+    Enslaved,
 }
 
 /// # Accept Reason
@@ -63,7 +68,7 @@ pub enum RejectReason {
 /// 
 /// Currently for Logging and Testing purposes, but could also be used for market
 /// adjustment as well.
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum AcceptReason {
     /// The Price Hint offered was met, any more detailed reason is lost on us.
     /// 
@@ -96,4 +101,42 @@ pub enum AcceptReason {
     /// 
     /// The change in value should be small (1/4th standard rate or something).
     AMV,
-}
\ No newline at end of file
+}
+
+/// # Trade Explanation
+/// 
+/// Why a pop accepted or rejected an offer, made by Pop::explain_offer.
+/// 
+/// Includes the AMV on both sides and how satisfaction would have changed,
+/// including which desire ranges would have been gained and lost.
+#[derive(Debug, Clone)]
+pub struct TradeExplanation {
+    /// The result of the offer.
+    pub result: OfferResult,
+    /// The AMV of the goods offered to us.
+    pub amv_gain: f64,
+    /// The AMV of the goods requested from us.
+    pub amv_loss: f64,
+    /// How satisfaction would change.
+    /// 
+    /// None if the offer was rejected before satisfaction was checked.
+    pub change: Option<SatisfactionValues>,
+}
+
+impl fmt::Display for TradeExplanation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Result: {:?}", self.result)?;
+        writeln!(f, "AMV: +{} / -{}", self.amv_gain, self.amv_loss)?;
+        if let Some(change) = &self.change {
+            writeln!(f, "Satisfaction: {} (steps {}, value {}, excess AMV {})", 
+                change.normalized, change.steps, change.value, change.excess_amv)?;
+            for (from, to) in change.ranges.iter() {
+                writeln!(f, "Gained: {} to {}", from, to)?;
+            }
+            for (from, to) in change.lost_ranges.iter() {
+                writeln!(f, "Lost: {} to {}", from, to)?;
+            }
+        }
+        Ok(())
+    }
+}
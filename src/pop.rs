@@ -4,7 +4,7 @@ use std::{collections::{HashMap, HashSet, VecDeque}, thread::current};
 use itertools::Itertools;
 use ordered_float::Float;
 
-use crate::{constants::POP_AMV_HARD_LOSS_THRESHOLD, data::Data, desire::{Desire, DesireTag}, drow::DRow, firm::Firm, freetimeaction::FreeTimeAction, household::Household, item::Item, market::Market, markethistory::MarketHistory, offerresult::{AcceptReason, OfferResult, RejectReason}, popfinancials::PopFinancials};
+use crate::{constants::{DAYS_PER_TURN, POP_DESIRE_REBUILD_THRESHOLD, POP_GROWTH_PERIOD, POP_PLANNING_TIME, POSITIONAL_MINIMUM_REFERENCE, SAVINGS_TIERS}, data::Data, desire::{Desire, DesireTag, PositionalMeasure}, drow::DRow, firm::{Firm, WorkerInfo, WorkerType}, freetimeaction::FreeTimeAction, habit::Habit, household::Household, inheritance::{Heir, Inheritance}, item::Item, market::Market, markethistory::MarketHistory, offerresult::{AcceptReason, OfferResult, RejectReason, TradeExplanation}, popfinancials::PopFinancials, priceexpectations::PriceExpectations, want::WantSource};
 
 
 use crate::constants::TIME_ID;
@@ -46,6 +46,9 @@ pub struct Pop {
     /// 
     /// This is sorted by household count, largest to smallest.
     pub demo_breakdown: Vec<DRow>,
+    /// The population the pop's desires were last built for, see 
+    /// update_desires_full().
+    pub desire_population: f64,
     /// How many days worth of work a single household in the group does.
     /// 
     /// IE. If a household has only 1 working adult is has an efficiency of 1.0, if it
@@ -70,13 +73,28 @@ pub struct Pop {
     pub working_desires: VecDeque<Desire>,
     /// The current satisfaction of the pop. Should be updated periodically.
     pub satisfaction: SatisfactionValues,
+    /// The pop's memory of how well it's habituating desires have been satisfied
+    /// and how much they have changed because of it.
+    pub habits: HashMap<Item, Habit>,
+    /// The per person reference each positional desire is currently scaled to,
+    /// taken from the other pops in the market.
+    pub positions: HashMap<Item, f64>,
+    /// The item last ordered in free_time_action and the time set aside to make 
+    /// use of it.
+    pub pending_order: Option<(Item, f64)>,
+    /// What the pop expects goods to be worth, used instead of market prices 
+    /// when valuing it's goods and trades.
+    pub prices: PriceExpectations,
+    /// Who inherits the pop's property should it die out entirely. If None, 
+    /// it's market does.
+    pub heir: Option<Heir>,
 
     /// The Financials of a Pop. used to store and oragnize it's financial data.
     pub financials: PopFinancials,
 
     /// The tags for the pop, defining any special properties of the pop and it's actions.
     /// 
-    /// Currently a placeholder for later purposes like denoting pops as slaves, or so on.
+    /// See PopTag for what each does.
     pub tags: HashSet<PopTag>
 }
 
@@ -88,12 +106,18 @@ impl Pop {
             firm,
             households: Household::zeroed_household(),
             demo_breakdown: vec![],
+            desire_population: 0.0,
             efficiency: 1.0,
             desires: VecDeque::new(),
             property: HashMap::new(),
             wants: HashMap::new(),
             working_desires: VecDeque::new(),
             satisfaction: SatisfactionValues::zero(),
+            habits: HashMap::new(),
+            positions: HashMap::new(),
+            pending_order: None,
+            prices: PriceExpectations::new(id),
+            heir: None,
             tags: HashSet::new(),
             financials: PopFinancials::new(),
         }
@@ -105,7 +129,7 @@ impl Pop {
     /// 
     /// This does not update desires. Do that separately.
     pub fn add_demo(mut self, demo: DRow) -> Self {
-        match self.demo_breakdown.binary_search_by(|x| x.household.count.total_cmp(&demo.household.count)) {
+        match self.demo_breakdown.binary_search_by(|x| demo.household.count.total_cmp(&x.household.count)) {
             Ok(pos) | 
             Err(pos) => self.demo_breakdown.insert(pos, demo),
         }
@@ -116,7 +140,7 @@ impl Pop {
     /// 
     /// Adds a demographic row to the demographic breakdown in proper ordering.
     pub fn include_demo(&mut self, demo: DRow) {
-        match self.demo_breakdown.binary_search_by(|x| x.household.count.total_cmp(&demo.household.count)) {
+        match self.demo_breakdown.binary_search_by(|x| demo.household.count.total_cmp(&x.household.count)) {
             Ok(pos) | 
             Err(pos) => self.demo_breakdown.insert(pos, demo),
         }
@@ -130,8 +154,193 @@ impl Pop {
     pub fn combine_households(&mut self, _data: &Data) {
         self.households = Household::zeroed_household();
         for row in self.demo_breakdown.iter() {
-            self.households.combine(&row.household);
+            if row.household.count > 0.0 {
+                self.households = self.households.combine(&row.household);
+            }
+        }
+    }
+
+    /// # Split Fraction
+    /// 
+    /// Splits fraction (0.0 to 1.0) of every demographic row off into a new 
+    /// pop, such as for a group migrating away. See split_off().
+    /// 
+    /// # Errors
+    /// 
+    /// If fraction isn't between 0.0 and 1.0, exclusive, we are left unchanged.
+    pub fn split_fraction(&mut self, new_id: usize, fraction: f64, data: &Data) -> Result<Pop, String> {
+        if !(fraction > 0.0 && fraction < 1.0) {
+            return Err(format!("Fraction {} must be between 0.0 and 1.0, exclusive.", fraction));
+        }
+        let rows = self.demo_breakdown.iter_mut()
+            .map(|row| {
+                let mut taken = row.clone();
+                taken.household.count = row.household.count * fraction;
+                row.household.count -= taken.household.count;
+                taken
+            })
+            .collect_vec();
+        Ok(self.split_off(new_id, rows, fraction, data))
+    }
+
+    /// # Split Rows
+    /// 
+    /// Splits whole demographic rows (by their index in demo_breakdown) off 
+    /// into a new pop. The new pop takes a share of everything else equal to
+    /// it's share of the population. See split_off().
+    /// 
+    /// Indices out of range are ignored.
+    pub fn split_rows(&mut self, new_id: usize, rows: &[usize], data: &Data) -> Pop {
+        let total = self.households.population();
+        let mut taken = vec![];
+        for (idx, row) in self.demo_breakdown.iter_mut().enumerate() {
+            if rows.contains(&idx) {
+                taken.push(row.clone());
+                row.household.count = 0.0;
+            }
+        }
+        let moved: f64 = taken.iter().map(|x| x.household.population()).sum();
+        let fraction = if total > 0.0 { (moved / total).min(1.0) } else { 0.0 };
+        self.split_off(new_id, taken, fraction, data)
+    }
+
+    /// # Split To Firm
+    /// 
+    /// Splits fraction of the pop off to work at a different firm, see 
+    /// split_fraction().
+    /// 
+    /// # Errors
+    /// 
+    /// If fraction isn't between 0.0 and 1.0, exclusive, we are left unchanged.
+    pub fn split_to_firm(&mut self, new_id: usize, fraction: f64, firm: usize, data: &Data) -> Result<Pop, String> {
+        let mut result = self.split_fraction(new_id, fraction, data)?;
+        result.firm = firm;
+        Ok(result)
+    }
+
+    /// # Split Off
+    /// 
+    /// Creates a new pop in the same market and firm from rows taken out of 
+    /// this one, giving it fraction of our property, wants, and financials.
+    /// 
+    /// The new pop shares our tags, efficiency, habits, positions, and price
+    /// expectations. Any rows left empty are removed, and both pops have their
+    /// households recombined and desires rebuilt, so this should be done 
+    /// between days.
+    fn split_off(&mut self, new_id: usize, rows: Vec<DRow>, fraction: f64, data: &Data) -> Pop {
+        let mut result = Pop::new(new_id, self.market, self.firm);
+        for row in rows.into_iter().filter(|x| x.household.count > 0.0) {
+            result.include_demo(row);
+        }
+        self.demo_breakdown.retain(|x| x.household.count > 0.0);
+        self.demo_breakdown.sort_by(|a, b| b.household.count.total_cmp(&a.household.count));
+        for (&good, record) in self.property.iter_mut() {
+            result.property.insert(good, record.split(fraction));
+        }
+        for (&want, record) in self.wants.iter_mut() {
+            result.wants.insert(want, record.split(fraction));
+        }
+        result.financials = self.financials.split(fraction);
+        result.efficiency = self.efficiency;
+        result.habits = self.habits.clone();
+        result.positions = self.positions.clone();
+        result.prices = self.prices.clone();
+        result.prices.seed = new_id;
+        result.heir = self.heir;
+        result.tags = self.tags.clone();
+        for pop in [&mut *self, &mut result] {
+            pop.combine_households(data);
+            pop.update_desires_full(data);
+        }
+        result
+    }
+
+    /// # Merge
+    /// 
+    /// Merges other into this pop. They must be in the same market, work for
+    /// the same firm, and have the same tags.
+    /// 
+    /// Rows with the same demographics are combined, the rest are added. 
+    /// Property, wants, and financials are added together, with their mood 
+    /// and plans weighted by population, see PopFinancials::merge(). We keep 
+    /// our habits, positions, and price expectations, taking other's only 
+    /// where we have none. If both have a pending order, ours is kept and 
+    /// the time other set aside for it is released.
+    /// 
+    /// Households are recombined and desires rebuilt, so this should be done
+    /// between days.
+    /// 
+    /// # Errors
+    /// 
+    /// If the pops can't be merged, or merging would change the total 
+    /// household count or property, we are left unchanged.
+    pub fn merge(&mut self, other: Pop, data: &Data) -> Result<(), String> {
+        if self.id == other.id {
+            return Err(format!("Pop {} cannot merge with itself.", self.id));
+        }
+        if self.market != other.market {
+            return Err(format!("Pop {} is in market {}, not {}.", other.id, other.market, self.market));
+        }
+        if self.firm != other.firm {
+            return Err(format!("Pop {} works for firm {}, not {}.", other.id, other.firm, self.firm));
+        }
+        if self.tags != other.tags {
+            return Err(format!("Pop {} has different tags from pop {}.", other.id, self.id));
+        }
+        let mut merged = self.clone();
+        let expected_count = merged.households.count + other.households.count;
+        let mut expected_property: HashMap<usize, f64> = merged.property.iter()
+            .map(|(good, record)| (*good, record.owned)).collect();
+        for (good, record) in other.property.iter() {
+            *expected_property.entry(*good).or_insert(0.0) += record.owned;
+        }
+        let total = merged.households.population() + other.households.population();
+        let weight = if total > 0.0 { other.households.population() / total } else { 0.5 };
+        for row in other.demo_breakdown {
+            if row.household.count <= 0.0 {
+                continue;
+            }
+            match merged.demo_breakdown.iter_mut().find(|x| x.same_demographics(&row)) {
+                Some(existing) if existing.household.count > 0.0 => {
+                    existing.household = existing.household.combine(&row.household);
+                },
+                Some(existing) => existing.household = row.household,
+                None => merged.demo_breakdown.push(row),
+            }
+        }
+        merged.demo_breakdown.sort_by(|a, b| b.household.count.total_cmp(&a.household.count));
+        for (good, record) in other.property.iter() {
+            merged.property.entry(*good).or_insert(PropertyRecord::new(0.0)).merge(record);
+        }
+        for (want, record) in other.wants.iter() {
+            merged.wants.entry(*want).or_insert(WantRecord::new()).merge(record);
+        }
+        merged.financials.merge(&other.financials, weight);
+        // we keep our own order, other's is dropped, releasing it's time.
+        if let Some((_, time)) = other.pending_order &&
+        let Some(record) = merged.property.get_mut(&TIME_ID) {
+            record.reserved -= time;
+        }
+        for (item, habit) in other.habits {
+            merged.habits.entry(item).or_insert(habit);
+        }
+        for (item, position) in other.positions {
+            merged.positions.entry(item).or_insert(position);
+        }
+        for (good, price) in other.prices.prices {
+            merged.prices.prices.entry(good).or_insert(price);
+        }
+        merged.combine_households(data);
+        merged.update_desires_full(data);
+        if (merged.households.count - expected_count).abs() > 1e-9 * expected_count.max(1.0) {
+            return Err(format!("Household count changed in merging pop {} into {}.", other.id, self.id));
+        }
+        if !expected_property.iter()
+        .all(|(good, owned)| (merged.property[good].owned - owned).abs() <= 1e-9 * owned.abs().max(1.0)) {
+            return Err(format!("Property changed in merging pop {} into {}.", other.id, self.id));
         }
+        *self = merged;
+        Ok(())
     }
 
     /// # Reset
@@ -143,30 +352,25 @@ impl Pop {
     /// Also resets the pop's financials for the day.
     pub fn reset_property(&mut self) {
         for (_, prop) in self.property.iter_mut() {
-            prop.expended = 0.0;
-            prop.offered = 0.0;
-            prop.reserved = 0.0;
-            prop.traded = 0.0;
-            prop.used = 0.0;
+            prop.reset();
         }
         for (_, want) in self.wants.iter_mut() {
-            want.expected = 0.0;
-            want.expended = 0.0;
-            want.reserved = 0.0;
+            want.reset();
         }
         for desire in self.desires.iter_mut() {
             desire.satisfaction = 0.0;
         }
+        self.pending_order = None;
 
         self.satisfaction = SatisfactionValues::zero();
     }
 
     /// # New Financial Day
     /// 
-    /// Called to start a new financial day. Updates starting, current, historical, and
-    /// average wealth.
+    /// Called to start a new financial day. Updates starting and current wealth.
     /// 
-    /// Does not update income, dividend, or decay.
+    /// Does not update income, dividend, or decay. Historical and average wealth
+    /// are updated at day end, see close_financial_day().
     pub fn new_financial_day(&mut self, market: &MarketHistory) {
         // Reset start of day wealth and current wealth.
         // AS this is called right after reset property at day start, this is 
@@ -174,9 +378,6 @@ impl Pop {
         let excess_wealth = self.excess_amv(market);
         self.financials.wealth = excess_wealth;
         self.financials.current_wealth = excess_wealth;
-        // update average wealth, average with previous days, but weigh to current day more.
-        self.financials.wealth_history.push_back(excess_wealth);
-        self.financials.update_average_wealth();
     }
 
     ///  # Record Income
@@ -212,19 +413,24 @@ impl Pop {
     /// This makes satisfying via the price a better AMV deal for the buyer.
     /// 
     /// This is not a hard rule, just a suggestion for later coding.
+    /// 
+    /// Slaves cannot trade, so they never offer anything.
     pub fn make_offer(&self, request: &HashMap<usize, f64>, data: &Data, 
     market: &MarketHistory, price_hint: &HashMap<usize, f64>) -> HashMap<usize, f64>{
+        if self.is_slave() {
+            return HashMap::new();
+        }
         // get the AMV of the request
         let mut req_amv = 0.0;
         // check if we have been given a price hint.
         if price_hint.len() > 0 {
             for (&good, &amt) in price_hint.iter() {
                 // get the AMV price of the price hint goods.
-                req_amv += market.get_record(good).price * amt;
+                req_amv += self.expected_price(good, market) * amt;
             }
         } else { // if no price hint, get market AMV for hint value.
             for (good, amt) in request.iter() {
-                req_amv += market.get_record(*good).price * amt;
+                req_amv += self.expected_price(*good, market) * amt;
             }
         }
         //println!("Requested AMV: {}", req_amv);
@@ -243,13 +449,13 @@ impl Pop {
         .filter(|x| price_hint.contains_key(x.0))
         .sorted_by(|a, b| {
             // iterate over our goods, sorting by current AMV value.
-            let val_a = market.get_record(*a.0).price;
-            let val_b = market.get_record(*b.0).price;
+            let val_a = self.expected_price(*a.0, market);
+            let val_b = self.expected_price(*b.0, market);
             val_b.total_cmp(&val_a)
         }) {
             //println!("Good: {}", good);
             // start with most valuable and either get just enough, or all available for it.
-            let unit_amv = market.get_record(*good).price;
+            let unit_amv = self.expected_price(*good, market);
             //println!("Good AMV: {}", unit_amv);
             // get target, capped at available, and rounded down.
             let mut shift = price_hint.get(good).unwrap().min(prop_info.available()).floor();
@@ -259,7 +465,7 @@ impl Pop {
                 // check that the sacrifice is worth it
                 let sat_lost = self.satisfaction_lost(&offer_goods, data, market);
                 // TODO: Update this checker to take into account priority differences rather than just satisfaction.
-                if sat_lost.satisfaction > sat_gain.satisfaction { // if too much, reduce by half (round down) and go back
+                if sat_lost.normalized > sat_gain.normalized { // if too much, reduce by half (round down) and go back
                     shift = (shift / 2.0).floor();
                     offer_goods.remove(good);
                 } else { // if not overdrawing, break out and stay there.
@@ -286,13 +492,13 @@ impl Pop {
             !price_hint.contains_key(x.0)) // and not the hint.
         .sorted_by(|a, b| {
             // iterate over our goods, sorting by current AMV value.
-            let val_a = market.get_record(*a.0).price;
-            let val_b = market.get_record(*b.0).price;
+            let val_a = self.expected_price(*a.0, market);
+            let val_b = self.expected_price(*b.0, market);
             val_b.total_cmp(&val_a)
         }) {
             // println!("Good: {}", good);
             // start with most valuable and either get just enough, or all available for it.
-            let unit_amv = market.get_record(*good).price;
+            let unit_amv = self.expected_price(*good, market);
             // println!("Good AMV: {}", unit_amv);
             let target_amt = ((req_amv - offer_amv) / unit_amv).ceil();
              // get target, capped at available, and rounded down.
@@ -306,7 +512,7 @@ impl Pop {
                     let sat_lost = self.satisfaction_lost(&offer_goods, data, market);
                     // TODO: Update this to target properly instead of estimating half reductions.
                     // println!("Satisfaciton Lost: {}", sat_lost.steps);
-                    if sat_lost.steps > sat_gain.steps { // if too much, reduce by half (round down) and go back
+                    if sat_lost.normalized > sat_gain.normalized { // if too much, reduce by half (round down) and go back
                         shift = (shift / 2.0).floor();
                         offer_goods.remove(good);
                     } else { // if not overdrawing, break out and stay there.
@@ -336,12 +542,12 @@ impl Pop {
         !price_hint.contains_key(x.0)) // or not the hint.
         .sorted_by(|a, b| {
             // iterate over our goods, sorting by current AMV value.
-            let val_a = market.get_record(*a.0).price;
-            let val_b = market.get_record(*b.0).price;
+            let val_a = self.expected_price(*a.0, market);
+            let val_b = self.expected_price(*b.0, market);
             val_b.total_cmp(&val_a)
         }) {
             // start with most valuable and either get just enough, or all available for it.
-            let unit_amv = market.get_record(*good).price;
+            let unit_amv = self.expected_price(*good, market);
             // println!("Good AMV: {}", unit_amv);
             let target_amt = ((req_amv - offer_amv) / unit_amv).ceil();
              // get target, capped at available, and rounded down.
@@ -352,7 +558,7 @@ impl Pop {
                     // check that the sacrifice is worth it
                     let sat_lost = self.satisfaction_lost(&offer_goods, data, market);
                     // TODO: Update this to target properly instead of estimating half reductions.
-                    if sat_lost.steps > sat_gain.steps { // if too much, reduce by half (round down) and go back
+                    if sat_lost.normalized > sat_gain.normalized { // if too much, reduce by half (round down) and go back
                         shift = (shift / 2.0).floor();
                         offer_goods.remove(good);
                     } else { // if not overdrawing, break out and stay there.
@@ -384,6 +590,8 @@ impl Pop {
     /// 
     /// ## Acceptance Logic
     /// 
+    /// Slaves never accept, as they cannot trade.
+    /// 
     /// First, if the AMV Lost is 4x the amount which would be gained, we never 
     /// accept, there are probably better options.
     /// 
@@ -394,21 +602,47 @@ impl Pop {
     /// but it is somethinsg.
     pub fn check_offer(&self, request: &HashMap<usize, f64>, offer: &HashMap<usize, f64>,
     data: &Data, market: &MarketHistory) -> OfferResult {
+        self.explain_offer(request, offer, data, market).result
+    }
+
+    /// # Explain Offer
+    /// 
+    /// Checks an offer the same way as check_offer, but returns why it came
+    /// to that result, including the AMV on both sides and the desire ranges 
+    /// which would be gained and lost.
+    pub fn explain_offer(&self, request: &HashMap<usize, f64>, offer: &HashMap<usize, f64>,
+    data: &Data, market: &MarketHistory) -> TradeExplanation {
         // Get the direct AMv results of our request and offer for that comparison as well.
         let mut amv_gain = 0.0;
         for (&good, &amt) in offer.iter() {
-            amv_gain += market.get_record(good).price * amt;
+            amv_gain += self.expected_price(good, market) * amt;
         }
         let mut amv_loss = 0.0;
         for (&good, &amt) in request.iter() {
-            amv_loss += market.get_record(good).price * amt;
+            amv_loss += self.expected_price(good, market) * amt;
         }
         println!("AMV Gain: {}", amv_gain);
         println!("AMV Loss: {}", amv_loss);
 
-        // Before any checking, the pop should never lose more than 4x what it gains in AMV.
-        if amv_gain < (amv_loss * POP_AMV_HARD_LOSS_THRESHOLD) {
-            return OfferResult::Reject(RejectReason::HardThresholdFailure);
+        // Slaves cannot give anything up, so never accept.
+        if self.is_slave() {
+            return TradeExplanation { 
+                result: OfferResult::Reject(RejectReason::Enslaved), 
+                amv_gain, 
+                amv_loss, 
+                change: None,
+            };
+        }
+
+        // Before any checking, the pop should never lose more than 4x what it gains in AMV,
+        // less if it's uncertain, more if it's willing to take risks.
+        if amv_gain < (amv_loss * self.financials.hard_loss_threshold()) {
+            return TradeExplanation { 
+                result: OfferResult::Reject(RejectReason::HardThresholdFailure), 
+                amv_gain, 
+                amv_loss, 
+                change: None 
+            };
         }
 
         // Get how the request would change our satisfaction.
@@ -419,25 +653,23 @@ impl Pop {
                 .and_modify(|x| *x -= amt)
                 .or_insert(-amt);
         }
-        let dup =  self.clone();
-        let change = dup.satisfaction_change(&change, data, market);
+        let change = self.satisfaction_change(&change, data, market);
 
-        if change.satisfaction > 0.0 {
+        let result = if change.normalized > 0.0 {
             // Accept if satisfaction increases.
-            return OfferResult::Accept(AcceptReason::Satisfaction);
-        }
-        let value_change = self.satisfaction.value;
-        println!("Density Change: {}", value_change);
-        if value_change > 0.0 {
+            OfferResult::Accept(AcceptReason::Satisfaction)
+        } else if self.satisfaction.value > 0.0 {
             // Accept if Satisfaction density increases
-            return OfferResult::Accept(AcceptReason::Density);
-        }
-        println!("AMV Gain: {}", change.excess_amv);
-        if change.excess_amv > 0.0 {
+            println!("Density Change: {}", self.satisfaction.value);
+            OfferResult::Accept(AcceptReason::Density)
+        } else if change.excess_amv > 0.0 {
             // lastly, if no change in range or density, check for AMV gain.
-            return OfferResult::Accept(AcceptReason::AMV);
-        }
-        OfferResult::Reject(RejectReason::NotAccepted)
+            println!("AMV Gain: {}", change.excess_amv);
+            OfferResult::Accept(AcceptReason::AMV)
+        } else {
+            OfferResult::Reject(RejectReason::NotAccepted)
+        };
+        TradeExplanation { result, amv_gain, amv_loss, change: Some(change) }
     }
 
     /// # Create Sell Orders
@@ -447,10 +679,15 @@ impl Pop {
     /// Currently, we offer everything that is in excess, exchangeable, and
     /// not a currency.
     /// 
+    /// Slaves cannot sell anything, so they never create any.
+    /// 
     /// TODO: This will likely be modified to be toggleable to reduce overusing this.
     pub fn create_sell_orders(&self, data: &Data, market: &MarketHistory) 
     -> HashMap<usize, f64> {
         let mut result = HashMap::new();
+        if self.is_slave() {
+            return result;
+        }
         for (&good, info) in self.property.iter() {
             let available = info.excess();
             if available > 0.0 && data.get_good(good).is_exchangeable() &&
@@ -477,8 +714,10 @@ impl Pop {
             .and_modify(|x| x.owned = time)
             .or_insert(PropertyRecord::new(time));
 
-        // reset our property and satisfaction for this new day.
+        // reset our property and satisfaction for this new day, our stockpiles
+        // are there to be used.
         self.reset_property();
+        self.release_stockpiles();
         self.new_financial_day(market);
 
         // do labor / wage swap
@@ -490,7 +729,121 @@ impl Pop {
         // NOTE: This needs to be either try_satsify_all_desires followed by recalcilute_working_desires
         // NOTE: or create a new working_desires to pass in, moved from self.desires, then call satisfy_until_incomplete.
         self.try_satisfy_until_incomplete(data, market);
-        // With desires partially satisfied, everything should be done.
+        // With desires partially satisfied, set aside what we can for the days ahead.
+        self.plan_stockpiles(data, market);
+    }
+
+    /// # Release Stockpiles
+    /// 
+    /// Frees up all saved goods so they can be used to satisfy today's desires.
+    /// 
+    /// Targets are left alone, so the pop still doesn't sell it's stockpiles.
+    pub fn release_stockpiles(&mut self) {
+        for record in self.property.values_mut() {
+            record.saved = 0.0;
+        }
+    }
+
+    /// # Plan Stockpiles
+    /// 
+    /// Looks PopFinancials::stockpile_days ahead and sets aside goods to cover 
+    /// the desires of those days.
+    /// 
+    /// Each future day is expected to need the full amount of every desire 
+    /// with an end, see stockpile_needs(). Day d is weighed by 
+    /// ((1 - decay_rate) / (1 + time_preference))^d, so goods which rot and 
+    /// pops which care little for tomorrow stockpile less.
+    /// 
+    /// The last plan is taken out of each good's target, and the new plan is 
+    /// added in its place. Targets only cover future days, what is reserved 
+    /// for today is kept separate. Needs are planned in order, LifeNeeds 
+    /// first, each putting as much as the pop has left over into saved. 
+    /// Whatever they are short is bought in free_time_action().
+    /// 
+    /// Should be called after today's desires have been satisfied.
+    pub fn plan_stockpiles(&mut self, data: &Data, market: &MarketHistory) {
+        let days = self.financials.stockpile_days;
+        let time_preference = self.financials.time_preference;
+        // drop the last plan, leaving the rest of our targets alone.
+        for record in self.property.values_mut() {
+            record.target -= record.planned;
+            record.planned = 0.0;
+        }
+        for (good, need) in self.stockpile_needs(data, market) {
+            let keep = (1.0 - data.get_good(good).decay_rate) / (1.0 + time_preference);
+            let planned: f64 = (1..=days)
+                .map(|day| need * keep.powi(day as i32))
+                .sum();
+            if planned <= 0.0 {
+                continue;
+            }
+            let record = self.property.entry(good)
+                .or_insert(PropertyRecord::new(0.0));
+            record.planned += planned;
+            record.target += planned;
+            record.saved += planned.min(record.available()).max(0.0);
+        }
+    }
+
+    /// # Stockpile Needs
+    /// 
+    /// The goods needed each day to satisfy every desire with an end, in the 
+    /// order they should be stockpiled, LifeNeeds first.
+    /// 
+    /// - Goods need their full amount.
+    /// - Classes need it of the member the pop owns the most of, or the 
+    ///   cheapest in the market if it owns none.
+    /// - Wants need enough of their cheapest source in the market, see 
+    ///   Data::cheapest_want_sources().
+    /// 
+    /// Desires which can't be mapped to a good are left out.
+    pub fn stockpile_needs(&self, data: &Data, market: &MarketHistory) -> Vec<(usize, f64)> {
+        let mut result = vec![];
+        for desire in self.desires.iter().chain(self.working_desires.iter())
+        .sorted_by_key(|x| !x.tags.iter().any(|tag| matches!(tag, DesireTag::LifeNeed(_)))) {
+            let Some(steps) = desire.steps else {
+                continue;
+            };
+            let need = desire.amount * steps.get() as f64;
+            match desire.item {
+                Item::Good(id) => result.push((id, need)),
+                Item::Class(id) => {
+                    let members = data.get_class(id);
+                    let owned = members.iter()
+                        .filter(|x| self.property.get(x).is_some_and(|x| x.owned > 0.0))
+                        .max_by(|a, b| self.property[a].owned.total_cmp(&self.property[b].owned)
+                            .then(b.cmp(a)));
+                    let good = owned.or_else(|| members.iter()
+                        .filter(|x| market.good_records.contains_key(x))
+                        .min_by(|a, b| market.good_records[a].price
+                            .total_cmp(&market.good_records[b].price)
+                            .then(a.cmp(b))));
+                    if let Some(&good) = good {
+                        result.push((good, need));
+                    }
+                },
+                Item::Want(id) => {
+                    if let Some(&(good, source, _)) = data.cheapest_want_sources(id, market).first() {
+                        let eff = data.get_good(good).wants_from(source)[&id];
+                        result.push((good, need / eff));
+                    }
+                },
+            }
+        }
+        result
+    }
+
+    /// # Stockpile Shortfall
+    /// 
+    /// The first good in our stockpile plan we have less of than our target 
+    /// plus what's reserved for today, and how much we are short, see 
+    /// stockpile_needs().
+    pub fn stockpile_shortfall(&self, data: &Data, market: &MarketHistory) -> Option<(Item, f64)> {
+        self.stockpile_needs(data, market).into_iter()
+            .filter_map(|(good, _)| self.property.get(&good)
+                .filter(|x| x.planned > 0.0)
+                .map(|x| (Item::Good(good), x.current_target() + x.reserved)))
+            .find(|(_, short)| *short > 0.0)
     }
 
     /// # Workday Actions
@@ -516,14 +869,123 @@ impl Pop {
     /// 
     /// What it prioritizes is as follows.
     /// 
-    /// 1. Purchasing it's desires.
+    /// 1. Purchasing it's desires, then what it's stockpiles are short.
     /// 2. Active consumption of it's goods for it's own satisfaction.
     /// 3. Planning.
     ///     a. This comes in the form of altering it's buy targets going forward, 
     /// 4. Activism (Being extra active in politics and the community, for better and worse)
-    pub fn free_time_action(&mut self) -> FreeTimeAction {
-        // first, try to 
-        todo!()
+    /// 
+    /// Active consumption is done first each call, so that anything bought by the 
+    /// last BuyOrder is put to use before deciding what to buy next.
+    /// 
+    /// ## Time
+    /// 
+    /// A BuyOrder sets aside the time needed to use or consume what's being bought
+    /// (see time_per_unit), buying only as much as the pop has time for. This is
+    /// given back at the next call, to be reserved properly when satisfying 
+    /// desires. The same item is never ordered twice in a row, if the market 
+    /// couldn't fill it, the pop moves on to planning.
+    /// 
+    /// Each plan or bit of activism takes up POP_PLANNING_TIME of the pop's free
+    /// time, see plan_free_time(). Once it has no free time left it returns End.
+    pub fn free_time_action(&mut self, data: &Data, market: &MarketHistory) -> FreeTimeAction {
+        // take back the time set aside for our last order.
+        let last_order = self.pending_order.take();
+        if let Some((_, time)) = last_order {
+            self.property.get_mut(&TIME_ID).unwrap().reserved -= time;
+        }
+        // put what we have to use.
+        self.try_satisfy_until_incomplete(data, market);
+        let free_time = self.property.get(&TIME_ID).map_or(0.0, |x| x.available());
+        if free_time <= 0.0 {
+            return FreeTimeAction::End;
+        }
+
+        // buy what we still desire, then what our stockpiles are short.
+        if let Some((item, amount)) = self.get_shopping_target()
+            .or_else(|| self.stockpile_shortfall(data, market)) &&
+        amount > 0.0 && last_order.is_none_or(|(last, _)| last != item) {
+            let per_unit = self.time_per_unit(item, data, market);
+            let amount = if per_unit > 0.0 { amount.min(free_time / per_unit) } else { amount };
+            let time = amount * per_unit;
+            self.property.get_mut(&TIME_ID).unwrap().reserved += time;
+            self.pending_order = Some((item, time));
+            return FreeTimeAction::BuyOrder { good: item, amount };
+        }
+
+        // plan, with whatever time is left.
+        self.plan_free_time(market)
+    }
+
+    /// # Plan Free Time
+    /// 
+    /// Decides what to do with free time once the pop can't buy anything else.
+    /// 
+    /// - Unmet LifeNeeds mean seeking migration, valued at the mortality they add.
+    /// - Other unmet desires mean seeking employment, valued at the portion of
+    ///   desires left unsatisfied.
+    /// - Satisfied pops with excess wealth and some risk tolerance seek to create
+    ///   a firm, valued at excess AMV * risk tolerance.
+    /// - Otherwise, they get active in the community.
+    /// 
+    /// Whatever is chosen reserves POP_PLANNING_TIME of the pop's time, or what
+    /// it has left if that's less.
+    pub fn plan_free_time(&mut self, market: &MarketHistory) -> FreeTimeAction {
+        if let Some(time) = self.property.get_mut(&TIME_ID) {
+            time.reserved += time.available().clamp(0.0, POP_PLANNING_TIME);
+        }
+        let missing_life_needs: f64 = self.life_need_mortality().values().sum();
+        if missing_life_needs > 0.0 {
+            return FreeTimeAction::SeekMigration(missing_life_needs);
+        }
+        if !self.working_desires.is_empty() {
+            let total = (self.desires.len() + self.working_desires.len()) as f64;
+            return FreeTimeAction::SeekEmployment(self.working_desires.len() as f64 / total);
+        }
+        let excess = self.excess_amv(market);
+        if excess > 0.0 && self.financials.risk_tolerance > 0.0 {
+            return FreeTimeAction::CreateFirm(excess * self.financials.risk_tolerance);
+        }
+        FreeTimeAction::GetActive
+    }
+
+    /// # Time Per Unit
+    /// 
+    /// How much time it takes to make use of one unit of an item.
+    /// 
+    /// Wants take the use or consumption time of their cheapest source in the
+    /// market, divided by how much of the want it gives. 
+    /// 
+    /// Goods take the time to use or consume them, the longer of the two if 
+    /// they give wants both ways. Classes take that of their cheapest member 
+    /// in the market. Goods which only give wants by owning them take no time.
+    pub fn time_per_unit(&self, item: Item, data: &Data, market: &MarketHistory) -> f64 {
+        let good = match item {
+            Item::Want(id) => {
+                let Some(&(good, source, _)) = data.cheapest_want_sources(id, market).first() else {
+                    return 0.0;
+                };
+                let good_data = data.get_good(good);
+                let eff = good_data.wants_from(source)[&id];
+                return good_data.time_for(source) / eff;
+            },
+            Item::Good(id) => id,
+            Item::Class(id) => {
+                let Some(&good) = data.get_class(id).iter()
+                    .filter(|x| market.good_records.contains_key(x))
+                    .min_by(|a, b| market.good_records[a].price
+                        .total_cmp(&market.good_records[b].price)
+                        .then(a.cmp(b))) else {
+                    return 0.0;
+                };
+                good
+            },
+        };
+        let good_data = data.get_good(good);
+        [WantSource::Use, WantSource::Consumption].into_iter()
+            .filter(|&source| !good_data.wants_from(source).is_empty())
+            .map(|source| good_data.time_for(source))
+            .fold(0.0, f64::max)
     }
 
     // standard day action, the work done by the pop during the day. This is primarily the buying of goods from the market.
@@ -531,9 +993,448 @@ impl Pop {
 
     /// # End Day
     /// 
-    /// The final wrap up of our day. It should 
-    pub fn day_end(&mut self, _data: &Data, _market: &MarketHistory) {
+    /// The final wrap up of our day. In order, it
+    /// 
+    /// 1. Moves working_desires back into desires, clearing it out.
+    /// 2. Actively consumes goods to satisfy desires.
+    /// 3. If the pop is a slave and owner is given, hands everything it 
+    ///    doesn't need over to owner, see surrender_excess().
+    /// 4. Releases the day's reservations.
+    /// 5. Decays stored wants, removing any which decay away entirely.
+    /// 6. Expires Service goods, recording their AMV as decay.
+    /// 7. Closes out the day's financials, see close_financial_day().
+    /// 8. Updates the pop's financial mood.
+    /// 9. Updates habits.
+    /// 
+    /// The desires are left as they were satisfied, so apply_growth() can 
+    /// apply mortality from any LifeNeeds left unsatisfied.
+    pub fn day_end(&mut self, data: &Data, owner: Option<&mut Firm>, market: &MarketHistory) {
+        while let Some(desire) = self.working_desires.pop_front() {
+            Pop::ordered_desire_insert(&mut self.desires, desire);
+        }
+        if !self.desires.is_empty() {
+            self.satisfaction = self.consume_desires(data, market);
+        }
+        // hand over the excess while our reservations still say what we need.
+        if self.is_slave() && let Some(owner) = owner {
+            self.surrender_excess(owner);
+        }
+        // with everything consumed, our reservations are done with.
+        for record in self.property.values_mut() {
+            record.reserved = 0.0;
+        }
+        for record in self.wants.values_mut() {
+            record.reserved = 0.0;
+        }
+        self.decay_wants(data);
+        self.financials.decay = self.expire_services(data, market);
+        self.close_financial_day(market);
+        self.update_financial_mood(market);
+        self.update_habits();
+    }
+
+    /// # Is Slave
+    /// 
+    /// Whether the pop has the Slave tag.
+    pub fn is_slave(&self) -> bool {
+        self.tags.contains(&PopTag::Slave)
+    }
+
+    /// # Surrender Excess
+    /// 
+    /// Hands everything the pop owns but doesn't need over to owner. Time is 
+    /// never handed over.
+    /// 
+    /// What it needs of a good is what it has reserved, plus what it has 
+    /// saved or its target, whichever is more. Must be called before the 
+    /// day's reservations are released.
+    /// 
+    /// Returns the goods handed over.
+    pub fn surrender_excess(&mut self, owner: &mut Firm) -> HashMap<usize, f64> {
+        let mut result = HashMap::new();
+        for (&good, record) in self.property.iter_mut() {
+            let excess = record.owned - record.reserved - record.saved.max(record.target);
+            if good == TIME_ID || excess <= 0.0 {
+                continue;
+            }
+            record.owned -= excess;
+            *owner.property.entry(good).or_insert(0.0) += excess;
+            result.insert(good, excess);
+        }
+        result
+    }
+
+    /// # Enslave
+    /// 
+    /// Makes the pop a slave of firm. The pop is tagged as a Slave, moved to
+    /// work for firm, and recorded as a Slave worker there, with no wage.
+    /// 
+    /// old_firm is the firm the pop worked for before, if it is not firm, and
+    /// the pop is removed from it's workers.
+    /// 
+    /// # Errors
+    /// 
+    /// If the pop is already a slave, or old_firm is not the pop's firm.
+    pub fn enslave(&mut self, firm: &mut Firm, old_firm: Option<&mut Firm>) -> Result<(), String> {
+        if self.is_slave() {
+            return Err(format!("Pop {} is already a slave.", self.id));
+        }
+        if let Some(old_firm) = old_firm {
+            if old_firm.id != self.firm {
+                return Err(format!("Pop {} does not work for firm {}.", self.id, old_firm.id));
+            }
+            old_firm.workers.remove(&self.id);
+        }
+        self.tags.insert(PopTag::Slave);
+        self.firm = firm.id;
+        let info = firm.workers.entry(self.id)
+            .or_insert(WorkerInfo::new(WorkerType::Slave));
+        info.worker_type = WorkerType::Slave;
+        info.wage.clear();
+        Ok(())
+    }
+
+    /// # Manumit
+    /// 
+    /// Frees the pop from firm. The pop loses it's Slave tag and stays on at 
+    /// firm as a normal Worker, with it's wage left to be set.
+    /// 
+    /// # Errors
+    /// 
+    /// If the pop is not a slave, or is not firm's slave.
+    pub fn manumit(&mut self, firm: &mut Firm) -> Result<(), String> {
+        if !self.is_slave() {
+            return Err(format!("Pop {} is not a slave.", self.id));
+        }
+        let Some(info) = firm.workers.get_mut(&self.id)
+            .filter(|x| x.worker_type == WorkerType::Slave) else {
+            return Err(format!("Pop {} is not a slave of firm {}.", self.id, firm.id));
+        };
+        info.worker_type = WorkerType::Worker;
+        self.tags.remove(&PopTag::Slave);
+        Ok(())
+    }
+
+    /// # Update Financial Mood
+    /// 
+    /// Updates the pop's financial mood and rates, see PopFinancials::update_mood().
+    /// 
+    /// Price volatility is the average volatility of the goods the pop owns, 
+    /// weighted by their AMV. Shortfall is the portion of steps unsatisfied in
+    /// desires which have an end.
+    pub fn update_financial_mood(&mut self, market: &MarketHistory) {
+        let mut amv = 0.0;
+        let mut weighted_volatility = 0.0;
+        for (good, record) in self.property.iter() {
+            if let Some(good_record) = market.good_records.get(good) {
+                let value = good_record.price * record.owned;
+                amv += value;
+                weighted_volatility += value * good_record.volatility;
+            }
+        }
+        let price_volatility = if amv > 0.0 { weighted_volatility / amv } else { 0.0 };
+        let shortfall = self.shortfall();
+        self.financials.update_mood(price_volatility, shortfall);
+    }
+
+    /// # Shortfall
+    /// 
+    /// The portion (0.0 to 1.0) of steps unsatisfied in desires which have an
+    /// end, whether still in working_desires or not.
+    pub fn shortfall(&self) -> f64 {
+        let mut steps = 0.0;
+        let mut satisfied = 0.0;
+        for desire in self.desires.iter().chain(self.working_desires.iter()) {
+            if let Some(end) = desire.steps {
+                steps += end.get() as f64;
+                satisfied += desire.satisfied_steps().min(end.get() as f64);
+            }
+        }
+        if steps > 0.0 { 1.0 - satisfied / steps } else { 0.0 }
+    }
+
+    /// # Decay Wants
+    /// 
+    /// Decays all stored wants by their decay rate, see Want::decay(). Wants 
+    /// which fall below MINIMUM_WANT_THRESHOLD are removed from storage.
+    pub fn decay_wants(&mut self, data: &Data) {
+        for (&id, record) in self.wants.iter_mut() {
+            if record.owned > 0.0 {
+                record.owned = data.get_want(id).decay(record.owned);
+            }
+        }
+        self.wants.retain(|_, record| record.owned > 0.0);
+    }
+
+    /// # Expire Services
+    /// 
+    /// Removes all Service goods the pop owns, as they cannot last past the day.
+    /// 
+    /// Returns the AMV lost.
+    pub fn expire_services(&mut self, data: &Data, market: &MarketHistory) -> f64 {
+        let mut lost = 0.0;
+        for (&good, record) in self.property.iter_mut() {
+            if record.owned > 0.0 && data.get_good(good).is_service() {
+                lost += record.owned * market.good_records.get(&good).map_or(0.0, |x| x.price);
+                record.owned = 0.0;
+            }
+        }
+        lost
+    }
+
+    /// # Close Financial Day
+    /// 
+    /// Records the day's numbers in the pop's financial history.
+    /// 
+    /// Sets amv_end to the AMV the pop has left over, pushes today's starting 
+    /// wealth, income, and dividends into their histories and updates their 
+    /// averages. Income and dividends are then zeroed out for tomorrow.
+    pub fn close_financial_day(&mut self, market: &MarketHistory) {
+        self.financials.amv_end = self.excess_amv(market);
+        self.financials.wealth_history.push_back(self.financials.wealth);
+        self.financials.income_history.push_back(self.financials.income);
+        self.financials.dividend_history.push_back(self.financials.dividends);
+        self.financials.update_average_wealth();
+        self.financials.update_average_income();
+        self.financials.update_average_dividend();
+        self.financials.income = 0.0;
+        self.financials.dividends = 0.0;
+    }
+
+    /// # Update Habits
+    /// 
+    /// Records how well each habituating desire was satisfied today, and updates
+    /// their amounts if their habit changed.
+    /// 
+    /// Satisfaction is measured as the portion of the desire's steps satisfied,
+    /// desires without an end only need their first step.
+    /// 
+    /// Habits are kept per item, so habituating desires for the same item 
+    /// share one. Their satisfaction is averaged and recorded once a day, 
+    /// using the rate and days of the first of them.
+    pub fn update_habits(&mut self) {
+        let mut days_satisfaction: HashMap<Item, (f64, f64, f64, usize)> = HashMap::new();
+        for desire in self.desires.iter().chain(self.working_desires.iter()) {
+            let Some((rate, days)) = desire.tags.iter().find_map(|tag| match tag {
+                DesireTag::Habituation { rate, days } => Some((*rate, *days)),
+                _ => None,
+            }) else {
+                continue;
+            };
+            let steps = desire.steps.map_or(1.0, |x| x.get() as f64);
+            let entry = days_satisfaction.entry(desire.item)
+                .or_insert((0.0, 0.0, rate, days));
+            entry.0 += desire.satisfied_steps() / steps;
+            entry.1 += 1.0;
+        }
+        for (item, (total, count, rate, days)) in days_satisfaction {
+            let habit = self.habits.entry(item).or_default();
+            let old = habit.multiplier;
+            let new = habit.record(total / count, rate, days);
+            if new == old {
+                continue;
+            }
+            for desire in self.desires.iter_mut().chain(self.working_desires.iter_mut())
+            .filter(|x| x.item == item && 
+            x.tags.iter().any(|tag| matches!(tag, DesireTag::Habituation { .. }))) {
+                desire.amount = desire.amount / old * new;
+            }
+        }
+    }
+
+    /// # Holdings Per Person
+    /// 
+    /// How much of an item the pop owns per person. Classes total up all of 
+    /// their member goods.
+    /// 
+    /// Returns 0.0 if the pop has no people.
+    pub fn holdings_per_person(&self, item: Item, data: &Data) -> f64 {
+        let population = self.households.population();
+        if population <= 0.0 {
+            return 0.0;
+        }
+        let owned = match item {
+            Item::Good(id) => self.property.get(&id).map_or(0.0, |x| x.owned),
+            Item::Class(id) => data.get_class(id).iter()
+                .map(|good| self.property.get(good).map_or(0.0, |x| x.owned))
+                .sum(),
+            Item::Want(id) => self.wants.get(&id).map_or(0.0, |x| x.owned),
+        };
+        owned / population
+    }
+
+    /// # Positional Items
+    /// 
+    /// Gets the items of all positional desires the pop has, and how they are
+    /// measured.
+    pub fn positional_items(&self) -> Vec<(Item, PositionalMeasure)> {
+        self.desires.iter().chain(self.working_desires.iter())
+            .filter_map(|desire| desire.tags.iter().find_map(|tag| match tag {
+                DesireTag::Positional(measure) => Some((desire.item, *measure)),
+                _ => None,
+            }))
+            .collect_vec()
+    }
+
+    /// # Update Positional Desires
+    /// 
+    /// Rescales the pop's positional desires to the per person holdings of the
+    /// other pops in the market, given by item in others.
+    /// 
+    /// The reference can go no lower than POSITIONAL_MINIMUM_REFERENCE. Desires
+    /// with no one else to compare to are left as they are.
+    pub fn update_positional_desires(&mut self, others: &HashMap<Item, Vec<f64>>) {
+        for desire in self.desires.iter_mut().chain(self.working_desires.iter_mut()) {
+            let Some(measure) = desire.tags.iter().find_map(|tag| match tag {
+                DesireTag::Positional(measure) => Some(*measure),
+                _ => None,
+            }) else {
+                continue;
+            };
+            let Some(reference) = others.get(&desire.item)
+                .and_then(|holdings| measure.reference(holdings)) else {
+                continue;
+            };
+            let new = reference.max(POSITIONAL_MINIMUM_REFERENCE);
+            let old = *self.positions.get(&desire.item).unwrap_or(&1.0);
+            if new != old {
+                desire.amount = desire.amount / old * new;
+            }
+            self.positions.insert(desire.item, new);
+        }
+    }
+
+    /// # Life Need Mortality
+    /// 
+    /// Gets the additional mortality caused by each LifeNeed desire which has
+    /// not been fully satisfied, whether still in working_desires or not.
+    /// 
+    /// Each missing step adds the LifeNeed's value to the fraction of the pop
+    /// which dies each day.
+    pub fn life_need_mortality(&self) -> HashMap<Item, f64> {
+        let mut result = HashMap::new();
+        for desire in self.desires.iter().chain(self.working_desires.iter()) {
+            let Some(steps) = desire.steps else {
+                continue; // LifeNeeds always have steps.
+            };
+            let missing = (steps.get() as f64 - desire.satisfied_steps()).max(0.0);
+            if missing == 0.0 {
+                continue;
+            }
+            for tag in desire.tags.iter() {
+                if let DesireTag::LifeNeed(mortality) = tag {
+                    *result.entry(desire.item).or_insert(0.0) += mortality * missing;
+                }
+            }
+        }
+        result
+    }
+
+    /// # Apply Growth
+    /// 
+    /// Applies a turn of births and deaths to each demographic row, from it's
+    /// household's birth_rate() and effective_mortality(), which adds the 
+    /// mortality of any LifeNeeds left unsatisfied (see life_need_mortality())
+    /// to the household's. These are measured over POP_GROWTH_PERIOD days, so 
+    /// each turn applies DAYS_PER_TURN / POP_GROWTH_PERIOD of them. Those born
+    /// this turn die at the same rate. Fractional counts are kept as stored up
+    /// growth.
+    /// 
+    /// Rows whose species has CohortAging instead have their births (scaled 
+    /// by how well the pop's desires are satisfied, see shortfall()) and 
+    /// deaths applied to the cohorts of their household, which then age, see
+    /// Household::age_cohorts().
+    /// 
+    /// Rows which die out are removed, and the rest are re-sorted by size. 
+    /// Households are recombined, and if the population has changed by more 
+    /// than POP_DESIRE_REBUILD_THRESHOLD since desires were last built they
+    /// are rebuilt to match.
+    /// 
+    /// This should be called after day_end(), while the pop's desires are 
+    /// still satisfied as they were at the end of the day.
+    /// 
+    /// Returns the births and deaths, the deaths caused by each unmet LifeNeed,
+    /// and the inheritance of the dead, see inheritance().
+    pub fn apply_growth(&mut self, data: &Data) -> GrowthReport {
+        let population_before = self.households.population();
+        let scale = DAYS_PER_TURN / POP_GROWTH_PERIOD;
+        let mut report = GrowthReport::new();
+        let shortfall = self.shortfall();
+        let causes = self.life_need_mortality();
+        let life_need: f64 = causes.values().sum();
+        for row in self.demo_breakdown.iter_mut() {
+            let mut births = row.household.birth_rate() * scale;
+            let normal = row.household.mortality();
+            let mortality = row.household.effective_mortality(life_need);
+            let deaths = mortality * scale;
+            let population = row.household.population();
+            let died = if let Some(aging) = data.get_species(row.species).aging {
+                births *= aging.birth_multiplier(shortfall);
+                let died = (population * (1.0 + births)).min(population * deaths);
+                row.household = row.household.age_cohorts(&aging, population * births, died);
+                if row.household.count <= 0.0 {
+                    // those left without adults go with their households.
+                    report.deaths += population * (1.0 + births) - died;
+                }
+                died
+            } else {
+                row.household.count = (row.household.count * (1.0 + births) * (1.0 - deaths)).max(0.0);
+                population * (1.0 + births) * deaths
+            };
+            report.births += population * births;
+            report.deaths += died;
+            // split the LifeNeed share of the dead between it's causes.
+            if mortality > 0.0 && life_need > 0.0 {
+                let share = (mortality - normal).max(0.0) / mortality;
+                for (item, cause) in causes.iter() {
+                    *report.causes.entry(*item).or_insert(0.0) += died * share * cause / life_need;
+                }
+            }
+        }
+        self.demo_breakdown.retain(|x| x.household.count > 0.0);
+        self.demo_breakdown.sort_by(|a, b| b.household.count.total_cmp(&a.household.count));
+        self.combine_households(data);
+        let population = self.households.population();
+        if self.demo_breakdown.is_empty() ||
+        (population - self.desire_population).abs() > self.desire_population * POP_DESIRE_REBUILD_THRESHOLD {
+            self.update_desires_full(data);
+        }
+        report.inheritance = self.inheritance(report.deaths, population_before);
+        report
+    }
 
+    /// # Inheritance
+    /// 
+    /// Who inherits the property of the deaths (in people) since the pop had 
+    /// population_before people, and what they inherit.
+    /// 
+    /// If any of the pop survives, the survivors inherit the dead's share of 
+    /// each good the pop owns. As property is held by the pop as a whole, it 
+    /// stays where it is.
+    /// 
+    /// If the pop died out, everything it owns goes to it's heir, or it's 
+    /// market if it has none. The goods are moved by World::settle_inheritance().
+    /// 
+    /// None if no one died or there is nothing to inherit.
+    pub fn inheritance(&self, deaths: f64, population_before: f64) -> Option<Inheritance> {
+        if deaths <= 0.0 || population_before <= 0.0 {
+            return None;
+        }
+        let (heir, share) = if self.households.population() > 0.0 {
+            (Heir::Survivors, (deaths / population_before).min(1.0))
+        } else {
+            (self.heir.unwrap_or(Heir::Market(self.market)), 1.0)
+        };
+        let mut result = Inheritance::new(self.id, self.market, heir);
+        for (&good, record) in self.property.iter() {
+            if record.owned > 0.0 {
+                result.goods.insert(good, record.owned * share);
+            }
+        }
+        if result.goods.is_empty() {
+            return None;
+        }
+        Some(result)
     }
 
     /// # Build Savings
@@ -543,9 +1444,140 @@ impl Pop {
     /// 
     /// Goods prefered for savings are highly salable and durable goods.
     /// 
-    /// TODO: Rethink how to do savings. Current method is most likely going to be slow and scale poorly.
-    pub fn build_savings(&mut self, market: &MarketHistory) {
-        todo!()
+    /// Goods are valued at the pop's expected price, see expected_price(), 
+    /// goods it knows no price for are worth nothing.
+    /// 
+    /// The target is PopFinancials::curr_saving_rate of the AMV of everything the
+    /// pop has (but time) which isn't reserved, anything already saved (like stockpiles) 
+    /// counting towards it.
+    /// 
+    /// Goods are bucketed into tiers, monies first, then SAVINGS_TIERS tiers by
+    /// salability * (1 - decay_rate). Tiers are filled in order, and the last 
+    /// tier needed saves the same portion of each good in it. Time, goods which
+    /// can't be exchanged, and those with no savings efficiency are never saved.
+    /// 
+    /// This runs in linear time with the pop's property.
+    /// 
+    /// Updates PopFinancials::saved to the AMV of all saved goods.
+    pub fn build_savings(&mut self, data: &Data, market: &Market, history: &MarketHistory) {
+        let prices: HashMap<usize, f64> = self.property.keys()
+            .map(|&good| {
+                let known = self.prices.prices.contains_key(&good) || 
+                    history.good_records.contains_key(&good);
+                (good, if known { self.expected_price(good, history) } else { 0.0 })
+            })
+            .collect();
+        let price = |good: usize| prices[&good];
+        // sort available goods into tiers, and get what we have to save.
+        let mut tiers: Vec<Vec<usize>> = vec![vec![]; SAVINGS_TIERS + 1];
+        let mut tier_amv = vec![0.0; SAVINGS_TIERS + 1];
+        let mut total_amv = 0.0;
+        let mut saved_amv = 0.0;
+        for (&good, record) in self.property.iter().filter(|x| *x.0 != TIME_ID) {
+            total_amv += price(good) * (record.available() + record.saved);
+            saved_amv += price(good) * record.saved;
+            let good_data = data.get_good(good);
+            if record.available() <= 0.0 || !good_data.is_exchangeable() {
+                continue;
+            }
+            let tier = if market.monies.contains(&good) {
+                0
+            } else {
+                let salability = market.goods_info.get(&good).map_or(0.0, |x| x.salability);
+                let efficiency = salability * (1.0 - good_data.decay_rate);
+                if efficiency <= 0.0 {
+                    continue;
+                }
+                1 + (((1.0 - efficiency) * SAVINGS_TIERS as f64) as usize).min(SAVINGS_TIERS - 1)
+            };
+            tiers[tier].push(good);
+            tier_amv[tier] += price(good) * record.available();
+        }
+        // fill tiers in order until we reach our target.
+        let mut remaining = self.financials.curr_saving_rate * total_amv - saved_amv;
+        for (goods, amv) in tiers.iter().zip(tier_amv) {
+            if remaining <= 0.0 {
+                break;
+            }
+            if amv <= 0.0 {
+                continue;
+            }
+            let portion = (remaining / amv).min(1.0);
+            for good in goods.iter() {
+                let record = self.property.get_mut(good).unwrap();
+                let shift = record.available() * portion;
+                record.saved += shift;
+                saved_amv += price(*good) * shift;
+            }
+            remaining -= amv * portion;
+        }
+        self.financials.saved = saved_amv;
+    }
+
+    /// # Expected Price
+    /// 
+    /// What the pop expects a good to be worth, see PriceExpectations.
+    pub fn expected_price(&self, good: usize, market: &MarketHistory) -> f64 {
+        self.prices.expected(good, market)
+    }
+
+    /// # Complete Trade
+    /// 
+    /// Carries out a trade the pop accepted, see check_offer(). The request 
+    /// is given up, recorded as traded, and the offer is added to our 
+    /// property. We can't give up more than we own.
+    /// 
+    /// The pop then learns from the prices it traded at, see observe_trade().
+    pub fn complete_trade(&mut self, request: &HashMap<usize, f64>, offer: &HashMap<usize, f64>,
+    market: &MarketHistory) {
+        for (good, &amount) in request.iter() {
+            if let Some(record) = self.property.get_mut(good) {
+                let given = amount.min(record.owned);
+                record.owned -= given;
+                record.traded += given;
+            }
+        }
+        for (&good, &amount) in offer.iter() {
+            self.property.entry(good)
+                .and_modify(|x| x.owned += amount)
+                .or_insert(PropertyRecord::new(amount));
+        }
+        self.observe_trade(request, offer, market);
+    }
+
+    /// # Observe Trade
+    /// 
+    /// Updates the pop's price expectations from a trade it saw, where request
+    /// was exchanged for offer.
+    /// 
+    /// The trade implies the request is worth what the offer is, so each good 
+    /// is seen at it's expected price scaled by that exchange rate. Requested 
+    /// goods go up if the offer was worth more than we expected the request to
+    /// be, and offered goods go down, and vice versa.
+    /// 
+    /// Does nothing if either side is expected to be worth nothing.
+    /// 
+    /// Called by complete_trade() for the pop's own trades, and by 
+    /// World::observe_trade() for those it sees in it's market.
+    pub fn observe_trade(&mut self, request: &HashMap<usize, f64>, offer: &HashMap<usize, f64>,
+    market: &MarketHistory) {
+        let value = |goods: &HashMap<usize, f64>| -> f64 {
+            goods.iter().map(|(&good, &amt)| self.expected_price(good, market) * amt).sum()
+        };
+        let request_value = value(request);
+        let offer_value = value(offer);
+        if request_value <= 0.0 || offer_value <= 0.0 {
+            return;
+        }
+        let rate = offer_value / request_value;
+        for &good in request.keys().sorted() {
+            let seen = self.expected_price(good, market) * rate;
+            self.prices.observe(good, seen, market);
+        }
+        for &good in offer.keys().sorted() {
+            let seen = self.expected_price(good, market) / rate;
+            self.prices.observe(good, seen, market);
+        }
     }
 
     /// # Excess AMV
@@ -555,7 +1587,7 @@ impl Pop {
     pub fn excess_amv(&self, market: &MarketHistory) -> f64 {
         let mut amv = 0.0;
         for (&good, &data) in self.property.iter() {
-            amv += market.get_record(good).price * data.available();
+            amv += self.expected_price(good, market) * data.available();
         }
         amv
     }
@@ -579,7 +1611,8 @@ impl Pop {
                 },
                 Item::Good(id) => {
                     // if we have this in our property, and it has a target, try to get that target.
-                    if let Some(property) = self.property.get(&id) {
+                    if let Some(property) = self.property.get(&id) &&
+                    property.current_target() > 0.0 {
                         let target = property.current_target();
                         return Some((Item::Good(id), target));
                     } else {
@@ -614,6 +1647,7 @@ impl Pop {
         let mut results = vec![];
         let mut range_acc = 0.0;
         let mut step_acc = 0.0;
+        let mut normalized_acc = 0.0;
 
         for amv in amv_gains.iter() {
             let mut amv_remaining = *amv;
@@ -660,15 +1694,17 @@ impl Pop {
             let dup_sat = dup.get_satisfaction(market);
             let curr_range = dup_sat.value - self_sat.value - range_acc;
             let curr_steps = dup_sat.steps - self_sat.steps - step_acc;
+            let curr_normalized = dup_sat.normalized - self_sat.normalized - normalized_acc;
             println!("Current Satisfaction Level: {}", curr_range);
             println!("Current Satisfaction Value: {}", curr_steps);
-            results.push(SatisfactionValues::new(curr_range, curr_steps, 0.0, 0.0));
+            results.push(SatisfactionValues::new(curr_range, curr_steps, curr_normalized, 0.0, 0.0));
             // add to the accumulators
             range_acc += curr_range;
             step_acc += curr_steps;
+            normalized_acc += curr_normalized;
         }
         // append the total sum of the end.
-        results.push(SatisfactionValues::new(range_acc, step_acc, 0.0, 0.0));
+        results.push(SatisfactionValues::new(range_acc, step_acc, normalized_acc, 0.0, 0.0));
 
         results
     }
@@ -731,6 +1767,7 @@ impl Pop {
         let self_sat = self.get_satisfaction(market);
         SatisfactionValues::new(dup_sat.value - self_sat.value, 
             dup_sat.steps - self_sat.steps, 
+            dup_sat.normalized - self_sat.normalized, 
             dup_sat.satisfaction - self_sat.satisfaction, 
             dup_sat.excess_amv - self_sat.excess_amv)
     }
@@ -771,13 +1808,98 @@ impl Pop {
     /// 
     /// Given a number of goods added/removed returns the result of that change in goods.
     /// 
-    /// Returns levels satisfied and levels. 
+    /// Returns levels satisfied and levels, along with the desire ranges gained
+    /// and lost. 
+    /// 
+    /// Only the desires which could be affected by the change are recalculated,
+    /// those that can use the changed goods, and any that compete with them
+    /// for goods, wants, or time (see affected_desires). The rest can't change,
+    /// so they are kept as they are, giving the same result as 
+    /// satisfaction_change_full without cloning the pop.
+    /// 
+    /// This assumes the pop's desires are as try_satisfy_all_desires left them.
+    /// If the pop has any working desires, it falls back to 
+    /// satisfaction_change_full, as those keep their satisfaction when 
+    /// recalculating.
+    /// 
+    /// NOTE: satisfy_next_desire sizes it's steps by the next desire in line,
+    /// and caps them at the desire's total steps rather than the steps it has 
+    /// left, so a desire can overshoot it's end depending on what desires are
+    /// being satisfied alongside it. When that happens this and 
+    /// satisfaction_change_full can disagree.
+    pub fn satisfaction_change(&self, change: &HashMap<usize, f64>, data: &Data, 
+    market: &MarketHistory) -> SatisfactionValues {
+        if !self.working_desires.is_empty() {
+            return self.satisfaction_change_full(change, data, market);
+        }
+        let (affected, goods, wants) = self.affected_desires(change.keys(), data);
+        // build a pop with only the affected desires, property, and wants.
+        let mut partial = Pop::new(self.id, self.market, self.firm);
+        for good in goods.iter() {
+            if let Some(&rec) = self.property.get(good) {
+                let mut rec = rec;
+                rec.reset();
+                partial.property.insert(*good, rec);
+            }
+        }
+        for want in wants.iter() {
+            if let Some(rec) = self.wants.get(want) {
+                let mut rec = rec.clone();
+                rec.reset();
+                partial.wants.insert(*want, rec);
+            }
+        }
+        for (good, val) in change.iter() {
+            partial.property.entry(*good)
+                .and_modify(|x| x.owned += val)
+                .or_insert(PropertyRecord::new(*val));
+        }
+        for &idx in affected.iter() {
+            let mut desire = self.desires[idx].clone();
+            desire.satisfaction = 0.0;
+            partial.desires.push_back(desire);
+        }
+        partial.try_satisfy_all_desires(data, market);
+
+        // combine the untouched desires and property with the recalculated ones.
+        let unaffected = self.desires.iter().enumerate()
+            .filter(|(idx, _)| affected.binary_search(idx).is_err())
+            .map(|(_, desire)| desire);
+        // valued at our own expectations, the partial pop doesn't have them.
+        let mut excess_amv = 0.0;
+        for (good, rec) in self.property.iter() {
+            let available = partial.property.get(good).unwrap_or(rec).available();
+            excess_amv += self.expected_price(*good, market) * available;
+        }
+        for (good, rec) in partial.property.iter() {
+            if !self.property.contains_key(good) {
+                excess_amv += self.expected_price(*good, market) * rec.available();
+            }
+        }
+        let new_sat = Pop::satisfaction_of(unaffected.chain(partial.desires.iter()), excess_amv);
+        let (gained, lost) = Pop::range_changes(
+            affected.iter().map(|&idx| &self.desires[idx]), 
+            partial.desires.iter());
+
+        SatisfactionValues::new(new_sat.value - self.satisfaction.value, 
+            new_sat.steps - self.satisfaction.steps, 
+            new_sat.normalized - self.satisfaction.normalized, 
+            new_sat.satisfaction - self.satisfaction.satisfaction, 
+            new_sat.excess_amv - self.satisfaction.excess_amv)
+            .with_ranges(gained, lost)
+    }
+
+    /// # Satisfaction Change Full
     /// 
-    /// This is a full sort, so it may be quite expensive to run.
+    /// Given a number of goods added/removed returns the result of that change in goods.
     /// 
-    /// NOTE: Not tested as this is fairly simple, so long as try_satisfy_all_desires works, this should also work.
-    /// TODO: If possible, upgrade this to be more efficient rather than totally recalculating satisfaction each time.
-    pub fn satisfaction_change(&self, change: &HashMap<usize, f64>, data: &Data, 
+    /// Returns levels satisfied and levels, along with the desire ranges gained
+    /// and lost. 
+    /// 
+    /// This clones the pop and recalculates all of it's satisfaction, so it may be
+    /// quite expensive to run. Prefer satisfaction_change, which gives the same 
+    /// result.
+    pub fn satisfaction_change_full(&self, change: &HashMap<usize, f64>, data: &Data, 
     market: &MarketHistory)  -> SatisfactionValues {
         let mut temp_pop = self.clone();
         temp_pop.reset_property();
@@ -794,6 +1916,7 @@ impl Pop {
         println!("AMV: {}", temp_pop.satisfaction.excess_amv);
         let range = temp_pop.satisfaction.value - self.satisfaction.value;
         let steps = temp_pop.satisfaction.steps - self.satisfaction.steps;
+        let normalized = temp_pop.satisfaction.normalized - self.satisfaction.normalized;
         let satisfaction = temp_pop.satisfaction.satisfaction - self.satisfaction.satisfaction;
         let amv = temp_pop.satisfaction.excess_amv - self.satisfaction.excess_amv;
         println!("Difference:");
@@ -801,66 +1924,47 @@ impl Pop {
         println!("Steps: {}", steps);
         println!("Satisfaction: {}", satisfaction);
         println!("AMV: {}", amv);
+        let (gained, lost) = Pop::range_changes(
+            self.desires.iter().chain(self.working_desires.iter()), 
+            temp_pop.desires.iter().chain(temp_pop.working_desires.iter()));
 
-        SatisfactionValues::new(range, steps, satisfaction, amv)
+        SatisfactionValues::new(range, steps, normalized, satisfaction, amv)
+            .with_ranges(gained, lost)
     }
 
     /// # Satisfaction Lost
     /// 
     /// Calculates the satisfaction lost by removing goods and wants from the 
+    /// pop.
     /// 
-    /// Calculates it by cloning the pop, removing goods, then satisfying desires.
-    /// 
-    /// Returns levels satisfied and levels
+    /// Calculates it via satisfaction_change with the goods removed.
     /// 
-    /// # Note Not tested
+    /// Returns levels satisfied and levels. The desire ranges lost are put in 
+    /// lost_ranges.
     pub fn satisfaction_lost(&self, removing: &HashMap<usize, f64>, data: &Data, market: &MarketHistory) -> SatisfactionValues {
-        // Clone existing pop.
-        let mut temp_pop = self.clone();
-        // Reset the 
-        temp_pop.reset_property(); 
-        for (good, val) in removing.iter() {
-            temp_pop.property.get_mut(good).unwrap().owned -= *val;
-        }
-        // satisfy the desires of the temporary pop.
-        temp_pop.try_satisfy_all_desires(data, market);
-        // with satisfaciton done, return the difference between the current and possible new
-        let range = self.satisfaction.value - temp_pop.satisfaction.value;
-        let steps = self.satisfaction.steps - temp_pop.satisfaction.steps;
-        let satisfaction = self.satisfaction.satisfaction - temp_pop.satisfaction.satisfaction;
-        let amv = self.satisfaction.excess_amv - temp_pop.satisfaction.excess_amv;
-        SatisfactionValues::new(range, steps, satisfaction, amv)
+        let change = removing.iter()
+            .map(|(&good, &val)| (good, -val))
+            .collect();
+        let change = self.satisfaction_change(&change, data, market);
+        SatisfactionValues::new(-change.value, -change.steps, -change.normalized, 
+            -change.satisfaction, -change.excess_amv)
+            .with_ranges(vec![], change.lost_ranges)
     }
 
     /// # Satisfaction Gain
     /// 
     /// Calculates the satisfaction gained by adding these goods.
     /// 
-    /// Calculates it by cloning the pop, adding the desire, then satisfying desires.
+    /// Calculates it via satisfaction_change with the goods added.
     /// 
-    /// Returns levels satisfied and levels
-    /// 
-    /// # Note Not tested
+    /// Returns levels satisfied and levels, and the desire ranges gained.
     pub fn satisfaction_gain(&self, new_goods: &HashMap<usize, f64>, 
     data: &Data, market: &MarketHistory) -> SatisfactionValues {
-        // Clone existing pop.
-        let mut temp_pop = self.clone();
-        // Reset the 
-        temp_pop.reset_property(); 
-        for (good, val) in new_goods.iter() {
-            temp_pop.property.entry(*good)
-                .and_modify(|x| x.owned += val)
-                .or_insert(PropertyRecord::new(*val));
-        }
-        // satisfy the desires of the temporary pop.
-        temp_pop.try_satisfy_all_desires(data, market);
-        // with satisfaciton done, return the difference between the current and possible new
-        let range = temp_pop.satisfaction.value - self.satisfaction.value;
-        let steps = temp_pop.satisfaction.steps - self.satisfaction.steps;
-        let satisfaction = self.satisfaction.satisfaction - temp_pop.satisfaction.satisfaction;
-        let amv = temp_pop.satisfaction.excess_amv - self.satisfaction.excess_amv;
-        debug_assert!(steps >= 0.0, "Satisfaction Gained must be non-negative.");
-        SatisfactionValues::new(range, steps, satisfaction, amv)
+        let change = self.satisfaction_change(new_goods, data, market);
+        debug_assert!(change.steps >= 0.0, "Satisfaction Gained must be non-negative.");
+        SatisfactionValues::new(change.value, change.steps, change.normalized, 
+            change.satisfaction, change.excess_amv)
+            .with_ranges(change.ranges, vec![])
     }
 
     /// # Get Satisfaction
@@ -872,27 +1976,29 @@ impl Pop {
     /// 
     /// We should always target more steps if possible, and a smaller range second.
     /// 
+    /// Steps are kept for comparison, but normalized satisfaction should be 
+    /// preferred, as steps let desires with small amounts overpower those with
+    /// large amounts.
+    /// 
     /// NOTE: Does not save to the pop.
     /// NOTE: This has not been tested. It is assumed to be correct.
-    /// TODO: Change steps to units of satisfaction so desires with small unit amounts can't overpower desires with big units out unreasonably.
     pub fn get_satisfaction(&self, market: &MarketHistory) -> SatisfactionValues {
+        Pop::satisfaction_of(self.desires.iter().chain(self.working_desires.iter()), 
+            self.excess_amv(market))
+    }
+
+    /// # Satisfaction Of
+    /// 
+    /// Sums up the satisfaction of the given desires, see get_satisfaction.
+    fn satisfaction_of<'a>(desires: impl Iterator<Item = &'a Desire>, 
+    excess_amv: f64) -> SatisfactionValues {
         let mut low = f64::INFINITY;
         let mut high = f64::NEG_INFINITY;
         let mut steps = 0.0;
+        let mut normalized = 0.0;
         let mut sat = 0.0;
-        for desire in self.desires.iter() {
-            if desire.satisfaction == 0.0 {
-                // If no satisfaciton, skip it.
-                continue;
-            }
-            low = low.min(desire.starting_value);
-            high = high.max(desire.satisfied_to_value());
-            // println!("Current Low: {}", low);
-            // println!("Current High: {}", high);
-            steps += desire.satisfied_steps();
-            sat += desire.satisfaction;
-        }
-        for desire in self.working_desires.iter() {
+        let mut ranges = vec![];
+        for desire in desires {
             if desire.satisfaction == 0.0 {
                 // If no satisfaciton, skip it.
                 continue;
@@ -902,7 +2008,9 @@ impl Pop {
             // println!("Current Low: {}", low);
             // println!("Current High: {}", high);
             steps += desire.satisfied_steps();
+            normalized += desire.normalized_satisfaction();
             sat += desire.satisfaction;
+            ranges.push((desire.starting_value, desire.satisfied_to_value()));
         }
         // sanity check that we reached something.
         if high == f64::NEG_INFINITY || low == f64::INFINITY {
@@ -913,7 +2021,108 @@ impl Pop {
         }
         // println!("High Range: {}", high);
         // println!("Low Range: {}", low);
-        SatisfactionValues::new(high - low, steps, sat, self.excess_amv(market))
+        SatisfactionValues::new(high - low, steps, normalized, sat, excess_amv)
+            .with_ranges(ranges, vec![])
+    }
+
+    /// # Affected Desires
+    /// 
+    /// Finds which of our desires could be affected by a change in the given 
+    /// goods, returning their indices (in order), and every good and want those 
+    /// desires could use.
+    /// 
+    /// A desire is affected if it could use one of the goods changed, or any 
+    /// good or want an affected desire could use, as it would be competing with
+    /// that desire.
+    fn affected_desires<'a>(&self, changed: impl Iterator<Item = &'a usize>, 
+    data: &Data) -> (Vec<usize>, HashSet<usize>, HashSet<usize>) {
+        let resources = self.desires.iter()
+            .map(|desire| Pop::desire_resources(desire, data))
+            .collect_vec();
+        let mut goods: HashSet<usize> = changed.copied().collect();
+        let mut wants = HashSet::new();
+        let mut affected = vec![false; resources.len()];
+        // keep going until no more desires are pulled in.
+        loop {
+            let mut grew = false;
+            for (idx, (desire_goods, desire_wants)) in resources.iter().enumerate() {
+                if affected[idx] {
+                    continue;
+                }
+                if desire_goods.iter().any(|x| goods.contains(x)) || 
+                desire_wants.iter().any(|x| wants.contains(x)) {
+                    affected[idx] = true;
+                    goods.extend(desire_goods.iter());
+                    wants.extend(desire_wants.iter());
+                    grew = true;
+                }
+            }
+            if !grew {
+                break;
+            }
+        }
+        let affected = affected.iter().enumerate()
+            .filter(|(_, x)| **x)
+            .map(|(idx, _)| idx)
+            .collect();
+        (affected, goods, wants)
+    }
+
+    /// # Desire Resources
+    /// 
+    /// Gets the goods and wants a desire could use to satisfy itself.
+    /// 
+    /// For wants, this includes every good which produces it, time if it is 
+    /// produced by use or consumption, and the other wants those goods produce
+    /// along the way.
+    fn desire_resources(desire: &Desire, data: &Data) -> (HashSet<usize>, HashSet<usize>) {
+        let mut goods = HashSet::new();
+        let mut wants = HashSet::new();
+        match desire.item {
+            Item::Good(id) => { goods.insert(id); },
+            Item::Class(id) => goods.extend(data.get_class(id).iter()),
+            Item::Want(id) => {
+                wants.insert(id);
+                for (good, source, _) in data.want_sources(id) {
+                    goods.insert(good);
+                    if source != WantSource::Ownership {
+                        goods.insert(TIME_ID);
+                    }
+                    wants.extend(data.get_good(good).wants_from(source).keys());
+                }
+            },
+        }
+        (goods, wants)
+    }
+
+    /// # Range Changes
+    /// 
+    /// Compares desires before and after their satisfaction was recalculated
+    /// and gets the ranges of each desire which were gained and lost, in that 
+    /// order.
+    /// 
+    /// Desires are matched up by everything but their satisfaction. Both ranges 
+    /// go in the direction of the desire's curve.
+    fn range_changes<'a>(before: impl Iterator<Item = &'a Desire>, 
+    after: impl Iterator<Item = &'a Desire>) -> (Vec<ValueRange>, Vec<ValueRange>) {
+        let mut gained = vec![];
+        let mut lost = vec![];
+        let mut others: Vec<&Desire> = after.collect();
+        for desire in before {
+            let Some(idx) = others.iter()
+                .position(|x| x.same_desire(desire)) else {
+                continue;
+            };
+            let matched = others.swap_remove(idx);
+            let before = desire.satisfied_steps();
+            let after = matched.satisfied_steps();
+            if after > before {
+                gained.push((desire.satisfied_to_value(), matched.satisfied_to_value()));
+            } else if after < before {
+                lost.push((matched.satisfied_to_value(), desire.satisfied_to_value()));
+            }
+        }
+        (gained, lost)
     }
 
     /// # Consume Desires
@@ -940,10 +2149,9 @@ impl Pop {
             let mut current_desire = working_desires.pop_front().unwrap();
 
             if self.consume_desire(&mut current_desire, data) { // if successful at satisfying
-                let next_step = current_desire.satisfied_to_value();
-                println!("Next Step: {}", next_step);
-                if let Some(end) = current_desire.end() { 
-                    if next_step < end { // if not past the end
+                if let Some(steps) = current_desire.steps { 
+                    // count steps, as the curve may go either direction.
+                    if current_desire.satisfied_steps() < steps.get() as f64 { // if not past the end
                         // put back
                         Pop::ordered_desire_insert(&mut working_desires, 
                             current_desire);
@@ -1004,9 +2212,8 @@ impl Pop {
                 }
                 // First try to get via ownership
                 if shifted < current_desire.amount { // check if we need more.
-                    let want = data.get_want(id);
                     // get the goods we can use for this.
-                    for good in want.ownership_sources.iter() {
+                    for good in data.get_want_sources(id, WantSource::Ownership).iter() {
                         // with a good gotten, reserve as much as necessary to satisfy it.
                         if let Some(good_rec) = self.property.get_mut(good) {
                             // Get how many of the good we need to reserve for it.
@@ -1059,9 +2266,8 @@ impl Pop {
                 }
                 // Then try for use if we still need more.
                 if shifted < current_desire.amount { // then try for use
-                    let want = data.get_want(id);
                     // get the goods we can use for this.
-                    for good in want.use_sources.iter() {
+                    for good in data.get_want_sources(id, WantSource::Use).iter() {
                         // with a good gotten, reserve as much as necessary to satisfy it.
                         if self.property.contains_key(good) {
                             // get time and the good
@@ -1135,9 +2341,8 @@ impl Pop {
                     }
                 }
                 if shifted < current_desire.amount { // lastly consumption
-                    let want = data.get_want(id);
                     // get the goods we can consume for this.
-                    for good in want.consumption_sources.iter() {
+                    for good in data.get_want_sources(id, WantSource::Consumption).iter() {
                         // with a good gotten, reserve as much as necessary to satisfy it.
                         if self.property.contains_key(good) {
                             // get time and the good
@@ -1286,9 +2491,8 @@ impl Pop {
                 }
                 // First try to get via ownership
                 if shifted < target_amount { // check if we need more.
-                    let want = data.get_want(id);
                     // get the goods we can use for this.
-                    for good in want.ownership_sources.iter() {
+                    for good in data.get_want_sources(id, WantSource::Ownership).iter() {
                         // with a good gotten, reserve as much as necessary to satisfy it.
                         if let Some(good_rec) = self.property.get_mut(good) {
                             // Get how many of the good we need to reserve for it.
@@ -1333,9 +2537,8 @@ impl Pop {
                 }
                 // Then try for use if we still need more.
                 if shifted < target_amount { // then try for use
-                    let want = data.get_want(id);
                     // get the goods we can use for this.
-                    for good in want.use_sources.iter() {
+                    for good in data.get_want_sources(id, WantSource::Use).iter() {
                         // with a good gotten, reserve as much as necessary to satisfy it.
                         if self.property.contains_key(good) {
                             // get time and the good
@@ -1398,9 +2601,8 @@ impl Pop {
                     }
                 }
                 if shifted < target_amount { // lastly consumption
-                    let want = data.get_want(id);
                     // get the goods we can consume for this.
-                    for good in want.consumption_sources.iter() {
+                    for good in data.get_want_sources(id, WantSource::Consumption).iter() {
                         // with a good gotten, reserve as much as necessary to satisfy it.
                         if self.property.contains_key(good) {
                             // get time and the good
@@ -1704,8 +2906,26 @@ impl Pop {
                 let culture = data.get_culture(culture_id);
                 Self::integrate_desires(&culture.desires, row, &mut desires);
             }
-            // Remaining sections go here.
+            // any other layers
+            for &layer_id in row.layers.iter() {
+                let layer = data.get_layer(layer_id);
+                Self::integrate_desires(&layer.desires, row, &mut desires);
+            }
         }
+        // apply any habits the pop has built up.
+        for desire in desires.iter_mut() {
+            if let Some(habit) = self.habits.get(&desire.item) && 
+            desire.tags.iter().any(|x| matches!(x, DesireTag::Habituation { .. })) {
+                desire.amount *= habit.multiplier;
+            }
+            if let Some(reference) = self.positions.get(&desire.item) && 
+            desire.tags.iter().any(|x| matches!(x, DesireTag::Positional(_))) {
+                desire.amount *= reference;
+            }
+        }
+        self.desires = desires.into();
+        self.desire_population = self.demo_breakdown.iter()
+            .map(|x| x.household.population()).sum();
     }
 
     /// Helper for getting desires from a part of demographics into our total desires.
@@ -1844,6 +3064,9 @@ pub struct PropertyRecord {
     /// This should be equal to reserved + saved. Any goods above this target are free 
     /// game to offer.
     pub target: f64,
+    /// How much of target was added by the pop's last stockpile plan, so it 
+    /// can be replaced by the next one. See Pop::plan_stockpiles().
+    pub planned: f64,
 }
 
 impl PropertyRecord {
@@ -1857,6 +3080,7 @@ impl PropertyRecord {
             offered: 0.0,
             target: 0.0,
             saved: 0.0,
+            planned: 0.0,
         }
     }
 
@@ -1868,6 +3092,60 @@ impl PropertyRecord {
         self.owned - self.reserved - self.saved
     }
 
+    /// # Reset
+    /// 
+    /// Resets everything but owned, saved, and target to zero.
+    pub fn reset(&mut self) {
+        self.expended = 0.0;
+        self.offered = 0.0;
+        self.reserved = 0.0;
+        self.traded = 0.0;
+        self.used = 0.0;
+    }
+
+    /// # Split
+    /// 
+    /// Splits off fraction (0.0 to 1.0) of the record, returning the part split
+    /// off and leaving the rest.
+    pub fn split(&mut self, fraction: f64) -> Self {
+        let taken = Self {
+            owned: self.owned * fraction,
+            reserved: self.reserved * fraction,
+            saved: self.saved * fraction,
+            expended: self.expended * fraction,
+            used: self.used * fraction,
+            traded: self.traded * fraction,
+            offered: self.offered * fraction,
+            target: self.target * fraction,
+            planned: self.planned * fraction,
+        };
+        self.owned -= taken.owned;
+        self.reserved -= taken.reserved;
+        self.saved -= taken.saved;
+        self.expended -= taken.expended;
+        self.used -= taken.used;
+        self.traded -= taken.traded;
+        self.offered -= taken.offered;
+        self.target -= taken.target;
+        self.planned -= taken.planned;
+        taken
+    }
+
+    /// # Merge
+    /// 
+    /// Adds other's record into ours.
+    pub fn merge(&mut self, other: &Self) {
+        self.owned += other.owned;
+        self.reserved += other.reserved;
+        self.saved += other.saved;
+        self.expended += other.expended;
+        self.used += other.used;
+        self.traded += other.traded;
+        self.offered += other.offered;
+        self.target += other.target;
+        self.planned += other.planned;
+    }
+
     /// # Current Target
     /// 
     /// How many more goods we need to reach our target.
@@ -1920,6 +3198,43 @@ impl WantRecord {
         }
     }
     
+    /// # Reset
+    /// 
+    /// Resets everything but owned to zero.
+    pub fn reset(&mut self) {
+        self.expected = 0.0;
+        self.expended = 0.0;
+        self.reserved = 0.0;
+    }
+
+    /// # Split
+    /// 
+    /// Splits off fraction (0.0 to 1.0) of the record, returning the part split
+    /// off and leaving the rest.
+    pub fn split(&mut self, fraction: f64) -> Self {
+        let taken = Self {
+            owned: self.owned * fraction,
+            reserved: self.reserved * fraction,
+            expected: self.expected * fraction,
+            expended: self.expended * fraction,
+        };
+        self.owned -= taken.owned;
+        self.reserved -= taken.reserved;
+        self.expected -= taken.expected;
+        self.expended -= taken.expended;
+        taken
+    }
+
+    /// # Merge
+    /// 
+    /// Adds other's record into ours.
+    pub fn merge(&mut self, other: &Self) {
+        self.owned += other.owned;
+        self.reserved += other.reserved;
+        self.expected += other.expected;
+        self.expended += other.expended;
+    }
+
     /// # Available 
     /// 
     /// How many wants are available for planning purposes.
@@ -1933,6 +3248,45 @@ impl WantRecord {
     }
 }
 
+/// # Growth Report
+/// 
+/// The births and deaths in a pop over a turn, in people, not households.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct GrowthReport {
+    /// The number of people born.
+    pub births: f64,
+    /// The number of people who died.
+    pub deaths: f64,
+    /// How many of those deaths were caused by each unmet LifeNeed desire, 
+    /// so a famine shows up as deaths from a lack of food.
+    pub causes: HashMap<Item, f64>,
+    /// What the dead left behind and who got it.
+    pub inheritance: Option<Inheritance>,
+}
+
+impl GrowthReport {
+    pub fn new() -> Self {
+        Self {
+            births: 0.0,
+            deaths: 0.0,
+            causes: HashMap::new(),
+            inheritance: None,
+        }
+    }
+
+    /// # Net
+    /// 
+    /// The net change in population, births - deaths.
+    pub fn net(&self) -> f64 {
+        self.births - self.deaths
+    }
+}
+
+/// # Value Range
+/// 
+/// A range of values along a desire's curve, from where it starts to where it ends.
+pub type ValueRange = (f64, f64);
+
 /// # Satsifaction Values
 /// 
 /// A helper storage unit which stores the value satisfied, number of steps satisfied,
@@ -1945,33 +3299,56 @@ impl WantRecord {
 /// Satisfaction is an accululation of all units dedicated to satisfying to desires.
 /// 
 /// Excess AMV is all the rest of the goods, liquidated into their AMV value.
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Clone)]
 pub struct SatisfactionValues {
     /// The total value of all desires as satisfied right now.
     pub value: f64,
     /// The number of steps (desire.satisfaction / desire.amount), completed.
     pub steps: f64,
+    /// The steps completed in units of satisfaction, weighted by each desire's
+    /// demand curve. See Desire::normalized_satisfaction().
+    /// 
+    /// Unlike steps, this isn't skewed by desires with small amounts, so it's 
+    /// what should be used when comparing satisfaction.
+    pub normalized: f64,
     /// The total amount of satisfaction in raw units.
     pub satisfaction: f64,
     /// The AMV of goods which didn't go into desires.
     pub excess_amv: f64,
-    // TODO: Add in the ranges satisfied below for more precise and useful internal valuation calculations.
-    // The ranges the satisfaction covers.
-    //pub ranges: Vec<(f64, f64)>,
+    /// The value ranges the satisfaction covers, one per desire, going from 
+    /// where the satisfaction starts on the desire's curve to where it ends.
+    /// 
+    /// For changes in satisfaction, these are the ranges gained.
+    pub ranges: Vec<ValueRange>,
+    /// The value ranges lost in a change in satisfaction, in the same order as
+    /// ranges. Always empty outside of changes.
+    pub lost_ranges: Vec<ValueRange>,
 }
 
 impl SatisfactionValues {
-    pub fn new(value: f64, steps: f64, satisfaction: f64, excess_amv: f64) -> Self {
+    pub fn new(value: f64, steps: f64, normalized: f64, satisfaction: f64, excess_amv: f64) -> Self {
         Self {
             value,
             steps,
+            normalized,
             satisfaction,
-            excess_amv
+            excess_amv,
+            ranges: vec![],
+            lost_ranges: vec![],
         }
     }
 
+    /// # With Ranges
+    /// 
+    /// Sets the ranges gained and lost. Consumes the original.
+    pub fn with_ranges(mut self, ranges: Vec<ValueRange>, lost_ranges: Vec<ValueRange>) -> Self {
+        self.ranges = ranges;
+        self.lost_ranges = lost_ranges;
+        self
+    }
+
     fn zero() -> SatisfactionValues {
-        Self::new(0.0, 0.0, 0.0, 0.0)
+        Self::new(0.0, 0.0, 0.0, 0.0, 0.0)
     }
 }
 
@@ -1979,9 +3356,11 @@ impl SatisfactionValues {
 /// 
 /// Tags a population can have. These tags apply to the entire population, regardless of the household.
 /// These tags can be inherited from the households within if they are cohiesive.
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
 pub enum PopTag {
     /// Population is a slave. It cannot sell anything it has and anything it owns but
-    /// doesn't need is immediately either scrapped to the tile, or handed over to the owner.
+    /// doesn't need is handed over to the owner at the end of the day.
+    /// 
+    /// See Pop::enslave() and Pop::manumit().
     Slave
 }
\ No newline at end of file
@@ -1,5 +1,7 @@
 use circular_buffer::CircularBuffer;
 
+use crate::constants::{MOOD_ADJUSTMENT_RATE, POP_AMV_HARD_LOSS_THRESHOLD, POP_BASE_SAVING_RATE, POP_SAVING_RATE_CAP};
+
 /// # Pop Financials
 /// 
 /// Pop financials is a helper which stores the financial information of a pop.
@@ -122,8 +124,12 @@ pub struct PopFinancials {
     // Plans Section
 
     /// The baseline savings ratio the pop seeks. Defined primarily by demographics.
+    /// 
+    /// POP_BASE_SAVING_RATE by default.
     pub base_saving_rate: f64,
     /// The Savings rate Cap, (hard cap of 1.0), this is based on Demographics also
+    /// 
+    /// POP_SAVING_RATE_CAP by default.
     pub saving_rate_cap: f64,
     /// The current active savings rate of the pop. It can shift up and down over time.
     pub curr_saving_rate: f64,
@@ -135,6 +141,11 @@ pub struct PopFinancials {
     /// The current rate of investment as has moved over time.
     pub current_investment_rate: f64,
 
+    /// How many days ahead the pop plans to stockpile goods for it's desires.
+    /// 
+    /// 0 means the pop only looks at today.
+    pub stockpile_days: usize,
+
     /// The baseline interest rate for the pop. Primarily defined by demographics.
     pub base_interest_rate: f64,
     /// The current cap on interest rate, this cap is a lower bound rather than an 
@@ -169,12 +180,13 @@ impl PopFinancials {
             uncertainty: 0.0,
             risk_tolerance: 0.0,
             time_preference: 0.0,
-            base_saving_rate: 0.0,
-            saving_rate_cap: 0.0,
-            curr_saving_rate: 0.0,
+            base_saving_rate: POP_BASE_SAVING_RATE,
+            saving_rate_cap: POP_SAVING_RATE_CAP,
+            curr_saving_rate: POP_BASE_SAVING_RATE,
             base_investment_rate: 0.0,
             investment_rate_cap: 0.0,
             current_investment_rate: 0.0,
+            stockpile_days: 0,
             base_interest_rate: 0.0,
             interest_rate_cap: 0.0,
             current_interest_rate: 0.0,
@@ -193,13 +205,14 @@ impl PopFinancials {
     /// 
     /// Takes the current wealth history and calculates the new average.
     /// 
-    /// Should be called at the start of the day, which should be before goods have
-    /// decayed.
+    /// Should be called after the day's starting wealth, which is taken before goods
+    /// have decayed, has been pushed into the history.
     /// 
     /// It should include skills and time.
     pub fn update_average_wealth(&mut self) -> f64 {
-        let res = self.wealth_history.iter().sum();
+        let res = Self::average(&self.wealth_history);
         self.average_wealth = res;
+        self.wealth_inertia = Self::trend(&self.wealth_history);
         res
     }
 
@@ -215,8 +228,9 @@ impl PopFinancials {
     /// 
     /// NOTE: Should take into account wages recieved only every few days eventually.
     pub fn update_average_income(&mut self) -> f64 {
-        let res = self.income_history.iter().sum();
+        let res = Self::average(&self.income_history);
         self.average_income = res;
+        self.income_inertia = Self::trend(&self.income_history);
         res
     }
 
@@ -228,8 +242,201 @@ impl PopFinancials {
     /// 
     /// NOTE: Should eventually take into account dividends recieved only every few days.
     pub fn update_average_dividend(&mut self) -> f64 {
-        let res = self.dividend_history.iter().sum();
+        let res = Self::average(&self.dividend_history);
         self.average_dividend = res;
+        self.dividend_inertia = Self::trend(&self.dividend_history);
         res
     }
+
+    /// # Update Mood
+    /// 
+    /// Moves the pop's financial mood towards where it's situation pushes it, 
+    /// then updates the current saving, investment, and interest rates to match.
+    /// 
+    /// Price volatility is that of the goods the pop holds, and shortfall is the
+    /// portion (0.0 to 1.0) of the pop's desires left unsatisfied.
+    /// 
+    /// Each mood moves MOOD_ADJUSTMENT_RATE of the way to it's target each call.
+    /// 
+    /// - Uncertainty targets the volatility of wealth and income, plus price 
+    ///   volatility and shortfall.
+    /// - Risk Tolerance targets 0.5 for a satisfied pop with steady wealth, 
+    ///   falling with shortfall and rising or falling with wealth inertia.
+    /// - Time Preference targets the base interest rate plus shortfall, pops 
+    ///   which can't meet their needs today care less about tomorrow.
+    /// 
+    /// Uncertainty pushes saving and interest up and investment down, risk 
+    /// tolerance does the opposite. Each rate is kept between 0.0 and it's cap,
+    /// except interest, which can't go below it's cap.
+    pub fn update_mood(&mut self, price_volatility: f64, shortfall: f64) {
+        let uncertainty = Self::volatility(&self.wealth_history) + 
+            Self::volatility(&self.income_history) + price_volatility + shortfall;
+        let risk_tolerance = (0.5 * (1.0 - shortfall) + self.wealth_inertia).max(0.0);
+        let time_preference = self.base_interest_rate + shortfall;
+        self.uncertainty += (uncertainty - self.uncertainty) * MOOD_ADJUSTMENT_RATE;
+        self.risk_tolerance += (risk_tolerance - self.risk_tolerance) * MOOD_ADJUSTMENT_RATE;
+        self.time_preference += (time_preference - self.time_preference) * MOOD_ADJUSTMENT_RATE;
+
+        let caution = self.uncertainty - self.risk_tolerance;
+        self.curr_saving_rate = (self.base_saving_rate * (1.0 + caution))
+            .clamp(0.0, self.saving_rate_cap.clamp(0.0, 1.0));
+        self.current_investment_rate = (self.base_investment_rate * (1.0 - caution))
+            .clamp(0.0, self.investment_rate_cap.max(0.0));
+        self.current_interest_rate = (self.time_preference + caution.max(0.0))
+            .max(self.interest_rate_cap);
+    }
+
+    /// # Hard Loss Threshold
+    /// 
+    /// The least AMV a pop will accept in a trade, relative to what it gives up.
+    /// 
+    /// POP_AMV_HARD_LOSS_THRESHOLD, raised by uncertainty and lowered by risk
+    /// tolerance, between 0.0 and 1.0.
+    pub fn hard_loss_threshold(&self) -> f64 {
+        (POP_AMV_HARD_LOSS_THRESHOLD * (1.0 + self.uncertainty - self.risk_tolerance))
+            .clamp(0.0, 1.0)
+    }
+
+    /// # Split
+    /// 
+    /// Splits off fraction (0.0 to 1.0) of the financials, returning the part
+    /// split off and leaving the rest.
+    /// 
+    /// Amounts of AMV (current values, histories, and averages) are divided 
+    /// between the two, while trends, mood, and plans are shared by both.
+    pub fn split(&mut self, fraction: f64) -> Self {
+        let mut taken = self.clone();
+        taken.scale(fraction);
+        self.scale(1.0 - fraction);
+        taken
+    }
+
+    /// # Merge
+    /// 
+    /// Merges other's financials into ours.
+    /// 
+    /// Amounts of AMV are added together, histories by day, newest to oldest.
+    /// Trends, mood, and plans are averaged, with weight (0.0 to 1.0) given to
+    /// other's. Stockpile days are the longer of the two.
+    pub fn merge(&mut self, other: &Self, weight: f64) {
+        self.wealth += other.wealth;
+        self.income += other.income;
+        self.dividends += other.dividends;
+        self.current_wealth += other.current_wealth;
+        self.consumed += other.consumed;
+        self.saved += other.saved;
+        self.invested += other.invested;
+        self.amv_end += other.amv_end;
+        self.decay += other.decay;
+        Self::merge_history(&mut self.wealth_history, &other.wealth_history);
+        Self::merge_history(&mut self.income_history, &other.income_history);
+        Self::merge_history(&mut self.dividend_history, &other.dividend_history);
+        self.average_wealth += other.average_wealth;
+        self.average_income += other.average_income;
+        self.average_dividend += other.average_dividend;
+        let mix = |ours: &mut f64, theirs: f64| *ours += weight * (theirs - *ours);
+        mix(&mut self.wealth_inertia, other.wealth_inertia);
+        mix(&mut self.income_inertia, other.income_inertia);
+        mix(&mut self.dividend_inertia, other.dividend_inertia);
+        mix(&mut self.uncertainty, other.uncertainty);
+        mix(&mut self.risk_tolerance, other.risk_tolerance);
+        mix(&mut self.time_preference, other.time_preference);
+        mix(&mut self.base_saving_rate, other.base_saving_rate);
+        mix(&mut self.saving_rate_cap, other.saving_rate_cap);
+        mix(&mut self.curr_saving_rate, other.curr_saving_rate);
+        mix(&mut self.base_investment_rate, other.base_investment_rate);
+        mix(&mut self.investment_rate_cap, other.investment_rate_cap);
+        mix(&mut self.current_investment_rate, other.current_investment_rate);
+        mix(&mut self.base_interest_rate, other.base_interest_rate);
+        mix(&mut self.interest_rate_cap, other.interest_rate_cap);
+        mix(&mut self.current_interest_rate, other.current_interest_rate);
+        self.stockpile_days = self.stockpile_days.max(other.stockpile_days);
+    }
+
+    /// # Scale
+    /// 
+    /// Multiplies all amounts of AMV by factor.
+    fn scale(&mut self, factor: f64) {
+        self.wealth *= factor;
+        self.income *= factor;
+        self.dividends *= factor;
+        self.current_wealth *= factor;
+        self.consumed *= factor;
+        self.saved *= factor;
+        self.invested *= factor;
+        self.amv_end *= factor;
+        self.decay *= factor;
+        for history in [&mut self.wealth_history, &mut self.income_history, &mut self.dividend_history] {
+            for day in history.iter_mut() {
+                *day *= factor;
+            }
+        }
+        self.average_wealth *= factor;
+        self.average_income *= factor;
+        self.average_dividend *= factor;
+    }
+
+    /// # Merge History
+    /// 
+    /// Adds other to history day by day, lining them up by their newest day.
+    fn merge_history(history: &mut CircularBuffer<32, f64>, other: &CircularBuffer<32, f64>) {
+        let mut days: Vec<f64> = history.iter().rev().copied().collect();
+        for (idx, day) in other.iter().rev().enumerate() {
+            match days.get_mut(idx) {
+                Some(existing) => *existing += day,
+                None => days.push(*day),
+            }
+        }
+        history.clear();
+        for day in days.into_iter().rev() {
+            history.push_back(day);
+        }
+    }
+
+    /// # Volatility
+    /// 
+    /// The coefficient of variation (standard deviation / mean) of a history.
+    /// 
+    /// 0.0 if there are fewer than 2 days of history or the mean is 0.0.
+    fn volatility(history: &CircularBuffer<32, f64>) -> f64 {
+        let mean = Self::average(history);
+        if history.len() < 2 || mean == 0.0 {
+            return 0.0;
+        }
+        let variance = history.iter()
+            .map(|x| (x - mean).powi(2))
+            .sum::<f64>() / history.len() as f64;
+        variance.sqrt() / mean.abs()
+    }
+
+    /// # Trend
+    /// 
+    /// The slope of a linear regression over a history, relative to it's mean,
+    /// so 0.1 is growth of 10% of the mean per day.
+    /// 
+    /// 0.0 if there are fewer than 2 days of history or the mean is 0.0.
+    fn trend(history: &CircularBuffer<32, f64>) -> f64 {
+        let mean = Self::average(history);
+        if history.len() < 2 || mean == 0.0 {
+            return 0.0;
+        }
+        let mid = (history.len() - 1) as f64 / 2.0;
+        let mut covariance = 0.0;
+        let mut variance = 0.0;
+        for (day, value) in history.iter().enumerate() {
+            covariance += (day as f64 - mid) * (value - mean);
+            variance += (day as f64 - mid).powi(2);
+        }
+        covariance / variance / mean.abs()
+    }
+
+    /// # Average
+    /// 
+    /// The mean of a history, 0.0 if it's empty.
+    fn average(history: &CircularBuffer<32, f64>) -> f64 {
+        if history.is_empty() {
+            return 0.0;
+        }
+        history.iter().sum::<f64>() / history.len() as f64
+    }
 }
\ No newline at end of file
@@ -0,0 +1,127 @@
+use std::collections::HashMap;
+
+use crate::{constants::PRICE_EXPECTATION_RATE, markethistory::{GoodRecord, MarketHistory}};
+
+/// # Price Expectations
+/// 
+/// What a pop expects goods to be worth, rather than taking the market's price
+/// as given.
+/// 
+/// A pop starts out expecting the market price, shifted by a small amount of 
+/// noise unique to it and the good if it's given any (see with_noise()), then 
+/// updates it's expectations from the trades it sees according to it's rule.
+#[derive(Debug, Clone)]
+pub struct PriceExpectations {
+    /// How expectations are updated from what the pop sees.
+    pub rule: ExpectationRule,
+    /// The price the pop expects each good to have. Goods not in here are 
+    /// expected to be at their starting price, see starting_price().
+    pub prices: HashMap<usize, f64>,
+    /// The change in price the pop expects each day, used by TrendFollowing.
+    pub trends: HashMap<usize, f64>,
+    /// How far (as a fraction of the market price) starting prices can be off.
+    /// 
+    /// 0.0 by default, making the pop a price taker until it sees trades.
+    pub noise: f64,
+    /// The seed for the noise, typically the pop's id.
+    pub seed: usize,
+}
+
+impl PriceExpectations {
+    pub fn new(seed: usize) -> Self {
+        Self {
+            rule: ExpectationRule::Adaptive { rate: PRICE_EXPECTATION_RATE },
+            prices: HashMap::new(),
+            trends: HashMap::new(),
+            noise: 0.0,
+            seed,
+        }
+    }
+
+    /// # With Rule
+    /// 
+    /// Sets the rule used to update expectations.
+    pub fn with_rule(mut self, rule: ExpectationRule) -> Self {
+        self.rule = rule;
+        self
+    }
+
+    /// # With Noise
+    /// 
+    /// Sets how far off starting prices can be, PRICE_EXPECTATION_NOISE is 
+    /// typical. 0.0 makes the pop start as a price taker.
+    pub fn with_noise(mut self, noise: f64) -> Self {
+        assert!((0.0..1.0).contains(&noise), "Noise must be between 0.0 and 1.0.");
+        self.noise = noise;
+        self
+    }
+
+    /// # Expected
+    /// 
+    /// The price the pop expects for a good.
+    pub fn expected(&self, good: usize, market: &MarketHistory) -> f64 {
+        match self.prices.get(&good) {
+            Some(price) => *price,
+            None => self.starting_price(good, market),
+        }
+    }
+
+    /// # Starting Price
+    /// 
+    /// The market price, shifted by up to noise in either direction. The shift
+    /// is always the same for the same seed and good.
+    /// 
+    /// Goods the market has no record of start at the price of a new record.
+    pub fn starting_price(&self, good: usize, market: &MarketHistory) -> f64 {
+        let price = market.good_records.get(&good)
+            .map_or(GoodRecord::new().price, |x| x.price);
+        price * (1.0 + self.noise * Self::noise_for(self.seed, good))
+    }
+
+    /// # Observe
+    /// 
+    /// Updates the expectation of a good from a price the pop saw it trade at.
+    /// 
+    /// - Adaptive moves the expectation `rate` of the way to the price seen.
+    /// - TrendFollowing does the same, but also tracks the change it's seeing
+    ///   (smoothed by `rate`) and adds `weight` of that trend on top, 
+    ///   expecting rising prices to keep rising.
+    /// 
+    /// Expectations never go below 0.0.
+    pub fn observe(&mut self, good: usize, price: f64, market: &MarketHistory) {
+        let old = self.expected(good, market);
+        let new = match self.rule {
+            ExpectationRule::Adaptive { rate } => old + rate * (price - old),
+            ExpectationRule::TrendFollowing { rate, weight } => {
+                let trend = self.trends.entry(good).or_insert(0.0);
+                *trend += rate * ((price - old) - *trend);
+                old + rate * (price - old) + weight * *trend
+            },
+        };
+        self.prices.insert(good, new.max(0.0));
+    }
+
+    /// # Noise For
+    /// 
+    /// A deterministic value between -1.0 and 1.0 for the seed and good, so 
+    /// pops don't all start with the same expectations.
+    fn noise_for(seed: usize, good: usize) -> f64 {
+        // splitmix64
+        let mut x = ((seed as u64) << 32 ^ good as u64).wrapping_add(0x9E3779B97F4A7C15);
+        x = (x ^ (x >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+        x = (x ^ (x >> 27)).wrapping_mul(0x94D049BB133111EB);
+        x ^= x >> 31;
+        (x as f64 / u64::MAX as f64) * 2.0 - 1.0
+    }
+}
+
+/// # Expectation Rule
+/// 
+/// How a pop updates it's price expectations from what it sees.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ExpectationRule {
+    /// Moves `rate` (0.0 to 1.0) of the way towards each price seen.
+    Adaptive { rate: f64 },
+    /// Adaptive, plus `weight` times the smoothed change in prices seen.
+    TrendFollowing { rate: f64, weight: f64 },
+}
@@ -36,7 +36,7 @@ use crate::{data::Data, item::Item, markethistory::MarketHistory};
 /// 
 /// - Innate Complexity Bonus, For a ruleset that is ignoring mas conservation, it's an 
 /// easy addition. However, I would rather, 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct Process {
     /// The unique id of the process.
     pub id: usize,
@@ -554,7 +554,7 @@ impl Process {
 /// Currently, only Goods are accepted by processes.
 ///
 /// Eventually I would like to expand this to include Classes and Wants.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct ProcessInput {
     /// The Good that is being used.
     pub good: usize,
@@ -629,7 +629,7 @@ impl Display for InputTag {
 /// # Process Output
 ///
 /// The information about process outputs.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct ProcessOutput {
     /// The item being output.
     /// 
@@ -692,13 +692,13 @@ impl ProcessResults {
 /// Adds special information to the outputs of a process part.
 ///
 /// Currently used for sanity checking outputs.
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum OutputTag {
     ConsumedOutput,
     UsedOutput
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum ProcessTag {
     /// Marks a process as it should always use the mass conservation logic, even if 
     /// game configuration says otherwise.
@@ -1,4 +1,4 @@
-use crate::{desire::Desire, household::{Household, HouseholdMod}};
+use crate::{desire::Desire, household::{CohortAging, Household, HouseholdMod}};
 
 /// # Species
 /// 
@@ -25,6 +25,8 @@ pub struct Species {
     /// 
     /// Assume this is being added to a house with no members at all.
     pub household_mod: HouseholdMod,
+    /// How the species' households age between cohorts, if they do at all.
+    pub aging: Option<CohortAging>,
 
     /// The desires that the species biologically needs or wants.
     /// Desires are sorted by their starting value, lowest to highest.
@@ -41,6 +43,7 @@ impl Species {
             id,
             name,
             household_mod: HouseholdMod::default_household(),
+            aging: None,
             desires: vec![],
         }
     }
@@ -53,6 +56,14 @@ impl Species {
         self
     }
 
+    /// # With Aging
+    /// 
+    /// Gives the species cohort aging.
+    pub fn with_aging(mut self, aging: CohortAging) -> Self {
+        self.aging = Some(aging);
+        self
+    }
+
     /// # With Desire
     /// 
     /// Inserts desire into proper place.
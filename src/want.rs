@@ -98,6 +98,30 @@ impl Want {
         self
     }
 
+    /// # Sources
+    /// 
+    /// Gets the goods which produce this want via the given method.
+    /// 
+    /// These are maintained by Data, don't edit them directly.
+    pub fn sources(&self, source: WantSource) -> &[usize] {
+        match source {
+            WantSource::Ownership => &self.ownership_sources,
+            WantSource::Use => &self.use_sources,
+            WantSource::Consumption => &self.consumption_sources,
+        }
+    }
+
+    /// # Sources Mut
+    /// 
+    /// Mutable version of sources, only for Data to keep it's indices in sync.
+    pub(crate) fn sources_mut(&mut self, source: WantSource) -> &mut Vec<usize> {
+        match source {
+            WantSource::Ownership => &mut self.ownership_sources,
+            WantSource::Use => &mut self.use_sources,
+            WantSource::Consumption => &mut self.consumption_sources,
+        }
+    }
+
     /// # Decay
     /// 
     /// Decays the given (start) quantity into the outputted result
@@ -118,6 +142,27 @@ impl Want {
     }
 }
 
+/// # Want Source
+/// 
+/// The ways a good can produce a want.
+/// 
+/// Ordered in the same order pops try to satisfy wants, Ownership, then Use, 
+/// then Consumption.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum WantSource {
+    /// Produced by just owning the good.
+    Ownership,
+    /// Produced by using the good, costs time.
+    Use,
+    /// Produced by consuming the good, costs time.
+    Consumption,
+}
+
+impl WantSource {
+    /// All sources in satisfaction order.
+    pub const ALL: [WantSource; 3] = [WantSource::Ownership, WantSource::Use, WantSource::Consumption];
+}
+
 /// # Want Effects
 /// 
 /// Currently a placeholder.
@@ -1,6 +1,8 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
-use crate::{market::Market, pop::Pop};
+use itertools::Itertools;
+
+use crate::{data::{Data, DataReload, ReloadReport}, firm::Firm, inheritance::{Heir, Inheritance}, item::Item, market::Market, markethistory::MarketHistory, pop::{GrowthReport, Pop}};
 
 
 
@@ -12,4 +14,191 @@ pub struct World {
     pub markets: HashMap<usize, Market>,
     /// All pops in the world currently.
     pub pops: HashMap<usize, Pop>,
-}
\ No newline at end of file
+    /// All firms in the world currently.
+    pub firms: HashMap<usize, Firm>,
+}
+
+impl World {
+    /// # Reload Data
+    /// 
+    /// Reloads changed definitions into data while the world is running, then
+    /// brings everything which depends on them up to date.
+    /// 
+    /// Pops with a species, culture, or layer whose desires changed have their
+    /// desires rebuilt with Pop::update_desires_full(). If any goods changed, every 
+    /// market's good trade priority is recalculated.
+    /// 
+    /// Returns the report from Data::reload(), with the pops and markets updated.
+    pub fn reload_data(&mut self, data: &mut Data, reload: DataReload) -> ReloadReport {
+        let mut report = data.reload(reload);
+        if report.any_desires_changed() {
+            for pop in self.pops.values_mut().sorted_by_key(|x| x.id) {
+                let affected = pop.demo_breakdown.iter()
+                    .any(|row| report.species_changed.contains(&row.species) ||
+                        row.culture.is_some_and(|x| report.cultures_changed.contains(&x)) ||
+                        row.layers.iter().any(|x| report.layers_changed.contains(x)));
+                if affected {
+                    pop.update_desires_full(data);
+                    report.pops_rebuilt.push(pop.id);
+                }
+            }
+        }
+        if report.any_goods_changed() {
+            for market in self.markets.values_mut().sorted_by_key(|x| x.id) {
+                market.update_good_trade_priority(data);
+                report.markets_refreshed.push(market.id);
+            }
+        }
+        report
+    }
+
+    /// # Update Positional Desires
+    /// 
+    /// Re-evaluates the positional desires of every pop against the other pops
+    /// in their market. Should be called once a day.
+    /// 
+    /// For each market, the per person holdings of each positional item are 
+    /// gathered from every pop there, then each pop is compared against all 
+    /// pops but itself.
+    pub fn update_positional_desires(&mut self, data: &Data) {
+        for market in self.markets.values().sorted_by_key(|x| x.id) {
+            let pop_ids = market.pops.iter().copied()
+                .filter(|x| self.pops.contains_key(x))
+                .sorted().collect_vec();
+            let items: HashSet<Item> = pop_ids.iter()
+                .flat_map(|id| self.pops[id].positional_items())
+                .map(|(item, _)| item)
+                .collect();
+            if items.is_empty() {
+                continue;
+            }
+            let holdings: HashMap<usize, HashMap<Item, f64>> = pop_ids.iter()
+                .map(|id| {
+                    let pop = &self.pops[id];
+                    (*id, items.iter().map(|item| (*item, pop.holdings_per_person(*item, data))).collect())
+                })
+                .collect();
+            for id in pop_ids.iter() {
+                let others: HashMap<Item, Vec<f64>> = items.iter()
+                    .map(|item| (*item, pop_ids.iter()
+                        .filter(|other| *other != id && self.pops[*other].households.population() > 0.0)
+                        .map(|other| holdings[other][item])
+                        .collect()))
+                    .collect();
+                self.pops.get_mut(id).unwrap().update_positional_desires(&others);
+            }
+        }
+    }
+
+    /// # Observe Trade
+    /// 
+    /// Every pop in the market sees a trade of request for offer and updates 
+    /// it's price expectations, see Pop::observe_trade().
+    /// 
+    /// The traders themselves are skipped, as they learn from it in 
+    /// Pop::complete_trade().
+    pub fn observe_trade(&mut self, market: usize, traders: &[usize], 
+    request: &HashMap<usize, f64>, offer: &HashMap<usize, f64>, history: &MarketHistory) {
+        let Some(market) = self.markets.get(&market) else {
+            return;
+        };
+        for id in market.pops.iter().filter(|x| !traders.contains(x)) {
+            if let Some(pop) = self.pops.get_mut(id) {
+                pop.observe_trade(request, offer, history);
+            }
+        }
+    }
+
+    /// # Day End
+    /// 
+    /// Ends the day of every pop, see Pop::day_end(). Deaths from the day
+    /// are applied after, by apply_growth().
+    /// 
+    /// histories holds the market history of each market by it's id.
+    /// 
+    /// Slaves hand their excess to the firm they work for.
+    /// 
+    /// Returns the pops which failed, and why. Pops with no history for their
+    /// market are skipped.
+    pub fn day_end(&mut self, data: &Data, histories: &HashMap<usize, MarketHistory>) 
+    -> Vec<(usize, String)> {
+        let mut failed = vec![];
+        for pop in self.pops.values_mut().sorted_by_key(|x| x.id) {
+            let Some(history) = histories.get(&pop.market) else {
+                failed.push((pop.id, format!("Market {} has no history.", pop.market)));
+                continue;
+            };
+            let owner = if pop.is_slave() { self.firms.get_mut(&pop.firm) } else { None };
+            pop.day_end(data, owner, history);
+        }
+        failed
+    }
+
+    /// # Apply Growth
+    /// 
+    /// Applies a turn of births and deaths to every pop, see Pop::apply_growth(),
+    /// then settles the inheritance of any which died out.
+    /// 
+    /// Returns each pop's births and deaths by the pop's id, and the pops 
+    /// whose inheritance couldn't be settled, and why. Those pops keep their 
+    /// property.
+    pub fn apply_growth(&mut self, data: &Data) -> (HashMap<usize, GrowthReport>, Vec<(usize, String)>) {
+        let reports: HashMap<usize, GrowthReport> = self.pops.values_mut()
+            .map(|pop| (pop.id, pop.apply_growth(data)))
+            .collect();
+        let failed = self.settle_inheritances(reports.values()
+            .filter_map(|x| x.inheritance.as_ref()));
+        (reports, failed)
+    }
+
+    /// # Settle Inheritances
+    /// 
+    /// Settles each inheritance in order of pop id, see settle_inheritance().
+    /// 
+    /// Returns the pops whose inheritance failed, and why.
+    fn settle_inheritances<'a>(&mut self, inheritances: impl Iterator<Item = &'a Inheritance>) -> Vec<(usize, String)> {
+        inheritances.sorted_by_key(|x| x.pop)
+            .filter_map(|x| self.settle_inheritance(x).err().map(|err| (x.pop, err)))
+            .collect()
+    }
+
+    /// # Settle Inheritance
+    /// 
+    /// Moves inherited goods from the pop to their heir.
+    /// 
+    /// Survivors already hold what they inherit, so nothing moves. A market 
+    /// adds the goods to it's unclaimed goods, and a firm to it's property. If
+    /// the heir firm no longer exists, the pop's market inherits instead.
+    /// 
+    /// # Errors
+    /// 
+    /// If the pop or market do not exist, nothing is moved.
+    pub fn settle_inheritance(&mut self, inheritance: &Inheritance) -> Result<(), String> {
+        if inheritance.heir == Heir::Survivors {
+            return Ok(());
+        }
+        let Some(pop) = self.pops.get_mut(&inheritance.pop) else {
+            return Err(format!("Pop {} does not exist.", inheritance.pop));
+        };
+        let heir_property = match inheritance.heir {
+            Heir::Firm(id) if self.firms.contains_key(&id) => 
+                &mut self.firms.get_mut(&id).unwrap().property,
+            _ => match self.markets.get_mut(&inheritance.market) {
+                Some(market) => &mut market.unclaimed,
+                None => return Err(format!("Market {} does not exist.", inheritance.market)),
+            },
+        };
+        for (&good, &amount) in inheritance.goods.iter() {
+            let Some(record) = pop.property.get_mut(&good) else {
+                continue;
+            };
+            let moved = amount.min(record.owned);
+            record.owned -= moved;
+            *heir_property.entry(good).or_insert(0.0) += moved;
+        }
+        // only drop records left with nothing in them.
+        pop.property.retain(|_, x| x.owned != 0.0 || x.reserved != 0.0 || 
+            x.target != 0.0 || x.saved != 0.0);
+        Ok(())
+    }
+}